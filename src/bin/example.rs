@@ -3,7 +3,7 @@ use std::{
     time::{Duration, Instant}
 };
 use minifb::{Key, Window, WindowOptions};
-use plane_drawer::{Angle, Cam, CamData, Color, Pos, Vec2d, D1, D2, D3};
+use plane_drawer::{clear_depth_buffer, new_depth_buffer, Angle, Cam, CamData, Color, DrawPlane, Pos, Presenter, Primitive, ScaleFilter, Scene, Shape2D, Vec2d, D1, D2, D3};
 
 const DEFAULT_DIMENSIONS: (usize, usize) = (512, 512);
 const FPS: usize = 50;
@@ -15,6 +15,10 @@ fn main() {
     let mut winsize = (DEFAULT_DIMENSIONS.0, DEFAULT_DIMENSIONS.1);
     let mut cam_data = CamData::new(winsize, 1);
     let mut window_buf = Vec2d::new_with_fill(winsize, |_| 0x00_00_00_00);
+    // everything is drawn at buffsize and scaled up to the window, press Z and X to change the resolution
+    let mut presenter = Presenter::new(&cam_data, ScaleFilter::Nearest);
+    // lets planes be drawn in any order, higher planes always end up on top
+    let mut depth_buf = new_depth_buffer(cam_data.buffsize());
 
     let mut window = Window::new(
         "Example",
@@ -31,6 +35,16 @@ fn main() {
     window.set_background_color(50, 50, 50);
     window.set_cursor_visibility(true);
 
+    // planes that never change can live in a scene, which sorts them by height every time it renders.
+    // These are pushed top first on purpose, the floor still ends up underneath
+    let mut scene = Scene::new();
+    let mut pad = DrawPlane::new(Pos::from((D3::from((-30, 0, -10)), Angle::zero())));
+    pad.push(Primitive::FilledShape(Shape2D::Circle((D2::zero(), D1::from(8))), Color::dark_green()));
+    scene.push_plane(pad);
+    let mut floor = DrawPlane::new(Pos::from((D3::from((0, 0, -20)), Angle::zero())));
+    floor.push(Primitive::FilledShape(Shape2D::Rect((D2::from((-50, -30)), D2::from((50, 30)))), Color::gray()));
+    scene.push_plane(floor);
+
     let rerender = true;
     let mut time_fuel = 0u128;
     // call `Instant::now` as the last thing before the loop
@@ -46,13 +60,20 @@ fn main() {
             let new_winsize = cam_data.winsize();
             window_buf = Vec2d::new_with_fill(new_winsize, |_| 0x00_00_00_00);
         }
+        // also catches resolution changes
+        if presenter.resize(&cam_data) {
+            depth_buf = new_depth_buffer(cam_data.buffsize());
+        }
 
 
         // after setting background color, we can draw on the buffer
-        window_buf.get_mut_flat1().fill(Color::new_from_u8((80, 50, 80)).to_u32());
+        presenter.buffer.get_mut_flat1().fill(Color::new_from_u8((80, 50, 80)).to_u32());
+        clear_depth_buffer(&mut depth_buf);
+        scene.render(&cam_data, &mut presenter.buffer);
         // --------------------------------------------------------------
         // draw relative to world origin
-        if let Some(mut cam) = Cam::new_rel_to_pos(&mut window_buf, &cam_data, Pos::zero(), 255) {
+        if let Some(cam) = Cam::new_rel_to_pos(&mut presenter.buffer, &cam_data, Pos::zero(), 255) {
+            let mut cam = cam.with_depth_buffer(&mut depth_buf);
             cam.draw_circle(D2::zero(), D1::from(5), Color::ultramarine_blue());
             cam.draw_circle(D2::from((10, 5)), D1::from(5), Color::ultramarine_blue());
             cam.draw_line((D2::zero(), D2::from((-10, 10))), Color::ultramarine_blue());
@@ -61,15 +82,18 @@ fn main() {
         }
 
         // draw relative to (20, 20, 50) with a 1/4 pi angle
-        if let Some(mut cam) = Cam::new_rel_to_pos(&mut window_buf, &cam_data, Pos::from((D3::from((20, 20, 50)), Angle::one_fourth_pi())), 255) {
+        if let Some(cam) = Cam::new_rel_to_pos(&mut presenter.buffer, &cam_data, Pos::from((D3::from((20, 20, 50)), Angle::one_fourth_pi())), 255) {
+            let mut cam = cam.with_depth_buffer(&mut depth_buf);
             cam.draw_circle(D2::zero(), D1::from(5), Color::red());
             cam.draw_circle(D2::from((10, 5)), D1::from(5), Color::red());
             cam.draw_line((D2::zero(), D2::from((-10, 10))), Color::red());
             cam.draw_rect_corners((D2::from((-5, -5)), D2::from((5, -10))), Color::red());
             cam.draw_wide_pixel(D2::from((-5, -15)), 3, Color::red());
+            cam.fill_rect(D2::from((-15, 0)), D2::from((3, 3)), Angle::zero(), Color::red());
 
         }
         // --------------------------------------------------------------
+        presenter.present(&mut window_buf);
 
 
         // just some minifb updating
@@ -95,9 +119,18 @@ fn main() {
                 }
                 Key::Z => {
                     cam_data.change_resolution(false, window.get_size());
+                    cam_data.set_zoom(cam_data.target_zoom());
                 }
                 Key::X => {
                     cam_data.change_resolution(true, window.get_size());
+                    cam_data.set_zoom(cam_data.target_zoom());
+                }
+                Key::F => {
+                    presenter.filter = match presenter.filter {
+                        ScaleFilter::Nearest => ScaleFilter::Integer,
+                        ScaleFilter::Integer => ScaleFilter::Bilinear,
+                        ScaleFilter::Bilinear => ScaleFilter::Nearest,
+                    };
                 }
                 _ => {}
             }
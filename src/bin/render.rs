@@ -0,0 +1,137 @@
+//! renders a scene file to an image without opening a window
+//!
+//! usage: `render <scene file> <output image>`, the image format comes from the extension (ppm, bmp, qoi, png or svg)
+//!
+//! The scene file is a [SceneDocument] in either encoding. A small text one looks like
+//! ```text
+//! (
+//!     version: 1,
+//!     camera: (winsize: (640, 480), buffscale: 2, pos: (coords: (x: 10, y: -5, z: 0), angle: 0.3)),
+//!     background: (r: 80, g: 50, b: 80, alpha: 255),
+//!     scene: (
+//!         planes: [
+//!             (
+//!                 pos: (coords: (x: 0, y: 0, z: -10), angle: 0),
+//!                 primitives: [
+//!                     FilledShape(Circle(((x: 0, y: 0), 5)), (r: 255, g: 0, b: 0, alpha: 255)),
+//!                     Line(((x: 0, y: 0), (x: 10, y: 10)), (r: 255, g: 255, b: 255, alpha: 255)),
+//!                 ],
+//!             ),
+//!         ],
+//!     ),
+//! )
+//! ```
+
+use std::{env, process};
+
+use plane_drawer::{save_image, SceneDocument};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 {
+        eprintln!("usage: {} <scene file> <output image>", args[0]);
+        process::exit(2);
+    }
+    if let Err(e) = render(&args[1], &args[2]) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+/// renders the scene file at `scene_path` into `image_path`, the error is the message to show
+fn render(scene_path: &str, image_path: &str) -> Result<(), String> {
+    let document = SceneDocument::load(scene_path).map_err(|e| format!("could not load {}: {}", scene_path, e))?;
+    let saved = if image_path.ends_with(".svg") {
+        document.render_svg().save(image_path)
+    } else {
+        save_image(&document.render(), image_path)
+    };
+    saved.map_err(|e| format!("could not write {}: {}", image_path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    use plane_drawer::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("plane_drawer_render_{}_{}", process::id(), name))
+    }
+    fn render_paths(scene: &PathBuf, image: &PathBuf) -> Result<(), String> {
+        render(scene.to_str().unwrap(), image.to_str().unwrap())
+    }
+    /// a red circle in the middle of a 64x48 image
+    fn circle_document() -> SceneDocument {
+        let mut plane = DrawPlane::new(Pos::new(D3::from((D1::zero(), D1::zero(), D1::from(-10))), Angle::zero()));
+        plane.push(Primitive::FilledShape(Shape2D::Circle((D2::zero(), D1::from(5))), Color::new(255, 0, 0)));
+        let mut scene = Scene::new();
+        scene.push_plane(plane);
+        let mut document = SceneDocument::new(SceneCamera::new((64, 48)), scene);
+        document.background = Color::new(0, 0, 255);
+        document
+    }
+
+    #[test]
+    fn the_documented_example_renders() {
+        let source = include_str!("render.rs");
+        let start = source.find("```text\n").unwrap() + "```text\n".len();
+        let end = start + source[start..].find("//! ```").unwrap();
+        let example: String = source[start..end].lines().map(|line| format!("{}\n", line.trim_start_matches("//!"))).collect();
+        let (scene, image) = (temp_path("example.ron"), temp_path("example.ppm"));
+        fs::write(&scene, example).unwrap();
+
+        render_paths(&scene, &image).unwrap();
+        let img = load_image(&image).unwrap();
+        assert_eq!(img.len(), (640, 480));
+        assert_eq!(img.get((0, 0)), Some(&Color::new(80, 50, 80)));
+        fs::remove_file(scene).unwrap();
+        fs::remove_file(image).unwrap();
+    }
+
+    #[test]
+    fn both_encodings_render_the_same_image() {
+        let document = circle_document();
+        for (encoding, name) in [(SceneEncoding::Text, "circle.ron"), (SceneEncoding::Binary, "circle.bin")] {
+            let (scene, image) = (temp_path(name), temp_path(&format!("{}.bmp", name)));
+            document.save(&scene, encoding).unwrap();
+
+            render_paths(&scene, &image).unwrap();
+            let img = load_image(&image).unwrap();
+            assert_eq!(img.len(), (64, 48));
+            assert_eq!(img.get((32, 24)), Some(&Color::new(255, 0, 0)), "{}", name);
+            assert_eq!(img.get((0, 0)), Some(&Color::new(0, 0, 255)), "{}", name);
+            fs::remove_file(scene).unwrap();
+            fs::remove_file(image).unwrap();
+        }
+    }
+
+    #[test]
+    fn svg_output_is_picked_by_extension() {
+        let (scene, image) = (temp_path("svg.ron"), temp_path("svg.svg"));
+        circle_document().save(&scene, SceneEncoding::Text).unwrap();
+
+        render_paths(&scene, &image).unwrap();
+        let svg = fs::read_to_string(&image).unwrap();
+        assert!(svg.contains("<svg"), "{}", svg);
+        assert!(svg.contains("<circle"), "{}", svg);
+        fs::remove_file(scene).unwrap();
+        fs::remove_file(image).unwrap();
+    }
+
+    #[test]
+    fn errors_name_the_file() {
+        let missing = temp_path("missing.ron");
+        let err = render_paths(&missing, &temp_path("missing.ppm")).unwrap_err();
+        assert!(err.starts_with(&format!("could not load {}", missing.display())), "{}", err);
+
+        let scene = temp_path("errors.ron");
+        circle_document().save(&scene, SceneEncoding::Text).unwrap();
+        let image = temp_path("errors.gif");
+        let err = render_paths(&scene, &image).unwrap_err();
+        assert!(err.starts_with(&format!("could not write {}", image.display())), "{}", err);
+        fs::remove_file(scene).unwrap();
+    }
+}
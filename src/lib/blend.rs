@@ -0,0 +1,156 @@
+use serde::{Serialize, Deserialize};
+
+use crate::draw_functions::Color;
+
+/// how a color is combined with the pixel already under it. Every mode uses straight (not premultiplied)
+/// alpha for both colors except [BlendMode::PremultipliedOver], which expects a premultiplied source.
+///
+/// All of the math is done with integers so results are the same on every machine.
+/// To use a mode with the free drawing functions wrap the target in [Blended](crate::Blended)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BlendMode {
+    /// source over
+    #[default]
+    Normal,
+    /// adds the colors together, good for glows
+    Additive,
+    /// multiplies the colors, good for shadows
+    Multiply,
+    Screen,
+    Overlay,
+    /// overwrites the pixel, including its alpha
+    Replace,
+    /// source over where the source color has already been multiplied by its alpha
+    PremultipliedOver,
+}
+
+/// `a * b / 255` rounded to the nearest integer, exact for all `a, b <= 255`
+pub fn mul_255(a: u32, b: u32) -> u32 {
+    let t = a * b + 128;
+    (t + (t >> 8)) >> 8
+}
+
+/// turns a premultiplied channel scaled up by 255 * 255 back into a straight channel,
+/// dividing only once so that nothing gets rounded twice. Returns 0 if `out_a` is 0
+fn unpremultiply(premultiplied: u32, out_a: u32) -> u8 {
+    if out_a == 0 {
+        return 0
+    }
+    let div = 255 * out_a;
+    ((premultiplied + div / 2) / div).min(255) as u8
+}
+
+impl BlendMode {
+    /// returns `src` drawn on top of `dst`
+    pub fn blend(self, src: Color, dst: Color) -> Color {
+        match self {
+            BlendMode::Replace => return src,
+            BlendMode::Normal if src.alpha == 255 => return src,
+            BlendMode::PremultipliedOver | BlendMode::Additive => (),
+            _ if src.alpha == 0 => return dst,
+            _ => (),
+        }
+        let (sa, da) = (src.alpha as u32, dst.alpha as u32);
+        match self {
+            BlendMode::PremultipliedOver => {
+                let out_a = sa + mul_255(da, 255 - sa);
+                let channel = |s: u8, d: u8| unpremultiply(s as u32 * 255 * 255 + d as u32 * da * (255 - sa), out_a);
+                Color::new_with_alpha(channel(src.r, dst.r), channel(src.g, dst.g), channel(src.b, dst.b), out_a as u8)
+            }
+            BlendMode::Additive => {
+                let out_a = (sa + da).min(255);
+                let channel = |s: u8, d: u8| unpremultiply((s as u32 * sa + d as u32 * da).min(255 * 255) * 255, out_a);
+                Color::new_with_alpha(channel(src.r, dst.r), channel(src.g, dst.g), channel(src.b, dst.b), out_a as u8)
+            }
+            _ => {
+                // separable blend modes composited the same way as the w3c compositing spec
+                let out_a = sa + mul_255(da, 255 - sa);
+                let channel = |s: u8, d: u8| {
+                    let mixed = self.mix(s as u32, d as u32);
+                    let premultiplied = s as u32 * sa * (255 - da) + d as u32 * da * (255 - sa) + sa * da * mixed;
+                    unpremultiply(premultiplied, out_a)
+                };
+                Color::new_with_alpha(channel(src.r, dst.r), channel(src.g, dst.g), channel(src.b, dst.b), out_a as u8)
+            }
+        }
+    }
+
+    /// the blend function for a single channel of two opaque colors
+    fn mix(self, s: u32, d: u32) -> u32 {
+        match self {
+            BlendMode::Multiply => mul_255(s, d),
+            BlendMode::Screen => s + d - mul_255(s, d),
+            BlendMode::Overlay => {
+                if d <= 127 {
+                    mul_255(2 * s, d)
+                } else {
+                    255 - mul_255(2 * (255 - s), 255 - d)
+                }
+            }
+            _ => s,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODES: [BlendMode; 7] = [
+        BlendMode::Normal,
+        BlendMode::Additive,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Overlay,
+        BlendMode::Replace,
+        BlendMode::PremultipliedOver,
+    ];
+
+    #[test]
+    fn mul_255_is_exact() {
+        for a in 0..=255 {
+            assert_eq!(mul_255(a, 0), 0);
+            assert_eq!(mul_255(a, 255), a);
+            for b in 0..=255 {
+                assert_eq!(mul_255(a, b), (a * b * 2 + 255) / 510, "{} * {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn transparent_sources_leave_the_pixel_alone() {
+        let dst = Color::new_with_alpha(10, 200, 90, 180);
+        for mode in MODES {
+            let src = Color::new_with_alpha(0, 0, 0, 0);
+            let expected = if mode == BlendMode::Replace { src } else { dst };
+            assert_eq!(mode.blend(src, dst), expected, "{:?}", mode);
+        }
+        // straight alpha modes ignore the color of a transparent source
+        for mode in [BlendMode::Normal, BlendMode::Additive, BlendMode::Multiply, BlendMode::Screen, BlendMode::Overlay] {
+            assert_eq!(mode.blend(Color::new_with_alpha(255, 255, 255, 0), dst), dst, "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn opaque_over_opaque() {
+        let src = Color::new(200, 100, 0);
+        let dst = Color::new(100, 200, 255);
+        assert_eq!(BlendMode::Normal.blend(src, dst), src);
+        assert_eq!(BlendMode::Replace.blend(src, dst), src);
+        assert_eq!(BlendMode::PremultipliedOver.blend(src, dst), src);
+        assert_eq!(BlendMode::Additive.blend(src, dst), Color::new(255, 255, 255));
+        assert_eq!(BlendMode::Multiply.blend(src, dst), Color::new(78, 78, 0));
+        assert_eq!(BlendMode::Screen.blend(src, dst), Color::new(222, 222, 255));
+        assert_eq!(BlendMode::Overlay.blend(src, dst), Color::new(157, 188, 255));
+    }
+
+    #[test]
+    fn half_transparent_over_opaque() {
+        let dst = Color::new(0, 0, 255);
+        let src = Color::new_with_alpha(255, 0, 0, 128);
+        assert_eq!(BlendMode::Normal.blend(src, dst), Color::new(128, 0, 127));
+        let premultiplied = Color::new_with_alpha(128, 0, 0, 128);
+        assert_eq!(BlendMode::PremultipliedOver.blend(premultiplied, dst), Color::new(128, 0, 127));
+        assert_eq!(BlendMode::Additive.blend(src, dst), Color::new(128, 0, 255));
+    }
+}
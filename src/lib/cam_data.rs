@@ -11,6 +11,8 @@ pub const MIN_CAMERA_SCALE: usize = 1;
 pub const LARGEST_ZOOM_EXPONENT: i8 = 9;
 pub const SMALLEST_ZOOM_EXPONENT: i8 = -10;
 
+pub const MIN_NEAR_DIST: D1 = D1::new_const("0.01");
+
 #[derive(Debug, Clone, Copy)]
 
 
@@ -24,6 +26,7 @@ pub struct CamData {
     zoom: D1,             // equivilant to 1/(width of each tile in buffer pixels), gets bigger as cam zooms out
     pub zoom_exponent: i8,  // target zoom = 2^zoom_exponent, used to make sure our target zoom ends on an even number (1/4, 1/2, 1, 2, 4). Changing this value has no effect on actual zoom, only the target zoom level returned by target_zoom()
     view_dist: D1,          // how far the camera can see
+    near_dist: D1,          // anything closer to the camera focal point than this (in z) gets clipped
     focal_len: D1,               // how far the camera ray origin is from the screen
     focal_len_multiplier: D1,   // exists because the focal length needs to change with zoom level (when screen widens, the focal len needs to lengthen proportionally or else parralax changes as the camera zooms in and out), instead of changing focal_len directly, we change this.
     pub cam_pos_rel: Pos,       // cam relative to current surface
@@ -40,6 +43,7 @@ impl CamData {
             winsize,
             buffsize: (winsize.0 / buffscale, winsize.1 / buffscale),
             view_dist: D1::from(1000),
+            near_dist: D1::one(),
             focal_len: D1::from(100),
             focal_len_multiplier: D1::from(1000),
             cam_pos_rel: Pos::zero(),
@@ -127,6 +131,14 @@ impl CamData {
     pub fn view_dist(&self) -> D1 {
         return self.view_dist
     }
+    pub fn near_dist(&self) -> D1 {
+        self.near_dist
+    }
+    /// sets how close to the camera focal point (in z) geometry can get before it is clipped.
+    /// Clamped to at least [MIN_NEAR_DIST] because points at the focal point would divide by 0
+    pub fn set_near_dist(&mut self, near_dist: D1) {
+        self.near_dist = near_dist.max(MIN_NEAR_DIST);
+    }
 
     /// returns the zero position of the plane we want to draw to (relative to world origin).
     /// 
@@ -742,6 +742,145 @@ impl D2<D1> {
     }
 }
 
+/// bezier curves and splines on the draw plane, `t` goes from 0 at the first control point to 1 at the last
+impl D2<D1> {
+    pub fn quadratic_bezier(points: [D2<D1>; 3], t: D1) -> D2<D1> {
+        let [p0, p1, p2] = points;
+        let u = D1::one() - t;
+        p0 * D2::from(u * u) + p1 * D2::from(D1::two() * u * t) + p2 * D2::from(t * t)
+    }
+    /// direction the curve moves at `t`, its length is how far it moves per unit of `t`
+    pub fn quadratic_bezier_derivative(points: [D2<D1>; 3], t: D1) -> D2<D1> {
+        let [p0, p1, p2] = points;
+        let u = D1::one() - t;
+        ((p1 - p0) * D2::from(u) + (p2 - p1) * D2::from(t)) * D2::two()
+    }
+    /// min and max corner of the smallest box that holds the whole curve
+    pub fn quadratic_bezier_bounds(points: [D2<D1>; 3]) -> (D2<D1>, D2<D1>) {
+        let [p0, p1, p2] = points;
+        let mut ts = Vec::with_capacity(2);
+        // where the derivative of each axis is 0
+        for (a, b, c) in [(p0.x, p1.x, p2.x), (p0.y, p1.y, p2.y)] {
+            // scaled down so a tiny denominator can't blow up the divide, like cubic_bezier_bounds
+            let scale = a.abs().max(b.abs()).max(c.abs());
+            if scale.is_zero() {
+                continue
+            }
+            let (a, b, c) = (a / scale, b / scale, c / scale);
+            let denom = a - b * D1::two() + c;
+            if !denom.is_zero() {
+                ts.push((a - b) / denom);
+            }
+        }
+        bounds_at(&[p0, p2], &ts, |t| D2::quadratic_bezier(points, t))
+    }
+
+    pub fn cubic_bezier(points: [D2<D1>; 4], t: D1) -> D2<D1> {
+        let [p0, p1, p2, p3] = points;
+        let u = D1::one() - t;
+        let three = D1::from(3);
+        p0 * D2::from(u * u * u)
+            + p1 * D2::from(three * u * u * t)
+            + p2 * D2::from(three * u * t * t)
+            + p3 * D2::from(t * t * t)
+    }
+    /// direction the curve moves at `t`, its length is how far it moves per unit of `t`
+    pub fn cubic_bezier_derivative(points: [D2<D1>; 4], t: D1) -> D2<D1> {
+        let [p0, p1, p2, p3] = points;
+        let u = D1::one() - t;
+        let three = D1::from(3);
+        (p1 - p0) * D2::from(three * u * u)
+            + (p2 - p1) * D2::from(three * D1::two() * u * t)
+            + (p3 - p2) * D2::from(three * t * t)
+    }
+    /// min and max corner of the smallest box that holds the whole curve
+    pub fn cubic_bezier_bounds(points: [D2<D1>; 4]) -> (D2<D1>, D2<D1>) {
+        let [p0, p1, p2, p3] = points;
+        let mut ts = Vec::with_capacity(4);
+        // the derivative of each axis is a t^2 + b t + c (divided by 3), solve for 0
+        for (w, x, y, z) in [(p0.x, p1.x, p2.x, p3.x), (p0.y, p1.y, p2.y, p3.y)] {
+            let three = D1::from(3);
+            let a = z - w + (x - y) * three;
+            let b = (w - x * D1::two() + y) * D1::two();
+            let c = x - w;
+            // scaled down so the discriminant can't overflow on big curves
+            let scale = a.abs().max(b.abs()).max(c.abs());
+            if scale.is_zero() {
+                continue
+            }
+            let (a, b, c) = (a / scale, b / scale, c / scale);
+            if a.abs() < D1::new_const("0.0001") {
+                if !b.is_zero() {
+                    ts.push(-c / b);
+                }
+                continue
+            }
+            let discriminant = b * b - D1::from(4) * a * c;
+            if discriminant < D1::zero() {
+                continue
+            }
+            let root = discriminant.sqrt();
+            ts.push((-b + root) / (a * D1::two()));
+            ts.push((-b - root) / (a * D1::two()));
+        }
+        bounds_at(&[p0, p3], &ts, |t| D2::cubic_bezier(points, t))
+    }
+
+    /// the cubic bezier control points of the part of a uniform catmull rom spline between `points[1]` and `points[2]`,
+    /// `points[0]` and `points[3]` only bend it
+    pub fn catmull_rom_to_cubic(points: [D2<D1>; 4]) -> [D2<D1>; 4] {
+        let [p0, p1, p2, p3] = points;
+        let six = D2::from(D1::from(6));
+        [p1, p1 + (p2 - p0) / six, p2 - (p3 - p1) / six, p2]
+    }
+
+    /// splits the curve into straight lines that are never more than `tolerance` away from it.
+    /// Returns the points between the lines, the first and last control points included
+    pub fn flatten_cubic_bezier(points: [D2<D1>; 4], tolerance: D1) -> Vec<D2<D1>> {
+        let mut out = vec![points[0]];
+        flatten_cubic(points, tolerance, 0, &mut out);
+        out
+    }
+}
+
+/// how many times a curve can be cut in half when flattening, 2^16 lines is plenty for any curve on screen
+const MAX_FLATTEN_DEPTH: u32 = 16;
+
+fn flatten_cubic(points: [D2<D1>; 4], tolerance: D1, depth: u32, out: &mut Vec<D2<D1>>) {
+    let [p0, p1, p2, p3] = points;
+    // Willcocks' flatness test, the curve is within `tolerance` of the line between the ends if the length of the
+    // largest x and y of u and v is at most 4 tolerance. Adding them up is never less than the length so it's only stricter
+    let three = D2::from(D1::from(3));
+    let u = p1 * three - p0 * D2::two() - p3;
+    let v = p2 * three - p0 - p3 * D2::two();
+    let dist = u.x.abs().max(v.x.abs()) + u.y.abs().max(v.y.abs());
+    if depth >= MAX_FLATTEN_DEPTH || dist <= tolerance * D1::from(4) {
+        out.push(p3);
+        return
+    }
+    // de casteljau split at t = 0.5
+    let p01 = (p0 + p1) / D2::two();
+    let p12 = (p1 + p2) / D2::two();
+    let p23 = (p2 + p3) / D2::two();
+    let p012 = (p01 + p12) / D2::two();
+    let p123 = (p12 + p23) / D2::two();
+    let mid = (p012 + p123) / D2::two();
+    flatten_cubic([p0, p01, p012, mid], tolerance, depth + 1, out);
+    flatten_cubic([mid, p123, p23, p3], tolerance, depth + 1, out);
+}
+
+/// box around `ends` and the curve at every `t` between 0 and 1
+fn bounds_at<F: Fn(D1) -> D2<D1>>(ends: &[D2<D1>], ts: &[D1], curve: F) -> (D2<D1>, D2<D1>) {
+    let inside = ts.iter().filter(|t| **t > D1::zero() && **t < D1::one()).map(|t| curve(*t));
+    let mut min = ends[0];
+    let mut max = ends[0];
+    for pt in ends.iter().copied().chain(inside) {
+        min = D2::from((min.x.min(pt.x), min.y.min(pt.y)));
+        max = D2::from((max.x.max(pt.x), max.y.max(pt.y)));
+    }
+    (min, max)
+}
+
 impl<T> D2<T> {
     pub const fn new_const(x: T, y: T) -> D2<T> {
         D2 {x,y}
@@ -861,10 +1000,10 @@ impl D1 {
     pub fn to_f32(self) -> f32 {
         self.val.lossy_into()
     }
-    // must round towards negative infinity
+    // must round towards negative infinity, saturates instead of wrapping if out of range
     pub fn to_i32(self) -> i32 {
         let yeet:i64 = self.floor().val.lossy_into();
-        yeet as i32
+        yeet.clamp(i32::MIN as i64, i32::MAX as i64) as i32
     }
     pub fn to_usize(self) -> usize {
         let yeet:i64 = self.val.lossy_into();
@@ -986,4 +1125,80 @@ impl TrigMult {
             cos: angle.cos()
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pt(x: i32, y: i32) -> D2<D1> {
+        D2::from((D1::from(x), D1::from(y)))
+    }
+    fn half() -> D1 {
+        D1::one() / D1::two()
+    }
+
+    #[test]
+    fn quadratic_bezier() {
+        let points = [pt(0, 0), pt(2, 4), pt(4, 0)];
+        assert_eq!(D2::quadratic_bezier(points, D1::zero()), pt(0, 0));
+        assert_eq!(D2::quadratic_bezier(points, half()), pt(2, 2));
+        assert_eq!(D2::quadratic_bezier(points, D1::one()), pt(4, 0));
+        assert_eq!(D2::quadratic_bezier_derivative(points, D1::zero()), pt(4, 8));
+        assert_eq!(D2::quadratic_bezier_derivative(points, half()), pt(4, 0));
+        assert_eq!(D2::quadratic_bezier_derivative(points, D1::one()), pt(4, -8));
+        assert_eq!(D2::quadratic_bezier_bounds(points), (pt(0, 0), pt(4, 2)));
+        // the ends are the bounds when the curve doesn't turn around
+        assert_eq!(D2::quadratic_bezier_bounds([pt(0, 0), pt(1, 1), pt(4, 2)]), (pt(0, 0), pt(4, 2)));
+    }
+
+    #[test]
+    fn nearly_straight_quadratic_bounds_dont_overflow() {
+        // a - 2b + c is the smallest step a D1 can take while a - b is huge
+        let tiny = D1 { val: I36F28::DELTA };
+        let points = [pt(0, 0), pt(1_000_000, 0), D2::from((D1::from(2_000_000) + tiny, D1::zero()))];
+        assert_eq!(D2::quadratic_bezier_bounds(points), (pt(0, 0), points[2]));
+    }
+
+    #[test]
+    fn cubic_bezier() {
+        let points = [pt(0, 0), pt(0, 4), pt(4, 4), pt(4, 0)];
+        assert_eq!(D2::cubic_bezier(points, D1::zero()), pt(0, 0));
+        assert_eq!(D2::cubic_bezier(points, half()), pt(2, 3));
+        assert_eq!(D2::cubic_bezier(points, D1::one()), pt(4, 0));
+        assert_eq!(D2::cubic_bezier_derivative(points, D1::zero()), pt(0, 12));
+        assert_eq!(D2::cubic_bezier_derivative(points, half()), pt(6, 0));
+        assert_eq!(D2::cubic_bezier_derivative(points, D1::one()), pt(0, -12));
+        assert_eq!(D2::cubic_bezier_bounds(points), (pt(0, 0), pt(4, 3)));
+
+        // an s curve turns around on x twice, the box has to hold every point and touch the curve at its sides
+        let s_curve = [pt(0, 0), pt(16, 1), pt(-12, 2), pt(4, 3)];
+        let (min, max) = D2::cubic_bezier_bounds(s_curve);
+        assert!(min.x < D1::zero() && max.x > D1::from(4), "{:?} {:?}", min, max);
+        assert_eq!((min.y, max.y), (D1::zero(), D1::from(3)));
+        let samples: Vec<D2<D1>> = (0..=1000).map(|i| D2::cubic_bezier(s_curve, D1::from(i) / D1::from(1000))).collect();
+        let sample_min = samples.iter().map(|pt| pt.x).min().unwrap();
+        let sample_max = samples.iter().map(|pt| pt.x).max().unwrap();
+        let close = D1::one() / D1::from(100);
+        assert!(min.x <= sample_min && sample_min - min.x < close, "{:?} {:?}", min, sample_min);
+        assert!(max.x >= sample_max && max.x - sample_max < close, "{:?} {:?}", max, sample_max);
+    }
+
+    #[test]
+    fn catmull_rom_through_evenly_spaced_points_is_straight() {
+        let cubic = D2::catmull_rom_to_cubic([pt(0, 0), pt(3, 0), pt(6, 0), pt(9, 0)]);
+        assert_eq!(cubic, [pt(3, 0), pt(4, 0), pt(5, 0), pt(6, 0)]);
+    }
+
+    #[test]
+    fn flattening() {
+        let line = [pt(0, 0), pt(1, 1), pt(2, 2), pt(3, 3)];
+        assert_eq!(D2::flatten_cubic_bezier(line, half()), [pt(0, 0), pt(3, 3)]);
+
+        let curve = [pt(0, 0), pt(0, 40), pt(40, 40), pt(40, 0)];
+        let coarse = D2::flatten_cubic_bezier(curve, D1::from(4));
+        let fine = D2::flatten_cubic_bezier(curve, D1::one() / D1::from(10));
+        assert!(coarse.len() > 2 && fine.len() > coarse.len());
+        assert_eq!((fine[0], *fine.last().unwrap()), (curve[0], curve[3]));
+    }
+}
@@ -1,12 +1,22 @@
 use std::fmt::Debug;
 use std::mem::swap;
 
+use fixed::types::I64F64;
 use serde::{Serialize, Deserialize};
 // use common::*;
 use crate::vec2d::Vec2d;
+use crate::blend::{mul_255, BlendMode};
 use crate::cam_data::*;
+use crate::pixel_format::PixelFormat;
+use crate::render_target::{Blended, DepthTested, RenderTarget, VectorShape};
 use crate::coordinates::*;
+use crate::font::Font;
+use crate::path::{quad_to_cubic, FillRule, Path};
 use crate::shape::*;
+use crate::stroke_font::{stroke_text_width, StrokeGlyph, STROKE_CAP_HEIGHT, STROKE_LINE_HEIGHT};
+use crate::svg;
+use crate::text_layout::{TextAlign, TextLayout, TextLayoutOptions};
+use crate::texture::{TextureAtlas, TextureError, TextureHandle};
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Color {
@@ -25,6 +35,7 @@ impl Color {
     pub fn new_transparent() -> Self {
         Self { r: 0, g: 0, b: 0, alpha: 0 }
     }
+    /// reads a 0RGB pixel, alpha is always 255
     pub fn new_from_u32(rgb: u32) -> Self {
         Self {
             r: ((rgb >> 16) & 0xFF) as u8,
@@ -41,9 +52,22 @@ impl Color {
             alpha: 255,
         }
     }
+    /// packs as 0RGB (what minifb expects), drops alpha. Use [Color::to_argb] to keep it
     pub fn to_u32(&self) -> u32 {
         ((self.r as u32) << 16) + ((self.g as u32) << 8) + (self.b as u32)
     }
+    pub fn new_from_argb(argb: u32) -> Self {
+        PixelFormat::Argb8888.decode(argb)
+    }
+    pub fn to_argb(&self) -> u32 {
+        PixelFormat::Argb8888.encode(*self)
+    }
+    pub fn new_from_format(pixel: u32, format: PixelFormat) -> Self {
+        format.decode(pixel)
+    }
+    pub fn to_format(&self, format: PixelFormat) -> u32 {
+        format.encode(*self)
+    }
     pub fn rgb(&self) -> (u8, u8, u8) {
         (self.r, self.g, self.b)
     }
@@ -53,6 +77,11 @@ impl Color {
     pub fn make_transparent(self) -> Color {
         return Color::new_with_alpha(self.r, self.g, self.b, self.alpha/2)
     }
+    /// draws self over `dst` (source over with straight alpha). If `dst` is opaque the result is too,
+    /// otherwise the alphas combine so transparent images stay transparent where nothing was drawn
+    pub fn over(self, dst: Color) -> Color {
+        BlendMode::Normal.blend(self, dst)
+    }
     pub fn button_pressed_change(self) -> Color {
         return Color::new(self.r/2, self.g/2, self.b/2)
     }
@@ -96,6 +125,30 @@ impl Color {
     pub fn black() -> Self {
         Self::new(0, 0, 0)
     }
+    /// the color with the same name as one of the constructors above (like "red" or "dark_green"),
+    /// or a hex color like "#ff8000"
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(hex) = name.strip_prefix('#') {
+            let rgb = u32::from_str_radix(hex, 16).ok().filter(|_| hex.len() == 6)?;
+            return Some(Color::new_from_u32(rgb))
+        }
+        Some(match name {
+            "maroon" => Color::maroon(),
+            "green" => Color::green(),
+            "dark_green" => Color::dark_green(),
+            "red" => Color::red(),
+            "ultramarine_blue" => Color::ultramarine_blue(),
+            "blue" => Color::blue(),
+            "gray" => Color::gray(),
+            "purple" => Color::purple(),
+            "pink" => Color::pink(),
+            "orange" => Color::orange(),
+            "yellow" => Color::yellow(),
+            "white" => Color::white(),
+            "black" => Color::black(),
+            _ => return None,
+        })
+    }
 
     /// cycles through a list of colors. Changes self to the next color in the list
     pub fn next(self) -> Self {
@@ -123,26 +176,170 @@ impl Color {
 }
 
 
-pub type Bez = ((usize, usize), (usize, usize));
+/// a line segment in signed pixel coords rel to bottom left of screen
+pub type Segment = ((i32, i32), (i32, i32));
+#[deprecated(note = "it was never a curve, use Segment")]
+pub type Bez = Segment;
+
+
+
+/// the part of a [Cam] that turns coords rel to the draw plane into screen coords, made with [Cam::projection].
+/// Doesn't borrow the target so it can be handed to it, see [RenderTarget::draw_vector]
+#[derive(Debug, Clone, Copy)]
+pub struct Projection<'a> {
+    pub zero_pos: Pos,
+    pub trig_mult: TrigMult,
+    pub cam_data: &'a CamData,
+    /// size of the target in pixels, the camera looks at its center
+    pub size: (usize, usize),
+}
+impl Projection<'_> {
+    /// distance in z from the camera focal point to a point `z` above the draw plane
+    pub fn depth(&self, z: D1) -> D1 {
+        self.cam_data.focal_len() - (self.zero_pos.z() + z)
+    }
+
+    /// projects a point rel to the draw plane (z is the height above the plane) to sub pixel coords rel to bottom left of screen.
+    /// Returns None if the point is closer to the camera than [CamData::near_dist] (or behind it)
+    pub fn project(&self, coords: D3) -> Option<D2<D1>> {
+        let depth = self.depth(coords.z);
+        if depth < self.cam_data.near_dist() {
+            return None
+        }
+        let xy = coords.xy().rotate_with_trig_mult(self.trig_mult);
+
+        let rel_point: D2<D1> = D2::from((
+            (xy.x + self.zero_pos.x())/self.cam_data.zoom(),
+            (xy.y + self.zero_pos.y())/self.cam_data.zoom()));
+
+        // scale first so big coords don't overflow before the divide
+        let scale = self.cam_data.focal_len() / depth;
+        // saturating so points far off screen stay far off screen instead of overflowing
+        let x = rel_point.x.val.saturating_mul(scale.val);
+        let y = rel_point.y.val.saturating_mul(scale.val);
+
+        // makes coords rel to center of screen instead of bottom left. The screen is the target being drawn into,
+        // usually the buffer of a [Presenter](crate::Presenter) but it can be a layer or part of another target
+        let half_size: D2<D1> = D2::from((D1::from(self.size.0)/D1::two(), D1::from(self.size.1)/D1::two()));
+        Some(D2::from((D1 { val: x.saturating_add(half_size.x.val) }, D1 { val: y.saturating_add(half_size.y.val) })))
+    }
+
+    /// clips a line rel to the draw plane so that no part of it is closer to the camera than [CamData::near_dist].
+    /// Returns None if the whole line is too close
+    pub fn clip_near(&self, line: (D3, D3)) -> Option<(D3, D3)> {
+        let near = self.cam_data.near_dist();
+        let depth0 = self.depth(line.0.z);
+        let depth1 = self.depth(line.1.z);
+        match (depth0 >= near, depth1 >= near) {
+            (true, true) => Some(line),
+            (false, false) => None,
+            (visible0, _) => {
+                let crossing = self.near_crossing(line.0, line.1);
+                if visible0 {
+                    Some((line.0, crossing))
+                } else {
+                    Some((crossing, line.1))
+                }
+            }
+        }
+    }
 
+    /// clips a closed polygon rel to the draw plane the same way as [Projection::clip_near].
+    /// Returns fewer than 3 points if nothing of it is left
+    pub fn clip_polygon_near(&self, points: &[D3]) -> Vec<D3> {
+        let near = self.cam_data.near_dist();
+        let mut clipped = Vec::with_capacity(points.len() + 1);
+        for (i, pt) in points.iter().enumerate() {
+            let next = points[(i + 1) % points.len()];
+            let depth0 = self.depth(pt.z);
+            let depth1 = self.depth(next.z);
+            if depth0 >= near {
+                clipped.push(*pt);
+            }
+            if (depth0 >= near) != (depth1 >= near) {
+                clipped.push(self.near_crossing(*pt, next));
+            }
+        }
+        clipped
+    }
+
+    /// where the line between two points on opposite sides of the near distance crosses it
+    fn near_crossing(&self, a: D3, b: D3) -> D3 {
+        let near = self.cam_data.near_dist();
+        let (depth_a, depth_b) = (self.depth(a.z), self.depth(b.z));
+        let t = (depth_a - near) / (depth_a - depth_b);
+        let mut crossing = a + (b - a) * D3::from(t);
+        // exactly at the near distance, rounding could otherwise leave it just too close to project
+        crossing.z = self.cam_data.focal_len() - self.zero_pos.z() - near;
+        crossing
+    }
+
+    /// whether the polygon rel to the draw plane goes counterclockwise on screen, so a face wound counterclockwise
+    /// seen from outside is turned towards the camera. False if none of it is in front of the near distance
+    pub fn faces_camera(&self, points: &[D3]) -> bool {
+        let clipped = self.clip_polygon_near(points);
+        let Some(projected) = clipped.iter().map(|pt| self.project(*pt)).collect::<Option<Vec<_>>>() else {
+            return false
+        };
+        let mut area = D1::zero();
+        for (i, pt) in projected.iter().enumerate() {
+            let next = projected[(i + 1) % projected.len()];
+            area += pt.x * next.y - next.x * pt.y;
+        }
+        area > D1::zero()
+    }
 
+    /// how far apart two points on the draw plane are when they land one screen pixel apart.
+    /// Returns None if the plane is closer than the near distance
+    pub fn plane_units_per_pixel(&self) -> Option<D1> {
+        let depth = self.depth(D1::zero());
+        if depth < self.cam_data.near_dist() {
+            return None
+        }
+        Some(depth * self.cam_data.zoom() / self.cam_data.focal_len())
+    }
+}
 
+/// draws into any [RenderTarget], by default the 0RGB buffer minifb uses
 #[derive(Debug)]
-pub struct Cam<'a> {
+pub struct Cam<'a, T: RenderTarget + ?Sized = Vec2d<u32>> {
     pub zero_pos: Pos, // position of draw plane relative to center of screen
     pub trig_mult: TrigMult,
     pub cam_data: &'a CamData,
-    pub img: &'a mut Vec2d<u32>,
+    pub img: &'a mut T,
+    /// opacity of the whole plane, multiplied into every color drawn. Overlapping primitives each get
+    /// faded on their own, draw into a [Layer](crate::Layer) first if that isn't wanted
     pub alpha: u8,
+    /// optional per pixel depth buffer (same size as `img`), see [Cam::with_depth_buffer]
+    pub depth: Option<&'a mut Vec2d<D1>>,
+    /// how every pixel this cam draws is combined with what is already there
+    pub blend_mode: BlendMode,
+}
+impl<'a, T: RenderTarget + ?Sized> Cam<'a, T> {
+    /// makes every draw call of this cam test and write the depth buffer, so that
+    /// higher planes hide lower ones no matter which order they are drawn in.
+    /// The depth buffer should be made with [new_depth_buffer] and reset with [clear_depth_buffer] every frame
+    pub fn with_depth_buffer(mut self, depth: &'a mut Vec2d<D1>) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+    pub fn projection(&self) -> Projection<'a> {
+        Projection { zero_pos: self.zero_pos, trig_mult: self.trig_mult, cam_data: self.cam_data, size: self.img.size() }
+    }
 }
-impl Cam<'_> {
-    /// returns None if the z height of the draw plane is at or above the focal point of the camera (above the camera, otherwise divide by 0 errors will happen)
+impl<T: RenderTarget + ?Sized> Cam<'_, T> {
+    /// returns None if the z height of the draw plane is at or above the focal point of the camera (above the camera, otherwise divide by 0 errors will happen).
+    /// Planes below the focal point but closer than [CamData::near_dist] still get a cam, their points are clipped by [Cam::project]
     pub fn new_rel_to_pos<'a>(
-        img: &'a mut Vec2d<u32>,
+        img: &'a mut T,
         cam_data: &'a CamData,
         rel_to: Pos,
         alpha: u8,
-    ) -> Option<Cam<'a>> {
+    ) -> Option<Cam<'a, T>> {
         let zero_pos = cam_data.zero_pos(rel_to);
 
         if zero_pos.z() >= cam_data.focal_len() {
@@ -154,51 +351,128 @@ impl Cam<'_> {
             img,
             alpha,
             cam_data,
+            depth: None,
+            blend_mode: BlendMode::Normal,
         };
         return Some(cam)
     }
     
-    /// change draw plane without having to make a new cam
+    /// change draw plane without having to make a new cam.
+    /// The plane can be anywhere, points that end up behind the near distance are clipped when projected
     pub fn change_cam_perspective(&mut self, zero_position: Pos) {
-        if zero_position.z() >= self.cam_data.focal_len() {
-            //println!("this would have been a crash");
-            return
-        }
         self.zero_pos = zero_position;
         self.trig_mult = TrigMult::new(-self.zero_pos.angle);
     }
     
     //-----------
-    /// takes physical coords rel to camera and returns pixel coords rel to bottom left of screen
-    pub fn coords_to_cam_coords(&self, coords: D2<D1>) -> (usize, usize) {
-        let coords = coords.rotate_with_trig_mult(self.trig_mult);
+    /// takes physical coords rel to camera and returns pixel coords rel to bottom left of screen.
+    /// Coords that land off screen can be negative or larger than the screen, they are clipped when rasterized.
+    /// Returns None if the point is closer to the camera than the near distance
+    pub fn coords_to_cam_coords(&self, coords: D2<D1>) -> Option<(i32, i32)> {
+        self.coords_to_screen_coords(coords).map(|pt| pt.to_i32())
+    }
+
+    /// same as [Cam::coords_to_cam_coords] but keeps the sub pixel precision, used by the fill functions
+    pub fn coords_to_screen_coords(&self, coords: D2<D1>) -> Option<D2<D1>> {
+        self.project(D3::from((coords, D1::zero())))
+    }
 
-        let rel_point = D3::from((
-            (coords.x + self.zero_pos.x())/self.cam_data.zoom(),
-            (coords.y + self.zero_pos.y())/self.cam_data.zoom(),
-            self.zero_pos.z()));
+    /// distance in z from the camera focal point to a point `z` above the draw plane
+    pub fn depth(&self, z: D1) -> D1 {
+        self.projection().depth(z)
+    }
 
+    /// projects a point rel to the draw plane (z is the height above the plane) to sub pixel coords rel to bottom left of screen.
+    /// Returns None if the point is closer to the camera than [CamData::near_dist] (or behind it)
+    pub fn project(&self, coords: D3) -> Option<D2<D1>> {
+        self.projection().project(coords)
+    }
 
-        let cam_fish = self.cam_data.focal_len();
-        let x = rel_point.x * cam_fish / (-rel_point.z+cam_fish);
-        let y = rel_point.y * cam_fish / (-rel_point.z+cam_fish);
+    /// clips a line rel to the draw plane so that no part of it is closer to the camera than [CamData::near_dist].
+    /// Returns None if the whole line is too close
+    pub fn clip_near(&self, line: (D3, D3)) -> Option<(D3, D3)> {
+        self.projection().clip_near(line)
+    }
 
-        // makes coords rel to center of screen instead of bottom left
-        let yeet = D2::from((x + D1::from(self.cam_data.winsize().0)/D1::two(), y + D1::from(self.cam_data.winsize().1)/D1::two()));
-        return yeet.to_usize()
+    /// the inverse of [Cam::coords_to_screen_coords], takes sub pixel coords rel to bottom left of screen and
+    /// returns the point on the draw plane that lands there. Returns None if the plane is closer than the near distance
+    pub fn screen_coords_to_coords(&self, screen_coords: D2<D1>) -> Option<D2<D1>> {
+        let depth = self.depth(D1::zero());
+        if depth < self.cam_data.near_dist() {
+            return None
+        }
+        let size = self.img.size();
+        let scale = self.cam_data.focal_len() / depth;
+        let x = (screen_coords.x - D1::from(size.0)/D1::two()) / scale * self.cam_data.zoom() - self.zero_pos.x();
+        let y = (screen_coords.y - D1::from(size.1)/D1::two()) / scale * self.cam_data.zoom() - self.zero_pos.y();
+        // undo the rotation of the plane
+        let inverse = TrigMult { sin: -self.trig_mult.sin, cos: self.trig_mult.cos };
+        Some(rotate_precise(D2::from((x, y)), inverse))
     }
     //-----------
 
 
 
+    /// draws a pixel rel to bottom left of screen at height `z` (rel to the camera, like `zero_pos.z()`),
+    /// if there is a depth buffer the pixel is only drawn if nothing higher has been drawn there yet.
+    /// The alpha of `color` gets multiplied by the alpha of the cam
+    pub fn plot(&mut self, coords: (i32, i32), z: D1, color: Color) {
+        if let Some(depth) = &mut self.depth {
+            if !test_and_set_depth(depth, coords, z) {
+                return
+            }
+        }
+        let color = self.fade(color);
+        draw_pixel_blend(self.img, coords, color, self.blend_mode);
+    }
+
+    /// offers `shape` to the target first, returns true if the target took it and nothing needs to be rasterized
+    fn draw_vector(&mut self, shape: &VectorShape, color: Color) -> bool {
+        let projection = self.projection();
+        let color = self.fade(color);
+        self.img.draw_vector(shape, color, &projection)
+    }
+
+    /// multiplies the alpha of `color` by the alpha of the cam
+    pub fn fade(&self, color: Color) -> Color {
+        Color::new_with_alpha(color.r, color.g, color.b, mul_255(color.alpha as u32, self.alpha as u32) as u8)
+    }
+
     pub fn draw_pixel(&mut self, coords: D2<D1>, color: Color) {
-        draw_pixel(self.img, self.coords_to_cam_coords(coords), color);
+        if let Some(pt) = self.coords_to_cam_coords(coords) {
+            self.plot(pt, self.zero_pos.z(), color);
+        }
     }
 
     pub fn draw_line(&mut self, line: (D2<D1>, D2<D1>), color: Color) {
-        let pt1_cam = self.coords_to_cam_coords(line.0);
-        let pt2_cam = self.coords_to_cam_coords(line.1);
-        draw_bezier(self.img, (pt1_cam, pt2_cam), color);
+        self.draw_line_3d((D3::from((line.0, D1::zero())), D3::from((line.1, D1::zero()))), color);
+    }
+
+    /// draws a line between points rel to the draw plane, z is the height above the plane.
+    /// The part of the line closer to the camera than the near distance is clipped off
+    pub fn draw_line_3d(&mut self, line: (D3, D3), color: Color) {
+        if self.draw_vector(&VectorShape::Line(line.0, line.1), color) {
+            return
+        }
+        let Some((pt1, pt2)) = self.clip_near(line) else {
+            return
+        };
+        let (Some(pt1_cam), Some(pt2_cam)) = (self.project(pt1), self.project(pt2)) else {
+            return
+        };
+        let segment = (pt1_cam.to_i32(), pt2_cam.to_i32());
+
+        // interpolate the height along the longer axis of the line
+        let z1 = self.zero_pos.z() + pt1.z;
+        let z2 = self.zero_pos.z() + pt2.z;
+        let (dx, dy) = ((segment.1 .0 as i64 - segment.0 .0 as i64).abs(), (segment.1 .1 as i64 - segment.0 .1 as i64).abs());
+        let len = D1::from(dx.max(dy).max(1));
+        let size = self.img.size();
+        for_each_line_pixel(size, segment, |pt| {
+            let steps = if dx > dy { pt.0 as i64 - segment.0 .0 as i64 } else { pt.1 as i64 - segment.0 .1 as i64 };
+            let z = z1 + (z2 - z1) * D1::from(steps.abs()) / len;
+            self.plot(pt, z, color);
+        });
     }
 
     pub fn draw_rect(
@@ -237,66 +511,286 @@ impl Cam<'_> {
         self.draw_line((tl, bl), color);
     }
 
-    // pub fn draw_rect_corners_solid(&mut self, corners: (D2<D1>, D2<D1>), color: Color) {
-    //     let bl = corners.0; // bottom left
-    //     let tr = corners.1; // top right
-        
-    //     let (bl, tr) = (bl, tr).fix_corners();
-    //     let bl = self.coords_to_cam_coords(bl);
-    //     let tr = self.coords_to_cam_coords(tr);
+    /// filled version of [Cam::draw_rect_corners], still correct when the draw plane is rotated
+    pub fn fill_rect_corners(&mut self, corners: (D2<D1>, D2<D1>), color: Color) {
+        let bl = corners.0; // bottom left
+        let tr = corners.1; // top right
+        let br = D2::from((tr.x, bl.y)); // bottom right corner
+        let tl = D2::from((bl.x, tr.y)); // top left corner
 
-    //     for x in bl.0..tr.0 {
-    //         for y in bl.1..tr.1 {
-    //             draw_pixel(self.img, (x, y), color);
-    //         }
-    //     }
-    // }
+        self.fill_polygon(&[bl, br, tr, tl], color);
+    }
+
+    /// filled version of [Cam::draw_rect]
+    pub fn fill_rect(
+        &mut self,
+        center: D2<D1>,
+        width: D2<D1>,
+        angle: Angle,
+        color: Color,
+    ) {
+        let bl = D2::from((center.x - width.x, center.y - width.y));
+        let tr = D2::from((center.x + width.x, center.y + width.y));
+        let br = D2::from((tr.x, bl.y)); // bottom right corner
+        let tl = D2::from((bl.x, tr.y)); // top left corner
+        let trig_mult = TrigMult::new(-angle);
+        let corners = [bl, br, tr, tl].map(|pt| pt.rotate_with_trig_mult(trig_mult));
+
+        self.fill_polygon(&corners, color);
+    }
+
+    /// fills the outline made by connecting `points` in order (the last point connects back to the first).
+    /// Every vertex is projected onto the screen and the projected outline is filled with [fill_polygon]
+    pub fn fill_polygon(&mut self, points: &[D2<D1>], color: Color) {
+        if self.draw_vector(&VectorShape::Fill { contours: &[points], fill_rule: FillRule::EvenOdd }, color) {
+            return
+        }
+        // every point is at the same height so either all or none of them are clipped
+        let Some(projected) = points.iter().map(|pt| self.coords_to_screen_coords(*pt)).collect::<Option<Vec<_>>>() else {
+            return
+        };
+        let z = self.zero_pos.z();
+        let size = self.img.size();
+        for_each_polygon_pixel(size, &projected, |pt| self.plot(pt, z, color));
+    }
+
+    /// filled version of [Cam::draw_circle], uses the same 16 sided polygon
+    pub fn fill_circle(&mut self, center: D2<D1>, radius: D1, color: Color) {
+        if self.draw_vector(&VectorShape::Circle { center, radius, filled: true }, color) {
+            return
+        }
+        let points = polygon_points(center, radius, 16, Angle::min());
+        self.fill_polygon(&points, color);
+    }
 
     pub fn draw_wide_pixel(&mut self, coords: D2<D1>, width: usize, color: Color) {
         // bottom left
-        let bl = self.coords_to_cam_coords(coords);
-
-        for x in bl.0..(bl.0 + width) {
-            for y in bl.1..(bl.1 + width) {
-                draw_pixel(self.img, (x, y), color);
+        let Some(bl) = self.coords_to_cam_coords(coords) else {
+            return
+        };
+        let Ok(width) = i32::try_from(width) else {
+            return
+        };
+        // only loop over the part that is on screen
+        let size = self.img.size();
+        let clamp = |len: usize| i32::try_from(len).unwrap_or(i32::MAX);
+        let xs = bl.0.max(0)..bl.0.saturating_add(width).min(clamp(size.0));
+        let ys = bl.1.max(0)..bl.1.saturating_add(width).min(clamp(size.1));
+
+        for x in xs {
+            for y in ys.clone() {
+                self.plot((x, y), self.zero_pos.z(), color);
             }
         }
     }
 
+    /// outline of the prism between two opposite corners, z is the height above the draw plane.
+    /// Same as [Cam::draw_shape3d] with [Shape3DStyle::Wireframe]
     pub fn draw_rect_prism_corners(&mut self, corners: (D3, D3), color: Color) {
-        let bl = corners.0.xy(); // bottom left
-        let tr = corners.1.xy(); // top right
-        let br = D2::from((tr.x, bl.y)); // bottom right
-        let tl = D2::from((bl.x, tr.y)); // top left
+        self.draw_shape3d(Shape3D::RectPrism(corners), Shape3DStyle::Wireframe(color));
+    }
+
+    /// draws `shape` rel to the draw plane, z is the height above the plane.
+    /// Prisms show only the faces that point towards the camera when shaded, spheres are the circle they cover
+    /// on screen and points are a small cross that stays the same size on screen
+    pub fn draw_shape3d(&mut self, shape: Shape3D, style: Shape3DStyle) {
+        match shape {
+            Shape3D::Point(pt) => {
+                let color = match style {
+                    Shape3DStyle::Wireframe(color) | Shape3DStyle::Shaded { color, .. } => color,
+                };
+                self.at_height(pt.z, |cam| {
+                    let Some(units) = cam.projection().plane_units_per_pixel() else {
+                        return
+                    };
+                    let arm = units * D1::from(POINT_MARKER_SIZE);
+                    let center = pt.xy();
+                    cam.draw_line((center - D2::from((arm, D1::zero())), center + D2::from((arm, D1::zero()))), color);
+                    cam.draw_line((center - D2::from((D1::zero(), arm)), center + D2::from((D1::zero(), arm))), color);
+                });
+            }
+            Shape3D::RectPrism(corners) => self.draw_rect_prism(corners, style),
+            Shape3D::Sphere((center, radius)) => {
+                // the edge of the sphere on screen is where the cone from the focal point touches it,
+                // which is wider than the sphere at the height of its center
+                let depth = self.depth(center.z);
+                if depth <= radius {
+                    return
+                }
+                let radius = radius * depth / (depth * depth - radius * radius).sqrt();
+                self.at_height(center.z, |cam| match style {
+                    Shape3DStyle::Wireframe(color) => cam.draw_circle(center.xy(), radius, color),
+                    Shape3DStyle::Shaded { color, edges } => {
+                        cam.fill_circle(center.xy(), radius, color);
+                        if let Some(edges) = edges {
+                            cam.draw_circle(center.xy(), radius, edges);
+                        }
+                    }
+                });
+            }
+        }
+    }
+
+    fn draw_rect_prism(&mut self, corners: (D3, D3), style: Shape3DStyle) {
+        let min = D3::from((corners.0.x.min(corners.1.x), corners.0.y.min(corners.1.y), corners.0.z.min(corners.1.z)));
+        let max = D3::from((corners.0.x.max(corners.1.x), corners.0.y.max(corners.1.y), corners.0.z.max(corners.1.z)));
+        // bit 0 of the index picks the max x, bit 1 the max y and bit 2 the max z
+        let vertices: [D3; 8] = std::array::from_fn(|i| D3::from((
+            if i & 1 == 0 { min.x } else { max.x },
+            if i & 2 == 0 { min.y } else { max.y },
+            if i & 4 == 0 { min.z } else { max.z },
+        )));
+
+        let (color, edge_color) = match style {
+            Shape3DStyle::Wireframe(color) => {
+                for (a, b) in PRISM_EDGES {
+                    self.draw_line_3d((vertices[a], vertices[b]), color);
+                }
+                return
+            }
+            Shape3DStyle::Shaded { color, edges } => (color, edges),
+        };
+        let projection = self.projection();
+        let mut visible_edges: Vec<(usize, usize)> = Vec::new();
+        for (face, normal) in PRISM_FACES {
+            let points = face.map(|i| vertices[i]);
+            if !projection.faces_camera(&points) {
+                continue
+            }
+            self.fill_face(&points, shade(color, normal));
+            for i in 0..4 {
+                let (a, b) = (face[i], face[(i + 1) % 4]);
+                let edge = (a.min(b), a.max(b));
+                if !visible_edges.contains(&edge) {
+                    visible_edges.push(edge);
+                }
+            }
+        }
+        if let Some(edge_color) = edge_color {
+            for (a, b) in visible_edges {
+                self.draw_line_3d((vertices[a], vertices[b]), edge_color);
+            }
+        }
+    }
 
+    /// fills a flat polygon rel to the draw plane that doesn't have to lie in it, z is the height above the plane.
+    /// The part closer to the camera than the near distance is clipped off. In the depth buffer the whole face
+    /// is as high as its lowest point
+    pub fn fill_face(&mut self, points: &[D3], color: Color) {
+        if self.draw_vector(&VectorShape::Face(points), color) {
+            return
+        }
+        let clipped = self.projection().clip_polygon_near(points);
+        let Some(projected) = clipped.iter().map(|pt| self.project(*pt)).collect::<Option<Vec<_>>>() else {
+            return
+        };
+        let Some(lowest) = clipped.iter().map(|pt| pt.z).min() else {
+            return
+        };
+        let z = self.zero_pos.z() + lowest;
+        let size = self.img.size();
+        for_each_polygon_pixel(size, &projected, |pt| self.plot(pt, z, color));
+    }
+
+    /// runs `draw` with the draw plane moved up by `z`, then puts it back
+    fn at_height<F: FnOnce(&mut Self)>(&mut self, z: D1, draw: F) {
         let original_zero = self.zero_pos;
+        let mut zero_position = original_zero;
+        zero_position.coords.z += z;
+        self.change_cam_perspective(zero_position);
+        draw(self);
+        self.change_cam_perspective(original_zero);
+    }
+
+    /// lines `spacing` apart over the part of the draw plane the cam sees, every `major_every`th one (counted from the
+    /// plane origin, 0 for none) in the full `color` and the rest fainter. When the lines get closer than 6 pixels
+    /// on screen the spacing doubles, in powers of two like [CamData::zoom_exponent] so one zoom step is one doubling.
+    /// Every other minor line fades out as they get close, so the grid doesn't jump when the spacing changes
+    pub fn draw_grid(&mut self, spacing: D1, major_every: u32, color: Color) {
+        let (Some((step, gap)), Some((min, max))) = (self.grid_step(spacing), self.visible_bounds()) else {
+            return
+        };
+        // gone at the gap where the spacing doubles, fully there at twice that
+        let fade = ((gap - MIN_GRID_GAP) / MIN_GRID_GAP).clamp(D1::zero(), D1::one());
+        let minor = Color::new_with_alpha(color.r, color.g, color.b, color.alpha / 2);
+        let fading = Color::new_with_alpha(color.r, color.g, color.b, (D1::from(minor.alpha as i32) * fade).to_i32() as u8);
+
+        for (i, x) in grid_lines(min.x, max.x, step) {
+            let color = grid_line_color(i, major_every, color, minor, fading);
+            self.draw_line((D2::from((x, min.y)), D2::from((x, max.y))), color);
+        }
+        for (i, y) in grid_lines(min.y, max.y, step) {
+            let color = grid_line_color(i, major_every, color, minor, fading);
+            self.draw_line((D2::from((min.x, y)), D2::from((max.x, y))), color);
+        }
+    }
 
-        if corners.0.z < self.cam_data.cam_pos_abs.z() {
-            let mut zero_position = self.zero_pos;
-            zero_position.coords.z += corners.0.z;
-            self.change_cam_perspective(zero_position);
-            self.draw_line((bl, br), color);
-            self.draw_line((br, tr), color);
-            self.draw_line((tr, tl), color);
-            self.draw_line((tl, bl), color);
-            self.change_cam_perspective(original_zero);
+    /// the x and y axes of the draw plane over the part the cam sees, labeled with the coords of the major lines
+    /// [Cam::draw_grid] draws for the same `spacing` and `major_every`. Labels that would crowd each other are skipped
+    pub fn draw_axes(&mut self, spacing: D1, major_every: u32, color: Color) {
+        let (Some((step, gap)), Some((min, max)), Some(units)) =
+            (self.grid_step(spacing), self.visible_bounds(), self.projection().plane_units_per_pixel()) else {
+            return
+        };
+        self.draw_line((D2::from((min.x, D1::zero())), D2::from((max.x, D1::zero()))), color);
+        self.draw_line((D2::from((D1::zero(), min.y)), D2::from((D1::zero(), max.y))), color);
+
+        let mut label_every = major_every.max(1) as i64;
+        while gap * D1::from(label_every) < MIN_LABEL_GAP {
+            label_every *= 2;
+        }
+        // a couple of pixels off the axis so the text doesn't sit on the line
+        let offset = units * D1::two();
+        let dimensions = D2::from((1, 1));
+        self.draw_text(D2::from(offset), "0", Font::builtin(), color, dimensions, false, false);
+        for (i, x) in grid_lines(min.x, max.x, step) {
+            if i != 0 && i % label_every == 0 {
+                self.draw_text(D2::from((x + offset, offset)), &svg::num(x), Font::builtin(), color, dimensions, false, false);
+            }
         }
+        for (i, y) in grid_lines(min.y, max.y, step) {
+            if i != 0 && i % label_every == 0 {
+                self.draw_text(D2::from((offset, y + offset)), &svg::num(y), Font::builtin(), color, dimensions, false, false);
+            }
+        }
+    }
 
-        if corners.1.z < self.cam_data.cam_pos_abs.z() {
-            let mut zero_position = self.zero_pos;
-            zero_position.coords.z += corners.1.z;
-            self.change_cam_perspective(zero_position);
-            self.draw_line((bl, br), color);
-            self.draw_line((br, tr), color);
-            self.draw_line((tr, tl), color);
-            self.draw_line((tl, bl), color);
-            self.change_cam_perspective(original_zero);
+    /// `spacing` doubled until its lines are at least [MIN_GRID_GAP] pixels apart on screen, and how far apart they are.
+    /// None if the plane is too close to see or the spacing would get too big
+    fn grid_step(&self, spacing: D1) -> Option<(D1, D1)> {
+        if spacing <= D1::zero() {
+            return None
+        }
+        let units = self.projection().plane_units_per_pixel()?;
+        let mut step = spacing;
+        while step / units < MIN_GRID_GAP {
+            if step > MAX_GRID_STEP {
+                return None
+            }
+            step *= D1::two();
         }
+        Some((step, step / units))
+    }
+
+    /// min and max corner of a box on the draw plane that covers the whole screen
+    fn visible_bounds(&self) -> Option<(D2<D1>, D2<D1>)> {
+        let (width, height) = self.img.size();
+        let (width, height) = (D1::from(width), D1::from(height));
+        let mut bounds: Option<(D2<D1>, D2<D1>)> = None;
+        for corner in [(D1::zero(), D1::zero()), (width, D1::zero()), (width, height), (D1::zero(), height)] {
+            let pt = self.screen_coords_to_coords(D2::from(corner))?;
+            let (min, max) = bounds.unwrap_or((pt, pt));
+            bounds = Some((D2::from((min.x.min(pt.x), min.y.min(pt.y))), D2::from((max.x.max(pt.x), max.y.max(pt.y)))));
+        }
+        bounds
     }
 
 
     /// circle is made up of line segments. step is the angle between each point on the circle
     pub fn draw_circle(&mut self, center: D2<D1>, radius: D1, color: Color) {
+        if self.draw_vector(&VectorShape::Circle { center, radius, filled: false }, color) {
+            return
+        }
         self.draw_polygon(center, radius, 16, Angle::min(), color);
     }
 
@@ -306,9 +800,9 @@ impl Cam<'_> {
         loop {
             //let new_point = (center.x + n.cos() * radius, center.y + n.sin() * radius);
             let new_point = center + D2::from((radius, D1::zero())).rotate(n);
-            let bez = (point, new_point);
-            self.draw_line(bez, color);
-            point = bez.1;
+            let segment = (point, new_point);
+            self.draw_line(segment, color);
+            point = segment.1;
             if n > end {
                 break
             }
@@ -317,6 +811,95 @@ impl Cam<'_> {
     }
 
 
+    /// how far apart two points on the draw plane are when they land one screen pixel apart.
+    /// Returns None if the plane is closer than the near distance
+    pub fn plane_units_per_pixel(&self) -> Option<D1> {
+        self.projection().plane_units_per_pixel()
+    }
+
+    /// curves from `points[0]` to `points[2]`, pulled towards `points[1]`
+    pub fn draw_quadratic_bezier(&mut self, points: [D2<D1>; 3], color: Color) {
+        self.draw_cubic_bezier(quad_to_cubic(points), color);
+    }
+
+    /// curves from `points[0]` to `points[3]`, leaving towards `points[1]` and arriving from `points[2]`.
+    /// The curve is split into lines that are within half a screen pixel of it, so it stays smooth at any zoom
+    pub fn draw_cubic_bezier(&mut self, points: [D2<D1>; 4], color: Color) {
+        let Some(tolerance) = self.curve_tolerance() else {
+            return
+        };
+        let flat = D2::flatten_cubic_bezier(points, tolerance);
+        self.draw_polyline(&flat, false, color);
+    }
+
+    /// a smooth curve that goes through every one of `points`
+    pub fn draw_catmull_rom(&mut self, points: &[D2<D1>], color: Color) {
+        if points.len() < 2 {
+            return
+        }
+        let Some(tolerance) = self.curve_tolerance() else {
+            return
+        };
+        // the ends are repeated so the curve reaches the first and last point
+        let at = |i: isize| points[i.clamp(0, points.len() as isize - 1) as usize];
+        let mut flat = vec![points[0]];
+        for i in 0..points.len() as isize - 1 {
+            let cubic = D2::catmull_rom_to_cubic([at(i - 1), at(i), at(i + 1), at(i + 2)]);
+            flat.extend(D2::flatten_cubic_bezier(cubic, tolerance).into_iter().skip(1));
+        }
+        self.draw_polyline(&flat, false, color);
+    }
+
+    /// half a screen pixel in plane units
+    fn curve_tolerance(&self) -> Option<D1> {
+        Some(self.plane_units_per_pixel()? * D1::new_const("0.5"))
+    }
+
+    /// draws the outlines of `path`, curves are split into lines within half a screen pixel of them
+    pub fn stroke_path(&mut self, path: &Path, color: Color) {
+        let Some(tolerance) = self.curve_tolerance() else {
+            return
+        };
+        for contour in path.contours(tolerance) {
+            self.draw_polyline(&contour.points, contour.closed, color);
+        }
+    }
+
+    /// fills the inside of `path`, outlines that aren't closed are filled as if they were
+    pub fn fill_path(&mut self, path: &Path, fill_rule: FillRule, color: Color) {
+        let Some(tolerance) = self.curve_tolerance() else {
+            return
+        };
+        let contours = path.contours(tolerance);
+        let outlines: Vec<&[D2<D1>]> = contours.iter().map(|contour| contour.points.as_slice()).collect();
+        if self.draw_vector(&VectorShape::Fill { contours: &outlines, fill_rule }, color) {
+            return
+        }
+        // every point is at the same height so either all or none of them are clipped
+        let Some(projected) = contours.iter()
+            .map(|contour| contour.points.iter().map(|pt| self.coords_to_screen_coords(*pt)).collect::<Option<Vec<_>>>())
+            .collect::<Option<Vec<_>>>() else {
+            return
+        };
+        let contours: Vec<&[D2<D1>]> = projected.iter().map(|points| points.as_slice()).collect();
+        let z = self.zero_pos.z();
+        let size = self.img.size();
+        for_each_path_pixel(size, &contours, fill_rule, |pt| self.plot(pt, z, color));
+    }
+
+    /// draws lines between `points` in order, if `closed` the last point is connected back to the first
+    pub fn draw_polyline(&mut self, points: &[D2<D1>], closed: bool, color: Color) {
+        if self.draw_vector(&VectorShape::Polyline { points, closed }, color) {
+            return
+        }
+        for line in points.windows(2) {
+            self.draw_line((line[0], line[1]), color);
+        }
+        if closed && points.len() > 2 {
+            self.draw_line((points[points.len() - 1], points[0]), color);
+        }
+    }
+
     pub fn draw_polygon(&mut self, center: D2<D1>, radius: D1, sides: u8, start_angle: Angle, color: Color) {
         if sides == 0 {
             return
@@ -331,9 +914,9 @@ impl Cam<'_> {
             wrapped_angle.wrap();
             let new_point = center + D2::from((radius, D1::zero())).rotate(wrapped_angle);
 
-            let bez = (point, new_point);
-            self.draw_line(bez, color);
-            point = bez.1;
+            let segment = (point, new_point);
+            self.draw_line(segment, color);
+            point = segment.1;
 
             angle += Angle::two_pi() * div;
             if angle > start_angle + Angle::two_pi() {
@@ -342,10 +925,97 @@ impl Cam<'_> {
         }
     }
 
-    pub fn draw_text(&mut self, coords:D2<D1>, string: &str, color: Color, dimensions: D2<u16>, center: bool, background: bool) {
-        let translated = self.coords_to_cam_coords(coords);
-        let coords_u = (translated.0 as u16, translated.1 as u16).into();
-        draw_text(self.img, coords_u, string, color, dimensions, center, background);
+    pub fn fill_shape(&mut self, shape: Shape2D<D1>, color: Color) {
+        match shape {
+            Shape2D::Rect(rect) => {
+                self.fill_rect_corners(rect, color)
+            }
+            Shape2D::Circle(circle) => {
+                self.fill_circle(circle.0, circle.1, color)
+            }
+        }
+    }
+
+    /// text that stays the same size on screen, `coords` is on the draw plane. With a depth buffer the text is
+    /// tested at the height of the plane like any other shape, so nearer planes cover it
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_text(&mut self, coords:D2<D1>, string: &str, font: &Font, color: Color, dimensions: D2<u16>, center: bool, background: bool) {
+        let line_height = D1::from(font.height() as i32 * dimensions.y as i32);
+        if self.draw_vector(&VectorShape::Text { coords, text: string, line_height, center }, color) {
+            return
+        }
+        let Some(translated) = self.coords_to_cam_coords(coords) else {
+            return
+        };
+        // the text functions work in unsigned pixels, skip text anchored off the bottom left of the screen
+        let (Ok(x), Ok(y)) = (u16::try_from(translated.0), u16::try_from(translated.1)) else {
+            return
+        };
+        let coords_u = (x, y).into();
+        let z = self.zero_pos.z();
+        let mut img = Blended::new(&mut *self.img, self.blend_mode).with_opacity(self.alpha);
+        match self.depth.as_deref_mut() {
+            Some(depth) => {
+                let mut img = DepthTested { target: &mut img, depth, z };
+                draw_text(&mut img, coords_u, string, font, color, dimensions, center, background);
+            }
+            None => draw_text(&mut img, coords_u, string, font, color, dimensions, center, background),
+        }
+    }
+
+    /// like [Cam::draw_text] but the text is part of the draw plane, so it rotates, zooms and shrinks with
+    /// perspective like any other shape. `height` is the height of one line in plane units and the text is
+    /// rotated by `angle` about `coords`, the same way [Cam::fill_rect] rotates.
+    ///
+    /// `coords` are the bottom left of the first line or the middle of the text if `center` is set
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_world_text(&mut self, coords: D2<D1>, string: &str, font: &Font, color: Color, height: D1, angle: Angle, center: bool) {
+        if font.height() == 0 {
+            return
+        }
+        let pixel = height / D1::from(font.height());
+        let mut origin = D2::zero();
+        if center {
+            let (width, height) = font.text_size(string);
+            origin.x -= D1::from(width) * pixel / D1::two();
+            origin.y += (D1::from(height) / D1::two() - D1::from(font.height())) * pixel;
+        }
+        let trig_mult = TrigMult::new(-angle);
+        for (x, y, len) in font.pixel_runs(string) {
+            let (x0, x1) = (D1::from(x) * pixel + origin.x, D1::from(x + len) * pixel + origin.x);
+            let (y0, y1) = (D1::from(y) * pixel + origin.y, D1::from(y + 1) * pixel + origin.y);
+            let corners = [(x0, y0), (x1, y0), (x1, y1), (x0, y1)]
+                .map(|corner| coords + rotate_precise(D2::from(corner), trig_mult));
+            self.fill_polygon(&corners, color);
+        }
+    }
+
+    /// draws `text` with the line based [StrokeGlyph] font so it stays sharp at any zoom. `height` is the height of
+    /// capital letters in plane units and the text is rotated by `angle` about `anchor` the same way [Cam::fill_rect] rotates.
+    ///
+    /// `anchor` is on the baseline of the first line, `alignment` decides if it is at the start, middle or end of each line.
+    /// Lines after a '\n' go below
+    pub fn draw_stroke_text(&mut self, anchor: D2<D1>, text: &str, height: D1, angle: Angle, alignment: TextAlign, color: Color) {
+        let scale = height / D1::from(STROKE_CAP_HEIGHT);
+        let trig_mult = TrigMult::new(-angle);
+        for (i, line) in text.split('\n').enumerate() {
+            let mut pen = D2::from((
+                -alignment.offset(stroke_text_width(line, height)),
+                -D1::from(i as i32 * STROKE_LINE_HEIGHT) * scale,
+            ));
+            for c in line.chars() {
+                let glyph = StrokeGlyph::get(c);
+                for stroke in glyph.strokes() {
+                    let points: Vec<D2<D1>> = stroke.iter()
+                        .map(|&(x, y)| anchor + rotate_precise(pen + D2::from((D1::from(x as i32) * scale, D1::from(y as i32) * scale)), trig_mult))
+                        .collect();
+                    for segment in points.windows(2) {
+                        self.draw_line((segment[0], segment[1]), color);
+                    }
+                }
+                pen.x += D1::from(glyph.advance as i32) * scale;
+            }
+        }
     }
 
     pub fn draw_shape(&mut self, shape: Shape2D<D1>, color: Color) {
@@ -358,15 +1028,392 @@ impl Cam<'_> {
             }
         }
     }
+
+    /// stretches `src` over the rectangle with corners `rect` (rel to the draw plane), rotated by `angle`
+    /// about its center the same way [Cam::fill_rect] rotates. The top row of `src` ends up along the top of the rect.
+    ///
+    /// Every screen pixel the rect covers is mapped back onto the plane and into `src`, so the image rotates
+    /// and shrinks with the plane. The alpha of `src` is blended like any other color
+    pub fn draw_image(&mut self, src: &Vec2d<Color>, rect: (D2<D1>, D2<D1>), angle: Angle, sampling: Sampling) {
+        let (src_width, src_height) = src.len();
+        let min = D2::from((rect.0.x.min(rect.1.x), rect.0.y.min(rect.1.y)));
+        let max = D2::from((rect.0.x.max(rect.1.x), rect.0.y.max(rect.1.y)));
+        let rect_size = max - min;
+        if src_width == 0 || src_height == 0 || rect_size.x == D1::zero() || rect_size.y == D1::zero() {
+            return
+        }
+        let center = (min + max) / D2::two();
+        let half = rect_size / D2::two();
+        let corners = [(-half.x, -half.y), (half.x, -half.y), (half.x, half.y), (-half.x, half.y)]
+            .map(|corner| center + rotate_precise(D2::from(corner), TrigMult::new(-angle)));
+        let Some(projected) = corners.iter().map(|pt| self.coords_to_screen_coords(*pt)).collect::<Option<Vec<_>>>() else {
+            return
+        };
+
+        // pixels the rect could cover, clipped to the screen
+        let size = self.img.size();
+        let x0 = projected.iter().map(|pt| pt.x).min().unwrap_or(D1::zero()).to_i32().max(0);
+        let y0 = projected.iter().map(|pt| pt.y).min().unwrap_or(D1::zero()).to_i32().max(0);
+        let x1 = (projected.iter().map(|pt| pt.x).max().unwrap_or(D1::zero()).to_i32() + 1).min(size.0 as i32);
+        let y1 = (projected.iter().map(|pt| pt.y).max().unwrap_or(D1::zero()).to_i32() + 1).min(size.1 as i32);
+        if x0 >= x1 || y0 >= y1 {
+            return
+        }
+
+        // the plane is flat so going from screen pixels to source pixels is linear. Map 3 pixel centers and step
+        // from there instead of mapping every pixel. Wide and checked, a rect much thinner than a source pixel
+        // would overflow D1
+        let to_src = TrigMult::new(angle);
+        let wide = |n: D1| I64F64::from(n.val);
+        let src_scale = (I64F64::from_num(src_width) / wide(rect_size.x), I64F64::from_num(src_height) / wide(rect_size.y));
+        let uv = |cam: &Self, x: i32, y: i32| -> Option<(I64F64, I64F64)> {
+            let pixel_center = D2::from((D1::from(x) + PIXEL_CENTER, D1::from(y) + PIXEL_CENTER));
+            let local = rotate_precise(cam.screen_coords_to_coords(pixel_center)? - center, to_src);
+            // source y goes down from the top of the rect
+            Some(((wide(local.x) + wide(half.x)).checked_mul(src_scale.0)?, (wide(half.y) - wide(local.y)).checked_mul(src_scale.1)?))
+        };
+        let step = |to: (I64F64, I64F64), from: (I64F64, I64F64)| Some((to.0.checked_sub(from.0)?, to.1.checked_sub(from.1)?));
+        let (Some(uv0), Some(uv_x), Some(uv_y)) = (uv(self, x0, y0), uv(self, x0 + 1, y0), uv(self, x0, y0 + 1)) else {
+            return
+        };
+        let (Some(step_x), Some(step_y)) = (step(uv_x, uv0), step(uv_y, uv0)) else {
+            return
+        };
+
+        let z = self.zero_pos.z();
+        let (src_width, src_height) = (I64F64::from_num(src_width), I64F64::from_num(src_height));
+        for y in y0..y1 {
+            let dy = I64F64::from(y - y0);
+            for x in x0..x1 {
+                let dx = I64F64::from(x - x0);
+                let at = |start: I64F64, step_x: I64F64, step_y: I64F64| start.checked_add(step_x.checked_mul(dx)?)?.checked_add(step_y.checked_mul(dy)?);
+                // anything that overflows is nowhere near the source
+                let (Some(u), Some(v)) = (at(uv0.0, step_x.0, step_y.0), at(uv0.1, step_x.1, step_y.1)) else {
+                    continue
+                };
+                if u < I64F64::ZERO || v < I64F64::ZERO || u >= src_width || v >= src_height {
+                    continue
+                }
+                let color = sample_image(src, D2::from((D1 { val: u.to_num() }, D1 { val: v.to_num() })), sampling);
+                if color.alpha != 0 {
+                    self.plot((x, y), z, color);
+                }
+            }
+        }
+    }
+
+    /// [Cam::draw_image] with a texture from `atlas`
+    pub fn draw_texture(
+        &mut self,
+        atlas: &TextureAtlas,
+        texture: TextureHandle,
+        rect: (D2<D1>, D2<D1>),
+        angle: Angle,
+        sampling: Sampling,
+    ) -> Result<(), TextureError> {
+        let texture = atlas.get(texture)?;
+        self.draw_image(texture, rect, angle, sampling);
+        Ok(())
+    }
+}
+
+/// offset from the corner of a pixel to its center
+const PIXEL_CENTER: D1 = D1::new_const("0.5");
+
+/// how [Cam::draw_shape3d] draws a shape
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Shape3DStyle {
+    /// every edge, including the ones on the far side
+    Wireframe(Color),
+    /// the faces that point towards the camera, darker the further they are turned away from the light.
+    /// `edges` outlines them
+    Shaded { color: Color, edges: Option<Color> },
+}
+
+/// half the width of the cross [Cam::draw_shape3d] draws for a point, in screen pixels
+const POINT_MARKER_SIZE: i32 = 2;
+
+/// corners of a prism that are connected, indexed like in [Cam::draw_rect_prism]
+const PRISM_EDGES: [(usize, usize); 12] = [
+    (0, 1), (2, 3), (4, 5), (6, 7),
+    (0, 2), (1, 3), (4, 6), (5, 7),
+    (0, 4), (1, 5), (2, 6), (3, 7),
+];
+
+/// faces of a prism wound counterclockwise seen from outside, with the direction they point
+const PRISM_FACES: [([usize; 4], (i32, i32, i32)); 6] = [
+    ([0, 2, 3, 1], (0, 0, -1)),
+    ([4, 5, 7, 6], (0, 0, 1)),
+    ([0, 1, 5, 4], (0, -1, 0)),
+    ([2, 6, 7, 3], (0, 1, 0)),
+    ([0, 4, 6, 2], (-1, 0, 0)),
+    ([1, 3, 7, 5], (1, 0, 0)),
+];
+
+/// `color` lit by a light above the draw plane that is a little towards -x and -y,
+/// faces turned away from it still keep some of their color
+fn shade(color: Color, normal: (i32, i32, i32)) -> Color {
+    let light = (-1, -2, 3);
+    let ambient = D1::new_const("0.4");
+    let facing = D1::from(normal.0 * light.0 + normal.1 * light.1 + normal.2 * light.2)
+        / D1::from(light.0 * light.0 + light.1 * light.1 + light.2 * light.2).sqrt();
+    let brightness = ambient + (D1::one() - ambient) * facing.max(D1::zero());
+    let shaded = color.merge(Color::black(), D1::one() - brightness);
+    Color::new_with_alpha(shaded.r, shaded.g, shaded.b, color.alpha)
+}
+
+/// closest [Cam::draw_grid] lets its lines get on screen before doubling the spacing, in pixels
+const MIN_GRID_GAP: D1 = D1::new_const("6");
+/// closest the labels of [Cam::draw_axes] get to each other, in pixels
+const MIN_LABEL_GAP: D1 = D1::new_const("40");
+/// the grid spacing stops doubling here so it can't overflow
+const MAX_GRID_STEP: D1 = D1::new_const("1000000000");
+
+/// index (counted from the plane origin) and position of every grid line from `min` to `max`
+fn grid_lines(min: D1, max: D1, step: D1) -> impl Iterator<Item = (i64, D1)> {
+    let first = (min / step).ceil().val.to_num::<i64>();
+    let last = (max / step).floor().val.to_num::<i64>();
+    (first..=last).map(move |i| (i, D1::from(i) * step))
+}
+
+/// major lines keep `color`, the minor lines that stay when the spacing doubles are `minor` and the rest `fading`
+fn grid_line_color(i: i64, major_every: u32, color: Color, minor: Color, fading: Color) -> Color {
+    if major_every != 0 && i % major_every as i64 == 0 {
+        color
+    } else if i % 2 == 0 {
+        minor
+    } else {
+        fading
+    }
+}
+
+/// how [Cam::draw_image] picks colors from the source image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Sampling {
+    /// the closest source pixel, keeps pixel art sharp
+    #[default]
+    Nearest,
+    /// blends the four closest source pixels, smoother when the image is scaled
+    Bilinear,
+}
+
+/// color of `src` at `uv`, measured in pixels from the top left corner of the image so pixel centers are at .5.
+/// Anything outside of the image takes the color of the closest edge pixel
+pub fn sample_image(src: &Vec2d<Color>, uv: D2<D1>, sampling: Sampling) -> Color {
+    let (width, height) = src.len();
+    if width == 0 || height == 0 {
+        return Color::new_transparent()
+    }
+    let clamp = |i: i32, len: usize| i.clamp(0, len as i32 - 1) as usize;
+    match sampling {
+        Sampling::Nearest => src[(clamp(uv.x.to_i32(), width), clamp(uv.y.to_i32(), height))],
+        Sampling::Bilinear => {
+            let x = uv.x - PIXEL_CENTER;
+            let y = uv.y - PIXEL_CENTER;
+            let (x0, y0) = (x.to_i32(), y.to_i32());
+            // how far towards the next pixel, out of 256
+            let tx = ((x - D1::from(x0)) * D1::from(256)).to_i32().clamp(0, 256) as u32;
+            let ty = ((y - D1::from(y0)) * D1::from(256)).to_i32().clamp(0, 256) as u32;
+            let corners = [
+                (src[(clamp(x0, width), clamp(y0, height))], (256 - tx) * (256 - ty)),
+                (src[(clamp(x0 + 1, width), clamp(y0, height))], tx * (256 - ty)),
+                (src[(clamp(x0, width), clamp(y0 + 1, height))], (256 - tx) * ty),
+                (src[(clamp(x0 + 1, width), clamp(y0 + 1, height))], tx * ty),
+            ];
+            // blend premultiplied so transparent pixels don't darken the edges of what they touch
+            let (mut r, mut g, mut b, mut alpha) = (0u32, 0u32, 0u32, 0u32);
+            for (color, weight) in corners {
+                let weight = weight * color.alpha as u32;
+                r += color.r as u32 * weight;
+                g += color.g as u32 * weight;
+                b += color.b as u32 * weight;
+                alpha += weight;
+            }
+            if alpha == 0 {
+                return Color::new_transparent()
+            }
+            let channel = |c: u32| ((c + alpha / 2) / alpha) as u8;
+            // the weights add up to 65536 * 255 for an opaque result
+            Color::new_with_alpha(channel(r), channel(g), channel(b), ((alpha + (1 << 15)) >> 16) as u8)
+        }
+    }
+}
+
+/// rotates with the full precision of [D1], `D2::rotate_with_trig_mult` drops down to 12 fractional bits
+fn rotate_precise(pt: D2<D1>, trig_mult: TrigMult) -> D2<D1> {
+    let (sin, cos) = (D1::from(trig_mult.sin), D1::from(trig_mult.cos));
+    D2::from((pt.x * cos - pt.y * sin, pt.x * sin + pt.y * cos))
+}
+
+/// returns the corners of a regular polygon with `sides` sides, starting at `start_angle`
+pub fn polygon_points(center: D2<D1>, radius: D1, sides: u8, start_angle: Angle) -> Vec<D2<D1>> {
+    let div = Angle::from(1.0) / Angle::from(sides as f32);
+    (0..sides).map(|i| {
+        let mut angle = start_angle + Angle::two_pi() * div * Angle::from(i as f32);
+        angle.wrap();
+        center + D2::from((radius, D1::zero())).rotate(angle)
+    }).collect()
+}
+
+/// scanline polygon fill using the even-odd rule.
+/// `points` are pixel coords rel to bottom left of screen, a pixel is filled if its center is inside the outline
+pub fn fill_polygon<T: RenderTarget + ?Sized>(img: &mut T, points: &[D2<D1>], color: Color) {
+    for_each_polygon_pixel(img.size(), points, |pt| draw_pixel(img, pt, color));
+}
+
+/// calls `plot` with every on screen pixel that [fill_polygon] would fill
+pub fn for_each_polygon_pixel<F: FnMut((i32, i32))>(size: (usize, usize), points: &[D2<D1>], plot: F) {
+    for_each_path_pixel(size, &[points], FillRule::EvenOdd, plot);
+}
+
+/// calls `plot` with every on screen pixel inside of `contours`, which are all treated as closed.
+/// Overlaps and holes are decided by `fill_rule`
+pub fn for_each_path_pixel<F: FnMut((i32, i32))>(size: (usize, usize), contours: &[&[D2<D1>]], fill_rule: FillRule, mut plot: F) {
+    let contours: Vec<&[D2<D1>]> = contours.iter().copied().filter(|points| points.len() >= 3).collect();
+    if contours.is_empty() {
+        return
+    }
+    let (width, height) = size;
+    let half = D1::one() / D1::two();
+
+    let mut min_y = D1::max();
+    let mut max_y = D1::min();
+    for pt in contours.iter().flat_map(|points| points.iter()) {
+        min_y = min_y.min(pt.y);
+        max_y = max_y.max(pt.y);
+    }
+    // clamp before converting so huge coords can't overflow
+    let min_y = min_y.clamp(D1::zero(), D1::from(height));
+    let max_y = max_y.clamp(D1::zero(), D1::from(height));
+    let y_start = (min_y - half).ceil().to_i32().max(0);
+    let y_end = (max_y - half).ceil().to_i32().min(height as i32);
+
+    // (x, +1 for edges going up and -1 for edges going down)
+    let mut crossings: Vec<(D1, i32)> = Vec::new();
+    for y in y_start..y_end {
+        // sample each row at the center of its pixels
+        let sample_y = D1::from(y) + half;
+        crossings.clear();
+        for points in &contours {
+            for i in 0..points.len() {
+                let a = points[i];
+                let b = points[(i + 1) % points.len()];
+                if (a.y <= sample_y) != (b.y <= sample_y) {
+                    crossings.push((edge_crossing(a, b, sample_y, width), if b.y > a.y { 1 } else { -1 }));
+                }
+            }
+        }
+        crossings.sort();
+
+        let mut winding = 0;
+        for (i, &(x0, direction)) in crossings.iter().enumerate() {
+            winding += direction;
+            let Some(&(x1, _)) = crossings.get(i + 1) else {
+                break
+            };
+            if !fill_rule.is_inside(winding) {
+                continue
+            }
+            let x0 = x0.clamp(D1::zero(), D1::from(width));
+            let x1 = x1.clamp(D1::zero(), D1::from(width));
+            let x_start = (x0 - half).ceil().to_i32().max(0);
+            let x_end = (x1 - half).ceil().to_i32().min(width as i32);
+            for x in x_start..x_end {
+                plot((x, y));
+            }
+        }
+    }
+}
+
+/// x where the edge from `a` to `b` crosses `y`, clamped to just outside of a screen `width` wide.
+/// Wide so that edges between far off screen points can't overflow
+fn edge_crossing(a: D2<D1>, b: D2<D1>, y: D1, width: usize) -> D1 {
+    let wide = |n: D1| I64F64::from(n.val);
+    let t = (wide(y) - wide(a.y)) / (wide(b.y) - wide(a.y));
+    let x = wide(a.x) + t * (wide(b.x) - wide(a.x));
+    let x = x.clamp(-I64F64::ONE, I64F64::from_num(width) + I64F64::ONE);
+    D1 { val: x.to_num() }
+}
+
+// cohen sutherland outcodes
+const INSIDE: u8 = 0;
+const LEFT: u8 = 1;
+const RIGHT: u8 = 2;
+const BOTTOM: u8 = 4;
+const TOP: u8 = 8;
+
+fn outcode(pt: (i64, i64), max: (i64, i64)) -> u8 {
+    let mut code = INSIDE;
+    if pt.0 < 0 {
+        code |= LEFT;
+    } else if pt.0 > max.0 {
+        code |= RIGHT;
+    }
+    if pt.1 < 0 {
+        code |= BOTTOM;
+    } else if pt.1 > max.1 {
+        code |= TOP;
+    }
+    code
+}
+
+/// clips a line to the pixels of a screen of `size` with the cohen sutherland algorithm.
+/// Returns None if no part of the line is on screen
+pub fn clip_line(segment: Segment, size: (usize, usize)) -> Option<Segment> {
+    if size.0 == 0 || size.1 == 0 {
+        return None
+    }
+    // i64 so the intersection math can't overflow
+    let max = (size.0 as i64 - 1, size.1 as i64 - 1);
+    let mut p0 = (segment.0 .0 as i64, segment.0 .1 as i64);
+    let mut p1 = (segment.1 .0 as i64, segment.1 .1 as i64);
+    let mut code0 = outcode(p0, max);
+    let mut code1 = outcode(p1, max);
+    loop {
+        if code0 | code1 == INSIDE {
+            return Some(((p0.0 as i32, p0.1 as i32), (p1.0 as i32, p1.1 as i32)))
+        }
+        if code0 & code1 != INSIDE {
+            // both points are on the same outside side of the screen
+            return None
+        }
+        let code = if code0 != INSIDE { code0 } else { code1 };
+        let (dx, dy) = (p1.0 - p0.0, p1.1 - p0.1);
+        let pt = if code & TOP != INSIDE {
+            (p0.0 + dx * (max.1 - p0.1) / dy, max.1)
+        } else if code & BOTTOM != INSIDE {
+            (p0.0 + dx * -p0.1 / dy, 0)
+        } else if code & RIGHT != INSIDE {
+            (max.0, p0.1 + dy * (max.0 - p0.0) / dx)
+        } else {
+            (0, p0.1 + dy * -p0.0 / dx)
+        };
+        if code == code0 {
+            p0 = pt;
+            code0 = outcode(p0, max);
+        } else {
+            p1 = pt;
+            code1 = outcode(p1, max);
+        }
+    }
 }
 
 // bresenham's line algorithm
-/// rel to bottom left of screen
-pub fn draw_bezier(img: &mut Vec2d<u32>, bez: Bez, color: Color) {
-    let mut x0 = bez.0 .0 as i32;
-    let mut y0 = bez.0 .1 as i32;
-    let x1 = bez.1 .0 as i32;
-    let y1 = bez.1 .1 as i32;
+/// rel to bottom left of screen, the line is clipped to the screen first so off screen parts cost nothing
+pub fn draw_segment<T: RenderTarget + ?Sized>(img: &mut T, segment: Segment, color: Color) {
+    for_each_line_pixel(img.size(), segment, |pt| draw_pixel(img, pt, color));
+}
+
+#[deprecated(note = "draws a straight line, use draw_segment or Cam::draw_cubic_bezier")]
+pub fn draw_bezier<T: RenderTarget + ?Sized>(img: &mut T, segment: Segment, color: Color) {
+    draw_segment(img, segment, color);
+}
+
+/// calls `plot` with every on screen pixel that [draw_segment] would draw
+pub fn for_each_line_pixel<F: FnMut((i32, i32))>(size: (usize, usize), segment: Segment, mut plot: F) {
+    let Some(((mut x0, mut y0), (x1, y1))) = clip_line(segment, size) else {
+        return
+    };
 
     let dx = (x1 - x0).abs();
     let dy = (y1 - y0).abs();
@@ -374,7 +1421,7 @@ pub fn draw_bezier(img: &mut Vec2d<u32>, bez: Bez, color: Color) {
     let sy = if y0 < y1 { 1 } else { -1 };
     let mut err = dx - dy;
     loop {
-        draw_pixel(img, (x0 as usize, y0 as usize), color);
+        plot((x0, y0));
 
         if x0 == x1 && y0 == y1 {
             break
@@ -393,21 +1440,20 @@ pub fn draw_bezier(img: &mut Vec2d<u32>, bez: Bez, color: Color) {
 
 /// also bresenham's line algorithm
 /// maybe faster than other line drawing fn
-pub fn draw_line(img: &mut Vec2d<u32>, bez: Bez, color: Color) {
-    let dx = (bez.1 .0 as i32 - bez.0 .0 as i32).abs();
-    let dy = (bez.1 .1 as i32 - bez.0 .1 as i32).abs();
+pub fn draw_line<T: RenderTarget + ?Sized>(img: &mut T, segment: Segment, color: Color) {
+    let dx = (segment.1 .0 as i64 - segment.0 .0 as i64).abs();
+    let dy = (segment.1 .1 as i64 - segment.0 .1 as i64).abs();
     if dx > dy {
-        draw_line_horizontal(img, bez, color);
+        draw_line_horizontal(img, segment, color);
     } else {
-        draw_line_vertical(img, bez, color);
+        draw_line_vertical(img, segment, color);
     }
 }
 
-pub fn draw_line_horizontal(img: &mut Vec2d<u32>, bez: Bez, color: Color) {
-    let mut x0 = bez.0 .0 as i32;
-    let mut y0 = bez.0 .1 as i32;
-    let mut x1 = bez.1 .0 as i32;
-    let mut y1 = bez.1 .1 as i32;
+pub fn draw_line_horizontal<T: RenderTarget + ?Sized>(img: &mut T, segment: Segment, color: Color) {
+    let Some(((mut x0, mut y0), (mut x1, mut y1))) = clip_line(segment, img.size()) else {
+        return
+    };
 
     if x0 > x1 {
         swap(&mut x0, &mut x1);
@@ -422,7 +1468,7 @@ pub fn draw_line_horizontal(img: &mut Vec2d<u32>, bez: Bez, color: Color) {
         let mut y = y0;
         let mut p = 2 * dy - dx;
         for i in 0..dx+1 {
-            draw_pixel(img, ((x0 + i) as usize, y as usize), color);
+            draw_pixel(img, (x0 + i, y), color);
             if p >= 0 {
                 y += dir;
                 p -= 2*dx;
@@ -432,11 +1478,10 @@ pub fn draw_line_horizontal(img: &mut Vec2d<u32>, bez: Bez, color: Color) {
     }   
 }
 
-pub fn draw_line_vertical(img: &mut Vec2d<u32>, bez: Bez, color: Color) {
-    let mut x0 = bez.0 .0 as i32;
-    let mut y0 = bez.0 .1 as i32;
-    let mut x1 = bez.1 .0 as i32;
-    let mut y1 = bez.1 .1 as i32;
+pub fn draw_line_vertical<T: RenderTarget + ?Sized>(img: &mut T, segment: Segment, color: Color) {
+    let Some(((mut x0, mut y0), (mut x1, mut y1))) = clip_line(segment, img.size()) else {
+        return
+    };
 
     if y0 > y1 {
         swap(&mut x0, &mut x1);
@@ -451,7 +1496,7 @@ pub fn draw_line_vertical(img: &mut Vec2d<u32>, bez: Bez, color: Color) {
         let mut x = x0;
         let mut p = 2 * dx - dy;
         for i in 0..dy+1 {
-            draw_pixel(img, (x as usize, (y0 + i) as usize), color);
+            draw_pixel(img, (x, y0 + i), color);
             if p >= 0 {
                 x += dir;
                 p -= 2*dy;
@@ -469,47 +1514,68 @@ pub fn draw_line_vertical(img: &mut Vec2d<u32>, bez: Bez, color: Color) {
 pub fn from_rgb(color: (u8, u8, u8)) -> u32 {
     (color.2 as u32) | ((color.1 as u32) << 8) | ((color.0 as u32) << 16)
 }
+/// packs as ARGB, same channel order as [from_rgb] with alpha in the top byte
 pub fn from_rgba(color: (u8, u8, u8, u8)) -> u32 {
-    (color.0 as u32) | ((color.1 as u32) << 8) | ((color.2 as u32) << 16) | ((color.3 as u32) << 24)
-    //((color.0 as u32) << 24) | ((color.1 as u32) << 16) | ((color.2 as u32) << 8) | (color.3 as u32)
+    (color.2 as u32) | ((color.1 as u32) << 8) | ((color.0 as u32) << 16) | ((color.3 as u32) << 24)
+}
+
+/// makes a depth buffer for [Cam::with_depth_buffer] where nothing has been drawn yet
+pub fn new_depth_buffer(len: (usize, usize)) -> Vec2d<D1> {
+    Vec2d::new_with_fill(len, |_| D1::min())
 }
 
-/// rel to bottom left of screen
-pub fn draw_pixel(img: &mut Vec2d<u32>, coords: (usize, usize), color: Color) {
-    let y = if let Some(y) = img.len().1.checked_sub(1) {
+/// resets a depth buffer so that the next frame can be drawn
+pub fn clear_depth_buffer(depth: &mut Vec2d<D1>) {
+    depth.get_mut_flat1().fill(D1::min());
+}
+
+/// returns true and stores `z` if `z` is at least as high as what is already at `coords` (rel to bottom left of screen).
+/// Equal heights pass so a plane can draw over itself
+pub fn test_and_set_depth(depth: &mut Vec2d<D1>, coords: (i32, i32), z: D1) -> bool {
+    let (Ok(x), Ok(y_up)) = (usize::try_from(coords.0), usize::try_from(coords.1)) else {
+        return false
+    };
+    let Some(y) = depth.len().1.checked_sub(1).and_then(|y| y.checked_sub(y_up)) else {
+        return false
+    };
+    match depth.get_mut((x, y)) {
+        Some(stored) if z >= *stored => {
+            *stored = z;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// rel to bottom left of screen, does nothing if coords are off screen
+pub fn draw_pixel<T: RenderTarget + ?Sized>(img: &mut T, coords: (i32, i32), color: Color) {
+    draw_pixel_blend(img, coords, color, BlendMode::Normal);
+}
+
+/// [draw_pixel] with a blend mode other than [BlendMode::Normal]
+pub fn draw_pixel_blend<T: RenderTarget + ?Sized>(img: &mut T, coords: (i32, i32), color: Color, blend_mode: BlendMode) {
+    let (Ok(x), Ok(y_up)) = (usize::try_from(coords.0), usize::try_from(coords.1)) else {
+        return
+    };
+    let (width, height) = img.size();
+    let y = if let Some(y) = height.checked_sub(1) {
         y
     } else {
         return
     };
-    let y = if let Some(y) = y.checked_sub(coords.1 as usize) {
+    let y = if let Some(y) = y.checked_sub(y_up) {
         y
     } else {
         return
     };
-    match img.get_mut((coords.0 as usize, y)) {
-        Some(p) => {
-            if color.alpha == 255 {
-                *p = color.to_u32();
-            } else {
-                let new_bytes = color.to_u32().to_be_bytes();
-                let old_bytes = p.to_be_bytes();
-    
-                let r = old_bytes[1] as f32
-                    + (new_bytes[1] as f32 - old_bytes[1] as f32) / 255.0 * color.alpha as f32;
-                let g = old_bytes[2] as f32
-                    + (new_bytes[2] as f32 - old_bytes[2] as f32) / 255.0 * color.alpha as f32;
-                let b = old_bytes[3] as f32
-                    + (new_bytes[3] as f32 - old_bytes[3] as f32) / 255.0 * color.alpha as f32;
-    
-                *p = from_rgb((r as u8, g as u8, b as u8));
-            }
-        }
-        None => (),
+    if x < width {
+        img.blend_pixel((x, y), color, blend_mode);
     }
 }
 
-pub fn draw_rectangle(
-    img: &mut Vec2d<u32>,
+/// rel to bottom left of screen. Like every free drawing function, translucent colors are blended with what is already there
+pub fn draw_rectangle<T: RenderTarget + ?Sized>(
+    img: &mut T,
     coords: D2<u16>,
     width: D2<u16>,
     color: Color,
@@ -523,187 +1589,121 @@ pub fn draw_rectangle(
     }
     for x in coords2.x..(coords2.x + width.x) {
         for y in coords2.y..(coords2.y + width.y) {
-            *img.get_mut_clamped((x as isize, (img.len().1 as u16 - y - 1) as isize))
-                .unwrap() = color.to_u32();
+            put_pixel_clamped(img, (x, y), color);
         }
     }
 }
 
-pub fn draw_rectangle_corners(
-    img: &mut Vec2d<u32>,
+pub fn draw_rectangle_corners<T: RenderTarget + ?Sized>(
+    img: &mut T,
     corners: (D2<u16>, D2<u16>),
     color: Color,
 ) {
     for x in corners.0.x..corners.1.x {
         for y in corners.0.y..corners.1.y {
-            *img.get_mut_clamped((x as isize, (img.len().1 as u16 - y - 1) as isize))
-                .unwrap() = color.to_u32();
+            put_pixel_clamped(img, (x, y), color);
         }
     }
 }
 
-pub fn draw_border(img: &mut Vec2d<u32>, corners: (D2<u16>, D2<u16>), color: Color) {
+/// rel to bottom left of screen, off screen coords get clamped to the closest edge pixel.
+/// Blends with [BlendMode::Normal], so only opaque colors overwrite
+fn put_pixel_clamped<T: RenderTarget + ?Sized>(img: &mut T, coords: (u16, u16), color: Color) {
+    let (width, height) = img.size();
+    if width == 0 || height == 0 {
+        return
+    }
+    let x = (coords.0 as usize).min(width - 1);
+    let y = height.saturating_sub(coords.1 as usize + 1);
+    img.blend_pixel((x, y), color, BlendMode::Normal);
+}
+
+pub fn draw_border<T: RenderTarget + ?Sized>(img: &mut T, corners: (D2<u16>, D2<u16>), color: Color) {
     let y2 = corners.0.y;
     for x2 in (corners.0.x)..(corners.1.x) {
-        draw_pixel(img, (x2 as usize, y2 as usize), color);
+        draw_pixel(img, (x2 as i32, y2 as i32), color);
     }
     let y2 = corners.1.y - 1;
     for x2 in (corners.0.x)..(corners.1.x) {
-        draw_pixel(img, (x2 as usize, y2 as usize), color);
+        draw_pixel(img, (x2 as i32, y2 as i32), color);
     }
     let x2 = corners.0.x;
     for y2 in (corners.0.y)..(corners.1.y) {
-        draw_pixel(img, (x2 as usize, y2 as usize), color);
+        draw_pixel(img, (x2 as i32, y2 as i32), color);
     }
     let x2 = corners.1.x - 1;
     for y2 in (corners.0.y)..(corners.1.y) {
-        draw_pixel(img, (x2 as usize, y2 as usize), color);
+        draw_pixel(img, (x2 as i32, y2 as i32), color);
     }
 }
 
-const LETTER_DIM: D2<u16> = D2::new_const(5, 5);
-const LETTER_SPACE_WIDTH: u16 = 1;
-
-pub fn draw_text(
-    img: &mut Vec2d<u32>,
-    mut coords: D2<u16>,
+/// draws `string` with every font pixel blown up to a `dimensions` sized rectangle. `coords` are the bottom left of the
+/// first line (rel to bottom left of screen), lines after a '\n' go below it. Characters the font doesn't have are drawn as a box
+#[allow(clippy::too_many_arguments)]
+pub fn draw_text<T: RenderTarget + ?Sized>(
+    img: &mut T,
+    coords: D2<u16>,
     string: &str,
+    font: &Font,
     color: Color,
     dimensions: D2<u16>, // use (2, 3) normally
     center: bool,
     background: bool,
 ) {
-    // turn string into vector of characters
-    let char_vec: Vec<char> = string.to_lowercase().chars().collect();
-    let length = char_vec.len() as u16;
-
-    // if true draw background box
-    if background {
-        let box_coords = coords - dimensions * D2::from(2);
-        let mut box_dimensions = dimensions * D2::from(6);
-
-        box_dimensions.x *= length;
-        box_dimensions += dimensions * D2::from(4);
-
-        draw_rectangle(
-            img,
-            box_coords,
-            box_dimensions,
-            Color::new(50, 50, 50),
-            center,
-        )
-    }
+    let pixel = (dimensions.x as i32, dimensions.y as i32);
+    let (width, height) = font.text_size(string);
+    let (width, height) = (width as i32, height as i32);
 
-    // if true, center the text by offsetting the coords
+    // bottom left of the first line
+    let mut origin = (coords.x as i32, coords.y as i32);
     if center {
-        coords = (
-            coords.x - length * dimensions.x * 3,
-            coords.y - dimensions.y * 5 / 2,
-        ).into();
+        origin.0 -= width * pixel.0 / 2;
+        origin.1 += height * pixel.1 / 2 - font.height() as i32 * pixel.1;
     }
-    
-    // its drawin time, letter_offset changes after each letter, (x,y) changes each pixel of each letter, and (x2, y2) are the actual coords of the pixel fed to the draw function.
-    let mut letter_offset = (0, 0);
-
-    for letter in char_vec {
-        // deal with functional characters
-        // if letter == "\n".chars().next().unwrap() { // i wonder if this crashes if you just do "\" and nothing after
-        //     letter_offset.0 = 0; // reset x offset
-        //     letter_offset.1 -= dimensions.y * 8; // one line down
-        //     continue
-        // }
-
-        // all other characters just get drawn normally
-        let letter_a = get_letter_array(letter);
-        for x in 0..5usize {
-            let x2 = coords.x + (x as u16)*dimensions.x + letter_offset.0;
-            for y in 0..5usize {
-                let y2 = coords.y + (y as u16)*dimensions.y + letter_offset.1;
-                if letter_a[x][y] == true {
-                    draw_rectangle(img, (x2, y2).into(), dimensions, color, center);
-                }
-            }
+
+    // if true draw background box
+    if background {
+        let bottom = origin.1 + (font.height() as i32 - height) * pixel.1;
+        let box_coords = (origin.0 - pixel.0 * 2, bottom - pixel.1 * 2);
+        let box_end = (origin.0 + (width + 2) * pixel.0, bottom + (height + 2) * pixel.1);
+        draw_rectangle_corners(img, (to_u16_coords(box_coords), to_u16_coords(box_end)), Color::new(50, 50, 50));
+    }
+
+    for (x, y, len) in font.pixel_runs(string) {
+        let start = (origin.0 + x * pixel.0, origin.1 + y * pixel.1);
+        let end = (start.0 + len * pixel.0, start.1 + pixel.1);
+        // off screen to the left or bottom gets cut off instead of clamped to the edge
+        if end.0 > 0 && end.1 > 0 {
+            draw_rectangle_corners(img, (to_u16_coords(start), to_u16_coords(end)), color);
         }
-        letter_offset.0 += dimensions.x * 6;
     }
 }
 
-pub fn draw_text_corners(
-    img: &mut Vec2d<u32>,
+fn to_u16_coords(coords: (i32, i32)) -> D2<u16> {
+    D2::from((coords.0.clamp(0, u16::MAX as i32) as u16, coords.1.clamp(0, u16::MAX as i32) as u16))
+}
+
+/// wraps `string` to fit between `corners` (bottom left and top right, rel to bottom left of screen) and draws it.
+/// Lines that don't fit are left out, the returned layout says if that happened.
+/// Use [TextLayout] directly for justified text, padding or color spans
+pub fn draw_text_corners<T: RenderTarget + ?Sized>(
+    img: &mut T,
     corners: (D2<u16>, D2<u16>),
     string: &str,
+    font: &Font,
     color: Color,
     pixel_dim: D2<u16>, // use (2, 3) normally
     centered: (bool, bool), // true if centered in x and y dimensions
-    ) {
-    let mut coords = D2::from((corners.0.x, corners.1.y - pixel_dim.y*LETTER_DIM.y)); // start at top left corner of box
-    let mut lines = Vec::new();
-    let mut current_line = String::new();
-    let word_vec = string.split_string();
-
-    for mut word in word_vec {
-        if word == "\n" {
-            // move down a line and continue to not draw anything
-            coords.x = corners.0.x;
-            coords.y -= word.string_dim_pixels(pixel_dim).y + LETTER_SPACE_WIDTH*pixel_dim.y;
-            lines.push(current_line);
-            current_line = String::new();
-            continue
-        }
-
-        // single word is too long to fit on a line by itself
-        if word.string_dim_pixels(pixel_dim).x > corners.dim().x {
-            if coords.x > corners.0.x {
-                // move down a line
-                coords.x = corners.0.x;
-                coords.y -= word.string_dim_pixels(pixel_dim).y + LETTER_SPACE_WIDTH*pixel_dim.y;
-                lines.push(current_line);
-                current_line = String::new();
-            }
-            let fit_len = corners.dim().x * word.len() as u16 / word.string_dim_pixels(pixel_dim).x;
-            word = word.as_str().substring(0, fit_len as usize).to_string();
-        } else if coords.x + word.string_dim_pixels(pixel_dim).x > corners.1.x {
-            // word doesn't fit in the, move down a line
-            coords.x = corners.0.x;
-            coords.y -= word.string_dim_pixels(pixel_dim).y + LETTER_SPACE_WIDTH*pixel_dim.y;
-            lines.push(current_line);
-            current_line = String::new();
-        }
-        if coords.y < corners.0.y {
-            break
-        }
-
-        // draw_text(img, coords, &word, color, pixel_dim, false, false);
-        if current_line.len() > 0 {
-            current_line.push(' ');
-        }
-        current_line.push_str(&word);
-        coords.x += word.string_dim_pixels(pixel_dim).x + LETTER_DIM.x*pixel_dim.x;
-    }
-    if current_line.len() > 0 {
-        lines.push(current_line);
-    }
-
-    // ------------------------------------------------------------------------------------------
-    let mut coords = D2::from((corners.0.x, corners.1.y - pixel_dim.y*LETTER_DIM.y)); // start at top left corner of box
-    let y_offset;
-    if centered.1 {
-        let all_lines_height = ((lines.len() as u16) * LETTER_DIM.y + LETTER_SPACE_WIDTH*(lines.len() as u16 - 1))*pixel_dim.y;
-        y_offset = (corners.dim().y - all_lines_height) / 2;
-    } else {
-        y_offset = 0;
-    }
-    for line in lines {
-        let x_offset = if centered.0 {
-            (corners.dim().x - line.string_dim_pixels(pixel_dim).x) / 2
-        } else {
-            0
-        };
-        // println!("line: {:?}, {:?}-{:?}", line, corners.dim().x, line.string_dim_pixels(pixel_dim).x);
-        // println!("dim of {:?} is: {:?}. at coords {:?}", line, line.string_dim_pixels(pixel_dim), coords + D2::from((x_offset, 0)));
-        draw_text(img, coords + D2::from((x_offset, 0)) - D2::from((0, y_offset)), &line, color, pixel_dim, false, false);
-        coords.y -= line.string_dim_pixels(pixel_dim).y + LETTER_SPACE_WIDTH*pixel_dim.y;
-    }
+    ) -> TextLayout {
+    let size = D2::from((corners.1.x.saturating_sub(corners.0.x), corners.1.y.saturating_sub(corners.0.y)));
+    let align = if centered.0 { TextAlign::Center } else { TextAlign::Left };
+    let options = TextLayoutOptions::new(pixel_dim, color)
+        .with_align(align)
+        .with_vertical_center(centered.1)
+        .with_markup(false);
+    let layout = TextLayout::new(string, font, size, &options);
+    layout.draw(img, corners.0, font);
+    layout
 }
 
 
@@ -790,14 +1790,13 @@ impl StringUtils for str {
 }
 
 pub trait PixelLen {
-    fn string_dim_pixels(&self, pixel_dim: D2<u16>) -> D2<u16>;
+    fn string_dim_pixels(&self, font: &Font, pixel_dim: D2<u16>) -> D2<u16>;
     fn split_string(&self) -> Vec<String>;
 }
 impl PixelLen for &str {
     // returns the pixel dimensions of the string in one long line, does not consider newline characters
-    fn string_dim_pixels(&self, pixel_dim: D2<u16>) -> D2<u16> {
-        return ((self.len() as u16 * LETTER_DIM.x + (self.len() as u16 - 1) * LETTER_SPACE_WIDTH) * pixel_dim.x,
-        LETTER_DIM.y * pixel_dim.y).into()
+    fn string_dim_pixels(&self, font: &Font, pixel_dim: D2<u16>) -> D2<u16> {
+        return (font.text_width(self) * pixel_dim.x, font.height() * pixel_dim.y).into()
     }
     /// splits string into vector of words, separated by spaces and special characters like \n
     fn split_string(&self) -> Vec<String> {
@@ -825,9 +1824,8 @@ impl PixelLen for &str {
 }
 
 impl PixelLen for String {
-    fn string_dim_pixels(&self, pixel_dim: D2<u16>) -> D2<u16> {
-        return ((self.len() as u16 * LETTER_DIM.x + (self.len() as u16 - 1) * LETTER_SPACE_WIDTH) * pixel_dim.x,
-        LETTER_DIM.y * pixel_dim.y).into()
+    fn string_dim_pixels(&self, font: &Font, pixel_dim: D2<u16>) -> D2<u16> {
+        return (font.text_width(self) * pixel_dim.x, font.height() * pixel_dim.y).into()
     }
     /// splits string into vector of words, separated by spaces
     fn split_string(&self) -> Vec<String> {
@@ -854,467 +1852,348 @@ impl PixelLen for String {
     }
 }
 
-pub fn draw_texture(
-    img: &mut Vec2d<u32>,
-    mut coords: D2<u16>,
-    texture_id: usize,
-    color: Color,
+/// draws `texture` with every pixel blown up to a `dimensions` sized rectangle. `coords` are rel to the bottom left of the screen
+pub fn draw_texture<T: RenderTarget + ?Sized>(
+    img: &mut T,
+    coords: D2<u16>,
+    atlas: &TextureAtlas,
+    texture: TextureHandle,
     dimensions: D2<u16>, // use (2, 3) normally
     center: bool,
     background: bool,
-) {
-    if background {
-        let box_coords = coords - dimensions * D2::from(2);
-        let box_dimensions = dimensions * D2::from(9) + dimensions * D2::from(4);
-        draw_rectangle(
-            img,
-            box_coords,
-            box_dimensions,
-            Color::new(50, 50, 50),
-            center,
-        )
-    }
+) -> Result<(), TextureError> {
+    let texture = atlas.get(texture)?;
+    let (width, height) = texture.len();
+    // u32 so big textures blown up a lot can't overflow, anything past the screen is skipped anyway
+    let dimensions = (dimensions.x as u32, dimensions.y as u32);
+    let size = ((width as u32).saturating_mul(dimensions.0), (height as u32).saturating_mul(dimensions.1));
+    let mut coords = (coords.x as u32, coords.y as u32);
     if center {
-        coords = coords - dimensions * D2::from(9) / D2::from(2);
+        coords = (coords.0.saturating_sub(size.0 / 2), coords.1.saturating_sub(size.1 / 2));
     }
-
-    let texture_vec = get_texture_vec(texture_id);
-    for x in 0..9usize {
-        let x2 = coords.x + (x as u16) * dimensions.x;
-        for y in 0..9usize {
-            let y2 = coords.y + (y as u16) * dimensions.y;
-            if texture_vec[x][y] == true {
-                draw_rectangle(img, (x2 as u16, y2 as u16).into(), dimensions, color, center);
+    if background {
+        let box_coords = (coords.0.saturating_sub(dimensions.0 * 2), coords.1.saturating_sub(dimensions.1 * 2));
+        let box_size = (size.0.saturating_add(dimensions.0 * 4), size.1.saturating_add(dimensions.1 * 4));
+        fill_screen_rect(img, box_coords, box_size, Color::new(50, 50, 50));
+    }
+
+    for y in 0..height {
+        // row 0 is the top of the texture
+        let y2 = coords.1.saturating_add(((height - 1 - y) as u32).saturating_mul(dimensions.1));
+        for x in 0..width {
+            let x2 = coords.0.saturating_add((x as u32).saturating_mul(dimensions.0));
+            let color = texture[(x, y)];
+            if color.alpha != 0 {
+                fill_screen_rect(img, (x2, y2), dimensions, color);
             }
         }
     }
+    Ok(())
 }
 
-
-// when the function is sus
-fn get_texture_vec(texture: usize) -> Vec<[bool; 9]> {
-    return match texture {
-        1 => vec![
-            [
-                false, false, false, false, false, false, false, false, false,
-            ],
-            [
-                false, false, false, false, false, false, false, false, false,
-            ],
-            [false, false, true, true, true, true, true, false, false],
-            [false, false, true, false, false, false, true, false, false],
-            [false, false, true, false, false, false, true, false, false],
-            [false, false, true, false, false, false, true, false, false],
-            [false, false, true, true, true, true, true, false, false],
-            [
-                false, false, false, false, false, false, false, false, false,
-            ],
-            [
-                false, false, false, false, false, false, false, false, false,
-            ],
-        ],
-        2 => vec![
-            [
-                false, false, false, false, false, false, false, false, false,
-            ],
-            [false, true, true, true, true, true, true, true, false],
-            [false, true, false, false, true, false, false, true, false],
-            [false, true, false, false, true, false, false, true, false],
-            [false, true, true, true, true, true, true, true, false],
-            [false, true, false, false, true, false, false, true, false],
-            [false, true, false, false, true, false, false, true, false],
-            [false, true, true, true, true, true, true, true, false],
-            [
-                false, false, false, false, false, false, false, false, false,
-            ],
-        ],
-        _ => vec![
-            [true, false, false, false, false, false, false, false, true],
-            [
-                false, false, false, false, false, false, false, false, false,
-            ],
-            [
-                false, false, false, false, false, false, false, false, false,
-            ],
-            [
-                false, false, false, false, false, false, false, false, false,
-            ],
-            [
-                false, false, false, false, false, false, false, false, false,
-            ],
-            [
-                false, false, false, false, false, false, false, false, false,
-            ],
-            [
-                false, false, false, false, false, false, false, false, false,
-            ],
-            [
-                false, false, false, false, false, false, false, false, false,
-            ],
-            [false, false, false, false, false, false, false, false, true],
-        ],
+/// blends `color` into the part of the rectangle rel to the bottom left of the screen that is on screen
+fn fill_screen_rect<T: RenderTarget + ?Sized>(img: &mut T, coords: (u32, u32), size: (u32, u32), color: Color) {
+    let (width, height) = img.size();
+    let x_end = (coords.0.saturating_add(size.0) as usize).min(width);
+    let y_end = (coords.1.saturating_add(size.1) as usize).min(height);
+    for y in coords.1 as usize..y_end {
+        for x in coords.0 as usize..x_end {
+            img.blend_pixel((x, height - 1 - y), color, BlendMode::Normal);
+        }
     }
 }
 
-//"abcdefghijklmnopqrstuvwxyz.0123456789,!?[#]():"
-fn get_letter_array(letter: char) -> [[bool; 5]; 5] {
-    return match letter {
-        '0' => [
-            [false, true, true, true, false],
-            [true, false, false, false, true],
-            [true, false, true, false, true],
-            [true, false, false, false, true],
-            [false, true, true, true, false],
-        ],
-        '1' => [
-            [false, false, false, false, false],
-            [true, false, false, false, true],
-            [true, true, true, true, true],
-            [true, false, false, false, false],
-            [false, false, false, false, false],
-        ],
-        '2' => [
-            [true, false, false, true, false],
-            [true, true, false, false, true],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [true, false, false, true, false],
-        ],
-        '3' => [
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [false, true, false, true, false],
-        ],
-        '4' => [
-            [false, false, true, false, false],
-            [false, false, true, true, false],
-            [false, false, true, false, true],
-            [true, true, true, true, true],
-            [false, false, true, false, false],
-        ],
-        '5' => [
-            [true, false, true, true, true],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [false, true, false, false, true],
-        ],
-        '6' => [
-            [false, true, true, true, false],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [false, true, false, false, true],
-        ],
-        '7' => [
-            [false, false, false, false, true],
-            [false, false, false, false, true],
-            [true, true, false, false, true],
-            [false, false, true, false, true],
-            [false, false, false, true, true],
-        ],
-        '8' => [
-            [false, true, false, true, false],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [false, true, false, true, false],
-        ],
-        '9' => [
-            [false, false, false, true, false],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [false, true, true, true, false],
-        ],
-        'a' => [
-            [true, true, true, true, false],
-            [false, false, true, false, true],
-            [false, false, true, false, true],
-            [false, false, true, false, true],
-            [true, true, true, true, false],
-        ],
-        'b' => [
-            [true, true, true, true, true],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [false, true, false, true, false],
-        ],
-        'c' => [
-            [false, true, true, true, false],
-            [true, false, false, false, true],
-            [true, false, false, false, true],
-            [true, false, false, false, true],
-            [true, false, false, false, true],
-        ],
-        'd' => [
-            [true, true, true, true, true],
-            [true, false, false, false, true],
-            [true, false, false, false, true],
-            [true, false, false, false, true],
-            [false, true, true, true, false],
-        ],
-        'e' => [
-            [true, true, true, true, true],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-        ],
-        'f' => [
-            [true, true, true, true, true],
-            [false, false, true, false, true],
-            [false, false, true, false, true],
-            [false, false, true, false, true],
-            [false, false, true, false, true],
-        ],
-        'g' => [
-            [false, true, true, true, false],
-            [true, false, false, false, true],
-            [true, false, false, false, true],
-            [true, false, false, false, true],
-            [true, true, false, false, true],
-        ],
-        'h' => [
-            [true, true, true, true, true],
-            [false, false, true, false, false],
-            [false, false, true, false, false],
-            [false, false, true, false, false],
-            [true, true, true, true, true],
-        ],
-        'i' => [
-            [true, false, false, false, true],
-            [true, false, false, false, true],
-            [true, true, true, true, true],
-            [true, false, false, false, true],
-            [true, false, false, false, true],
-        ],
-        'j' => [
-            [true, false, false, false, true],
-            [true, false, false, false, true],
-            [true, true, true, true, true],
-            [false, false, false, false, true],
-            [false, false, false, false, true],
-        ],
-        'k' => [
-            [true, true, true, true, true],
-            [false, false, true, false, false],
-            [false, false, true, false, false],
-            [false, true, false, true, false],
-            [true, false, false, false, true],
-        ],
-        'l' => [
-            [true, true, true, true, true],
-            [true, false, false, false, false],
-            [true, false, false, false, false],
-            [true, false, false, false, false],
-            [true, false, false, false, false],
-        ],
-        'm' => [
-            [true, true, true, true, true],
-            [false, false, false, true, false],
-            [false, false, true, false, false],
-            [false, false, false, true, false],
-            [true, true, true, true, true],
-        ],
-        'n' => [
-            [true, true, true, true, true],
-            [false, false, false, true, false],
-            [false, false, true, false, false],
-            [false, true, false, false, false],
-            [true, true, true, true, true],
-        ],
-        'o' => [
-            [false, true, true, true, false],
-            [true, false, false, false, true],
-            [true, false, false, false, true],
-            [true, false, false, false, true],
-            [false, true, true, true, false],
-        ],
-        'p' => [
-            [true, true, true, true, true],
-            [false, false, true, false, true],
-            [false, false, true, false, true],
-            [false, false, true, false, true],
-            [false, false, false, true, false],
-        ],
-        'q' => [
-            [false, true, true, true, false],
-            [true, false, false, false, true],
-            [true, false, false, false, true],
-            [true, true, false, false, true],
-            [true, true, true, true, false],
-        ],
-        'r' => [
-            [true, true, true, true, true],
-            [false, false, true, false, true],
-            [false, true, true, false, true],
-            [true, false, true, false, true],
-            [false, false, false, true, false],
-        ],
-        's' => [
-            [true, false, false, true, false],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [true, false, true, false, true],
-            [false, true, false, false, true],
-        ],
-        't' => [
-            [false, false, false, false, true],
-            [false, false, false, false, true],
-            [true, true, true, true, true],
-            [false, false, false, false, true],
-            [false, false, false, false, true],
-        ],
-        'u' =>[
-            [false, true, true, true, true],
-            [true, false, false, false, false],
-            [true, false, false, false, false],
-            [true, false, false, false, false],
-            [false, true, true, true, true],
-        ],
-        'v' => [
-            [false, false, false, true, true],
-            [false, true, true, false, false],
-            [true, false, false, false, false],
-            [false, true, true, false, false],
-            [false, false, false, true, true],
-        ],
-        'w' => [
-            [false, true, true, true, true],
-            [true, false, false, false, false],
-            [true, true, true, false, false],
-            [true, false, false, false, false],
-            [false, true, true, true, true],
-        ],
-        'x' => [
-            [true, false, false, false, true],
-            [false, true, false, true, false],
-            [false, false, true, false, false],
-            [false, true, false, true, false],
-            [true, false, false, false, true],
-        ],
-        'y' => [
-            [false, false, false, false, true],
-            [false, false, false, true, false],
-            [true, true, true, false, false],
-            [false, false, false, true, false],
-            [false, false, false, false, true],
-        ],
-        'z' => [
-            [true, false, false, false, true],
-            [true, true, false, false, true],
-            [true, false, true, false, true],
-            [true, false, false, true, true],
-            [true, false, false, false, true],
-        ],
-        ' ' => [
-            [false, false, false, false, false],
-            [false, false, false, false, false],
-            [false, false, false, false, false],
-            [false, false, false, false, false],
-            [false, false, false, false, false],
-        ],
-        '.' => [
-            [false, false, false, false, false],
-            [true, false, false, false, false],
-            [false, false, false, false, false],
-            [false, false, false, false, false],
-            [false, false, false, false, false],
-        ],
-        ',' => [
-            [false, false, false, false, false],
-            [true, false, false, false, false],
-            [false, true, false, false, false],
-            [false, false, false, false, false],
-            [false, false, false, false, false],
-        ],
-        '!' => [
-            [false, false, false, false, false],
-            [false, false, false, false, false],
-            [true, false, true, true, true],
-            [false, false, false, false, false],
-            [false, false, false, false, false],
-        ],
-        '?' => [
-            [false, false, false, false, false],
-            [false, false, false, false, true],
-            [true, false, true, false, true],
-            [false, false, false, true, false],
-            [false, false, false, false, false],
-        ],
-        '[' => [
-            [false, false, false, false, false],
-            [true, true, true, true, true],
-            [true, false, false, false, true],
-            [false, false, false, false, false],
-            [false, false, false, false, false],
-        ],
-        ']' => [
-            [false, false, false, false, false],
-            [false, false, false, false, false],
-            [true, false, false, false, true],
-            [true, true, true, true, true],
-            [false, false, false, false, false],
-        ],
-        '(' | '{' | '<' => [
-            [false, false, false, false, false],
-            [false, true, true, true, false],
-            [true, false, false, false, true],
-            [false, false, false, false, false],
-            [false, false, false, false, false],
-        ],
-        ')' | '}' | '>' => [
-            [false, false, false, false, false],
-            [false, false, false, false, false],
-            [true, false, false, false, true],
-            [false, true, true, true, false],
-            [false, false, false, false, false],
-        ],
-        '+' => [
-            [false, false, true, false, false],
-            [false, false, true, false, false],
-            [true, true, true, true, true],
-            [false, false, true, false, false],
-            [false, false, true, false, false],
-        ],
-        '-' => [
-            [false, false, true, false, false],
-            [false, false, true, false, false],
-            [false, false, true, false, false],
-            [false, false, true, false, false],
-            [false, false, true, false, false],
-        ],
-        ':' => [
-            [false, false, false, false, false],
-            [false, false, false, false, false],
-            [false, true, false, true, false],
-            [false, false, false, false, false],
-            [false, false, false, false, false],
-        ],
-        '/' => [
-            [true, false, false, false, false],
-            [false, true, false, false, false],
-            [false, false, true, false, false],
-            [false, false, false, true, false],
-            [false, false, false, false, true],
-        ],
-        '%' => [
-            [true, false, false, false, true],
-            [false, true, false, false, false],
-            [false, false, true, false, false],
-            [false, false, false, true, false],
-            [true, false, false, false, true],
-        ],
-        _ => [
-            [true, false, true, false, true],
-            [false, true, false, true, false],
-            [true, false, true, false, true],
-            [false, true, false, true, false],
-            [true, false, true, false, true],
-        ],
-    }
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn big_textures_blown_up_are_clipped_to_the_screen() {
+        let mut atlas = TextureAtlas::new();
+        let texture = atlas.insert("big", Vec2d::new_with_fill((300, 300), |_| Color::red()));
+        let mut img: Vec2d<Color> = Vec2d::new_with_fill((8, 6), |_| Color::black());
+        draw_texture(&mut img, D2::from((2, 1)), &atlas, texture, D2::from((300, 300)), false, true).unwrap();
+        for y in 0..6 {
+            for x in 0..8 {
+                // y is flipped, the texture starts one row up from the bottom
+                let expected = if x >= 2 && y < 5 { Color::red() } else { Color::new(50, 50, 50) };
+                assert_eq!(img[(x, y)], expected, "pixel {:?}", (x, y));
+            }
+        }
+        draw_texture(&mut img, D2::from((u16::MAX, u16::MAX)), &atlas, texture, D2::from((u16::MAX, u16::MAX)), true, true).unwrap();
+    }
+
+    #[test]
+    fn text_is_depth_tested() {
+        let cam_data = CamData::new((40, 20), 1);
+        let plane = Pos::new(D3::from((D1::zero(), D1::zero(), D1::from(-10))), Angle::zero());
+        let mut img: Vec2d<Color> = Vec2d::new_with_fill((40, 20), |_| Color::black());
+        let mut depth = new_depth_buffer((40, 20));
+        // something nearer already covers the left half
+        for y in 0..20 {
+            for x in 0..20 {
+                depth[(x, y)] = D1::zero();
+            }
+        }
+        let mut cam = Cam::new_rel_to_pos(&mut img, &cam_data, plane, 255).unwrap().with_depth_buffer(&mut depth);
+        cam.draw_text(D2::zero(), "########", Font::builtin(), Color::white(), D2::from((1, 1)), true, false);
+        let drawn = |x_range: std::ops::Range<usize>| x_range.flat_map(|x| (0..20).map(move |y| (x, y))).filter(|pt| img[*pt] == Color::white()).count();
+        assert_eq!(drawn(0..20), 0);
+        assert!(drawn(20..40) > 0);
+    }
+
+    #[test]
+    fn cams_look_at_the_center_of_their_target() {
+        let cam_data = CamData::new((100, 80), 1);
+        let plane = Pos::new(D3::from((D1::zero(), D1::zero(), D1::from(-10))), Angle::zero());
+        let mut img: Vec2d<Color> = Vec2d::new_with_fill((100, 80), |_| Color::black());
+        let mut sub = crate::render_target::SubTarget::new(&mut img, (10, 10), (40, 20));
+        let cam = Cam::new_rel_to_pos(&mut sub, &cam_data, plane, 255).unwrap();
+
+        let close = |a: D2<D1>, b: D2<D1>| (a - b).x.abs() < D1::one() / D1::from(1000) && (a - b).y.abs() < D1::one() / D1::from(1000);
+        let center = D2::from((D1::from(20), D1::from(10)));
+        assert_eq!(cam.project(D3::zero()), Some(center));
+        assert!(close(cam.screen_coords_to_coords(center).unwrap(), D2::zero()));
+        let (min, max) = cam.visible_bounds().unwrap();
+        assert!(close(min + max, D2::zero()));
+        assert!(close(cam.project(D3::from((max, D1::zero()))).unwrap(), D2::from((D1::from(40), D1::from(20)))));
+    }
+
+    #[test]
+    fn lines_are_clipped_to_the_screen() {
+        let size = (10, 10);
+        // fully outside, on one side and passing by a corner
+        assert_eq!(clip_line(((-5, 2), (-1, 8)), size), None);
+        assert_eq!(clip_line(((-10, 5), (5, -10)), size), None);
+        // crossing one edge
+        assert_eq!(clip_line(((5, 5), (15, 5)), size), Some(((5, 5), (9, 5))));
+        assert_eq!(clip_line(((3, -4), (3, 4)), size), Some(((3, 0), (3, 4))));
+        // crossing two edges
+        assert_eq!(clip_line(((-5, 5), (15, 5)), size), Some(((0, 5), (9, 5))));
+        assert_eq!(clip_line(((-10, -10), (20, 20)), size), Some(((0, 0), (9, 9))));
+        // the intersection math must not overflow
+        assert_eq!(clip_line(((i32::MIN, i32::MIN), (i32::MAX, i32::MAX)), size), Some(((0, 0), (9, 9))));
+        assert_eq!(clip_line(((i32::MIN, 5), (i32::MAX, 5)), size), Some(((0, 5), (9, 5))));
+        assert_eq!(clip_line(((5, i32::MAX), (5, i32::MIN)), size), Some(((5, 9), (5, 0))));
+        // a single point
+        assert_eq!(clip_line(((3, 4), (3, 4)), size), Some(((3, 4), (3, 4))));
+        assert_eq!(clip_line(((30, 4), (30, 4)), size), None);
+        assert_eq!(clip_line(((0, 0), (5, 5)), (0, 10)), None);
+    }
+
+    #[test]
+    fn wide_pixels_are_clipped_to_the_screen() {
+        let cam_data = CamData::new((8, 8), 1);
+        let plane = Pos::new(D3::from((D1::zero(), D1::zero(), D1::from(-10))), Angle::zero());
+        let mut img: Vec2d<Color> = Vec2d::new_with_fill((8, 8), |_| Color::black());
+        let drawn = |img: &Vec2d<Color>| img.get_flat1().iter().filter(|p| **p == Color::white()).count();
+
+        Cam::new_rel_to_pos(&mut img, &cam_data, plane, 255).unwrap().draw_wide_pixel(D2::zero(), 3, Color::white());
+        assert_eq!(drawn(&img), 9);
+        // only the on screen part gets looped over, so this is quick
+        Cam::new_rel_to_pos(&mut img, &cam_data, plane, 255).unwrap().draw_wide_pixel(D2::zero(), 1 << 30, Color::white());
+        assert_eq!(drawn(&img), 16);
+        img.get_mut_flat1().fill(Color::black());
+        Cam::new_rel_to_pos(&mut img, &cam_data, plane, 255).unwrap().draw_wide_pixel(D2::zero(), usize::MAX, Color::white());
+        assert_eq!(drawn(&img), 0);
+    }
+
+    #[test]
+    fn lines_are_clipped_to_the_near_distance() {
+        let mut cam_data = CamData::new((40, 20), 1);
+        cam_data.set_near_dist(D1::zero());
+        assert_eq!(cam_data.near_dist(), MIN_NEAR_DIST);
+        cam_data.set_near_dist(D1::from(-3));
+        assert_eq!(cam_data.near_dist(), MIN_NEAR_DIST);
+        cam_data.set_near_dist(D1::from(5));
+        let mut img: Vec2d<Color> = Vec2d::new_with_fill((40, 20), |_| Color::black());
+        let plane = Pos::new(D3::from((D1::zero(), D1::zero(), D1::from(-10))), Angle::zero());
+        let cam = Cam::new_rel_to_pos(&mut img, &cam_data, plane, 255).unwrap();
+
+        // the plane is 10 below the focal point, so anything higher than 5 above the plane is too close
+        let (near_z, top) = (cam_data.focal_len() + D1::from(5), cam_data.focal_len() + D1::from(10));
+        let start = D3::zero();
+        let end = D3::from((D1::from(10), D1::zero(), top));
+        let (a, b) = cam.clip_near((start, end)).unwrap();
+        assert_eq!(a, start);
+        assert_eq!(b.z, near_z);
+        assert!((b.x - D1::from(10) * near_z / top).abs() < D1::one() / D1::from(1000));
+        assert!(cam.project(b).is_some());
+        // the other way around keeps the far end
+        assert_eq!(cam.clip_near((end, start)).map(|(a, b)| (a.z, b)), Some((near_z, start)));
+
+        let behind = D3::from((D1::zero(), D1::zero(), near_z + D1::one()));
+        assert_eq!(cam.clip_near((behind, end)), None);
+        assert_eq!(cam.clip_near((start, start)), Some((start, start)));
+    }
+
+    fn polygon(points: &[(f64, f64)]) -> Vec<D2<D1>> {
+        points.iter().map(|&(x, y)| D2::from((D1::from(x), D1::from(y)))).collect()
+    }
+    /// every pixel plotted, panics if one is plotted twice
+    fn polygon_pixels(size: (usize, usize), points: &[D2<D1>]) -> Vec<(i32, i32)> {
+        let mut pixels = Vec::new();
+        for_each_polygon_pixel(size, points, |pt| {
+            assert!(!pixels.contains(&pt), "{:?} plotted twice", pt);
+            pixels.push(pt);
+        });
+        pixels.sort();
+        pixels
+    }
+    /// the pixels whose centers are inside of `inside`
+    fn pixels_where(size: (usize, usize), inside: impl Fn(f64, f64) -> bool) -> Vec<(i32, i32)> {
+        let mut pixels: Vec<_> = (0..size.0 as i32).flat_map(|x| (0..size.1 as i32).map(move |y| (x, y)))
+            .filter(|&(x, y)| inside(x as f64 + 0.5, y as f64 + 0.5))
+            .collect();
+        pixels.sort();
+        pixels
+    }
+
+    #[test]
+    fn polygons_fill_the_pixels_whose_centers_they_cover() {
+        let size = (10, 10);
+        let rect = polygon(&[(1.0, 1.0), (5.0, 1.0), (5.0, 4.0), (1.0, 4.0)]);
+        assert_eq!(polygon_pixels(size, &rect).len(), 12);
+        assert_eq!(polygon_pixels(size, &rect), pixels_where(size, |x, y| x < 5.0 && y < 4.0 && x > 1.0 && y > 1.0));
+
+        // a square rotated 45 degrees, no pixel center is on its edges
+        let diamond = polygon(&[(5.0, 0.75), (9.25, 5.0), (5.0, 9.25), (0.75, 5.0)]);
+        assert_eq!(polygon_pixels(size, &diamond), pixels_where(size, |x, y| (x - 5.0).abs() + (y - 5.0).abs() < 4.25));
+
+        // a U shape
+        let concave = polygon(&[(1.0, 1.0), (9.0, 1.0), (9.0, 9.0), (6.0, 9.0), (6.0, 4.0), (4.0, 4.0), (4.0, 9.0), (1.0, 9.0)]);
+        let notch = |x: f64, y: f64| x > 4.0 && x < 6.0 && y > 4.0;
+        assert_eq!(polygon_pixels(size, &concave).len(), 64 - 10);
+        assert_eq!(polygon_pixels(size, &concave), pixels_where(size, |x, y| x > 1.0 && x < 9.0 && y > 1.0 && y < 9.0 && !notch(x, y)));
+    }
+
+    #[test]
+    fn filled_polygons_blend_every_pixel_once() {
+        let diamond = polygon(&[(5.0, 0.75), (9.25, 5.0), (5.0, 9.25), (0.75, 5.0)]);
+        let mut img: Vec2d<Color> = Vec2d::new_with_fill((10, 10), |_| Color::black());
+        let color = Color::new_with_alpha(255, 255, 255, 128);
+        fill_polygon(&mut img, &diamond, color);
+        let once = BlendMode::Normal.blend(color, Color::black());
+        // y is flipped
+        let filled: Vec<_> = polygon_pixels((10, 10), &diamond).iter().map(|&(x, y)| (x as usize, 9 - y as usize)).collect();
+        for y in 0..10 {
+            for x in 0..10 {
+                let expected = if filled.contains(&(x, y)) { once } else { Color::black() };
+                assert_eq!(img[(x, y)], expected, "pixel {:?}", (x, y));
+            }
+        }
+
+        // the same square rotated on a plane, drawn through a cam
+        let cam_data = CamData::new((40, 40), 1);
+        let plane = Pos::new(D3::from((D1::zero(), D1::zero(), D1::from(-10))), Angle::one_fourth_pi());
+        let mut recording = crate::render_target::RecordingTarget::new((40, 40));
+        let mut cam = Cam::new_rel_to_pos(&mut recording, &cam_data, plane, 255).unwrap();
+        cam.fill_polygon(&polygon(&[(-8.0, -8.0), (8.0, -8.0), (8.0, 8.0), (-8.0, 8.0)]), color);
+        let mut written: Vec<_> = recording.writes.iter().map(|(pt, _)| *pt).collect();
+        let count = written.len();
+        written.sort();
+        written.dedup();
+        assert_eq!(written.len(), count);
+        // a 45 degree square has its corners straight up, down, left and right of its center
+        assert!(written.contains(&(20, 20)));
+        assert!(!written.contains(&(20 + 8, 20 + 8)));
+    }
+
+    #[test]
+    fn huge_polygons_dont_panic() {
+        let huge = D1::from(1 << 30);
+        let points = [D2::from((-huge, -huge)), D2::from((huge, -huge)), D2::from((huge, huge)), D2::from((-huge, huge))];
+        assert_eq!(polygon_pixels((8, 8), &points).len(), 64);
+        let sliver = [D2::from((-huge, D1::zero())), D2::from((huge, D1::one())), D2::from((huge, D1::from(3)))];
+        polygon_pixels((8, 8), &sliver);
+
+        // a plane right under the near distance blows everything up
+        let cam_data = CamData::new((8, 8), 1);
+        let plane = Pos::new(D3::from((D1::zero(), D1::zero(), cam_data.focal_len() - cam_data.near_dist())), Angle::zero());
+        let mut img: Vec2d<Color> = Vec2d::new_with_fill((8, 8), |_| Color::black());
+        let mut cam = Cam::new_rel_to_pos(&mut img, &cam_data, plane, 255).unwrap();
+        cam.fill_polygon(&polygon(&[(-1000.0, -1000.0), (1000.0, 0.0), (0.0, 1000.0)]), Color::white());
+        cam.fill_polygon(&points, Color::white());
+        // projected far outside of D1, but still covering the screen
+        assert!(img.get_flat1().iter().all(|p| *p == Color::white()));
+    }
+
+    #[test]
+    fn translucent_rectangles_blend() {
+        let mut img: Vec2d<Color> = Vec2d::new_with_fill((4, 4), |_| Color::white());
+        let color = Color::new_with_alpha(0, 0, 0, 128);
+        draw_rectangle(&mut img, D2::from((0, 0)), D2::from((2, 2)), color, false);
+        let blended = BlendMode::Normal.blend(color, Color::white());
+        assert_eq!((img[(0, 3)], img[(1, 2)], img[(2, 2)]), (blended, blended, Color::white()));
+        draw_rectangle(&mut img, D2::from((0, 0)), D2::from((1, 1)), Color::black(), false);
+        assert_eq!(img[(0, 3)], Color::black());
+    }
+
+    #[test]
+    fn images_follow_their_rect() {
+        let cam_data = CamData::new((40, 40), 1);
+        let plane = Pos::new(D3::from((D1::zero(), D1::zero(), D1::from(-10))), Angle::zero());
+        // red green on top, blue white below
+        let (red, green, blue) = (Color::new(255, 0, 0), Color::new(0, 255, 0), Color::new(0, 0, 255));
+        let src = Vec2d::new_with_fill((2, 2), |pt| [red, green, blue, Color::white()][pt.1 * 2 + pt.0]);
+        let rect = (D2::from((D1::from(-8), D1::from(-8))), D2::from((D1::from(8), D1::from(8))));
+        let corners = |angle: Angle| {
+            let mut img: Vec2d<Color> = Vec2d::new_with_fill((40, 40), |_| Color::black());
+            let mut cam = Cam::new_rel_to_pos(&mut img, &cam_data, plane, 255).unwrap();
+            cam.draw_image(&src, rect, angle, Sampling::Nearest);
+            // top left, top right, bottom left and bottom right of the screen, 5 plane units in from the edge of the rect
+            let at = |x: i32, y: i32| {
+                let pt = cam.coords_to_cam_coords(D2::from((D1::from(x), D1::from(y)))).unwrap();
+                cam.img[(pt.0 as usize, 39 - pt.1 as usize)]
+            };
+            [at(-5, 5), at(5, 5), at(-5, -5), at(5, -5)]
+        };
+        assert_eq!(corners(Angle::zero()), [red, green, blue, Color::white()]);
+        // positive angles turn clockwise like Cam::fill_rect, so a quarter turn puts the top row on the right
+        assert_eq!(corners(Angle::half_pi()), [blue, red, Color::white(), green]);
+    }
+
+    #[test]
+    fn images_on_very_thin_rects_dont_overflow() {
+        let cam_data = CamData::new((40, 40), 1);
+        let plane = Pos::new(D3::from((D1::zero(), D1::zero(), D1::from(-10))), Angle::zero());
+        let src = Vec2d::new_with_fill((1000, 1000), |_| Color::white());
+        let mut img: Vec2d<Color> = Vec2d::new_with_fill((40, 40), |_| Color::black());
+        let mut cam = Cam::new_rel_to_pos(&mut img, &cam_data, plane, 255).unwrap();
+        let thin = D1 { val: fixed::types::I36F28::from_bits(1) };
+        for angle in [Angle::zero(), Angle::one_fourth_pi()] {
+            cam.draw_image(&src, (D2::from((-D1::from(10), D1::zero())), D2::from((D1::from(10), thin))), angle, Sampling::Bilinear);
+            cam.draw_image(&src, (D2::zero(), D2::from((thin, thin))), angle, Sampling::Nearest);
+        }
+    }
+
+    /// how many white pixels are in the middle row and column
+    fn white_through_middle(img: &Vec2d<Color>) -> (usize, usize) {
+        let (width, height) = img.len();
+        let row = (0..width).filter(|x| img[(*x, height / 2)] == Color::white()).count();
+        let column = (0..height).filter(|y| img[(width / 2, *y)] == Color::white()).count();
+        (row, column)
+    }
+
+    #[test]
+    fn world_text_turns_and_zooms_with_the_plane() {
+        let plane = Pos::new(D3::from((D1::zero(), D1::zero(), D1::from(-10))), Angle::zero());
+        let draw = |zoom: D1, angle: Angle| {
+            let mut cam_data = CamData::new((64, 64), 1);
+            cam_data.set_zoom(zoom);
+            let mut img: Vec2d<Color> = Vec2d::new_with_fill((64, 64), |_| Color::black());
+            let mut cam = Cam::new_rel_to_pos(&mut img, &cam_data, plane, 255).unwrap();
+            cam.draw_world_text(D2::zero(), "I", Font::builtin(), Color::white(), D1::from(20), angle, true);
+            white_through_middle(&img)
+        };
+        // the stem of the I goes all the way up, across the middle it is only the stem
+        let (row, column) = draw(D1::one(), Angle::zero());
+        assert!(column > row * 3, "{:?}", (row, column));
+        // turned a quarter the stem lies across the screen
+        let (turned_row, turned_column) = draw(D1::one(), Angle::half_pi());
+        assert!(turned_row.abs_diff(column) <= 1 && turned_column.abs_diff(row) <= 1, "{:?}", (turned_row, turned_column));
+        // zooming out twice as far halves it
+        let (_, zoomed_column) = draw(D1::two(), Angle::zero());
+        assert!(zoomed_column.abs_diff(column / 2) <= 1, "{:?}", (zoomed_column, column));
+    }
+}
@@ -0,0 +1,562 @@
+use std::{collections::HashMap, error, fmt, fs, io, path::Path, sync::OnceLock};
+
+use crate::coordinates::*;
+use crate::vec2d::Vec2d;
+
+/// one character of a [Font]
+#[derive(Debug, Clone)]
+pub struct Glyph {
+    /// pixels that get drawn, row 0 is the top
+    pub bitmap: Vec2d<bool>,
+    /// where the bottom left of `bitmap` goes relative to the pen, which sits on the baseline. Y goes up
+    pub offset: D2<i16>,
+    /// how far the pen moves right after this glyph
+    pub advance: u16,
+}
+impl Glyph {
+    /// every '#' in `rows` is drawn, anything else is left empty. The first row is the top.
+    /// Sits on the baseline and leaves a 1 pixel gap before the next glyph
+    pub fn from_rows(rows: &[&str]) -> Self {
+        let width = rows.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+        let bitmap = Vec2d::new_with_fill((width, rows.len()), |(x, y)| rows[y].chars().nth(x) == Some('#'));
+        Self {
+            bitmap,
+            offset: D2::from((0, 0)),
+            advance: width as u16 + 1,
+        }
+    }
+    /// hollow rectangle, what fonts draw for characters they don't have
+    pub fn missing_box(size: (usize, usize)) -> Self {
+        let (width, height) = size;
+        let bitmap = Vec2d::new_with_fill(size, |(x, y)| x == 0 || y == 0 || x + 1 == width || y + 1 == height);
+        Self {
+            bitmap,
+            offset: D2::from((0, 0)),
+            advance: width as u16 + 1,
+        }
+    }
+    pub fn size(&self) -> (usize, usize) {
+        self.bitmap.len()
+    }
+}
+
+#[derive(Debug)]
+pub enum FontError {
+    Io(io::Error),
+    /// the line the problem is on, starting at 1
+    Bdf { line: usize, message: String },
+}
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FontError::Io(e) => write!(f, "{}", e),
+            FontError::Bdf { line, message } => write!(f, "bad bdf font at line {}: {}", line, message),
+        }
+    }
+}
+impl error::Error for FontError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            FontError::Io(e) => Some(e),
+            FontError::Bdf { .. } => None,
+        }
+    }
+}
+impl From<io::Error> for FontError {
+    fn from(e: io::Error) -> Self {
+        FontError::Io(e)
+    }
+}
+
+/// a bitmap font, measured in font pixels. The text functions scale every font pixel up to their `dimensions`.
+///
+/// [Font::default] is the 5x5 font the text functions have always used, [Font::from_bdf] reads anything else
+#[derive(Debug, Clone)]
+pub struct Font {
+    glyphs: HashMap<char, Glyph>,
+    kerning: HashMap<(char, char), i16>,
+    /// pixels from the baseline to the top of the tallest glyphs
+    pub ascent: u16,
+    /// pixels below the baseline that glyphs like g and y can reach
+    pub descent: u16,
+    /// distance from one baseline to the next
+    pub line_height: u16,
+    /// drawn for characters the font doesn't have
+    pub missing: Glyph,
+}
+impl Default for Font {
+    fn default() -> Self {
+        Self::default_5x5()
+    }
+}
+impl Font {
+    /// a font without any glyphs, everything is drawn as a box until glyphs are added with [Font::insert]
+    pub fn new(ascent: u16, descent: u16, line_height: u16) -> Self {
+        let height = (ascent as usize).max(3);
+        Self {
+            glyphs: HashMap::new(),
+            kerning: HashMap::new(),
+            ascent,
+            descent,
+            line_height,
+            missing: Glyph::missing_box((height * 3 / 5 + 1, height)),
+        }
+    }
+    /// the original font, every glyph is 5x5 with a 1 pixel gap. It has every printable ascii character,
+    /// but only one case so upper and lower case letters look the same
+    pub fn default_5x5() -> Self {
+        let mut font = Self::new(5, 0, 6);
+        for (chars, rows) in DEFAULT_GLYPHS {
+            let glyph = Glyph::from_rows(rows);
+            for c in chars.chars() {
+                font.insert(c.to_ascii_uppercase(), glyph.clone());
+                font.insert(c, glyph.clone());
+            }
+        }
+        font
+    }
+    /// a shared [Font::default_5x5] for when there is nowhere to keep a font
+    pub fn builtin() -> &'static Font {
+        static BUILTIN: OnceLock<Font> = OnceLock::new();
+        BUILTIN.get_or_init(Font::default_5x5)
+    }
+
+    pub fn insert(&mut self, c: char, glyph: Glyph) {
+        self.glyphs.insert(c, glyph);
+    }
+    /// moves `right` closer to `left` (negative) or further away (positive) when it comes right after it
+    pub fn set_kerning(&mut self, left: char, right: char, amount: i16) {
+        if amount == 0 {
+            self.kerning.remove(&(left, right));
+        } else {
+            self.kerning.insert((left, right), amount);
+        }
+    }
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.glyphs.contains_key(&c)
+    }
+    /// [Font::missing] if the font doesn't have `c`
+    pub fn glyph(&self, c: char) -> &Glyph {
+        self.glyphs.get(&c).unwrap_or(&self.missing)
+    }
+    pub fn kerning(&self, left: char, right: char) -> i16 {
+        self.kerning.get(&(left, right)).copied().unwrap_or(0)
+    }
+    /// ascent + descent, the height of one line of text without the gap to the next
+    pub fn height(&self) -> u16 {
+        self.ascent.saturating_add(self.descent)
+    }
+    /// x position of the pen before every character of `line` (in font pixels) and where the pen ends up
+    pub fn pen_positions<'a>(&'a self, line: &'a str) -> impl Iterator<Item = (char, i32)> + 'a {
+        let mut pen = 0i32;
+        let mut previous = None;
+        line.chars().map(move |c| {
+            if let Some(previous) = previous {
+                pen += self.kerning(previous, c) as i32;
+            }
+            let at = pen;
+            pen += self.glyph(c).advance as i32;
+            previous = Some(c);
+            (c, at)
+        })
+    }
+    /// how far the pen moves while drawing `line`, where the next character would go
+    pub fn advance_width(&self, line: &str) -> u16 {
+        self.pen_positions(line)
+            .last()
+            .map_or(0, |(c, pen)| pen + self.glyph(c).advance as i32)
+            .clamp(0, u16::MAX as i32) as u16
+    }
+    /// width of `line` in font pixels, from the pen start to the right edge of the last pixel.
+    /// Does not treat '\n' any different from other characters
+    pub fn text_width(&self, line: &str) -> u16 {
+        self.pen_positions(line)
+            .map(|(c, pen)| {
+                let glyph = self.glyph(c);
+                pen + glyph.offset.x as i32 + glyph.size().0 as i32
+            })
+            .max()
+            .unwrap_or(0)
+            .clamp(0, u16::MAX as i32) as u16
+    }
+    /// width and height of the block `string` takes up in font pixels, '\n' starts a new line below
+    pub fn text_size(&self, string: &str) -> (u16, u16) {
+        let width = string.split('\n').map(|line| self.text_width(line)).max().unwrap_or(0);
+        let lines = string.split('\n').count() as u16;
+        (width, self.height().saturating_add((lines - 1).saturating_mul(self.line_height)))
+    }
+    /// horizontal runs of pixels that need to be drawn for `string` as (x, y, length) in font pixels.
+    /// (0, 0) is the bottom left of the first line (`descent` below its baseline), y goes up and lines after a '\n' go below
+    pub fn pixel_runs(&self, string: &str) -> Vec<(i32, i32, i32)> {
+        let mut runs = Vec::new();
+        for (i, line) in string.split('\n').enumerate() {
+            let baseline = self.descent as i32 - i as i32 * self.line_height as i32;
+            for (c, pen) in self.pen_positions(line) {
+                let glyph = self.glyph(c);
+                let (width, height) = glyph.size();
+                for row in 0..height {
+                    // bitmap rows go down from the top
+                    let y = baseline + glyph.offset.y as i32 + (height - 1 - row) as i32;
+                    let mut x = 0;
+                    while x < width {
+                        if !glyph.bitmap[(x, row)] {
+                            x += 1;
+                            continue
+                        }
+                        let start = x;
+                        while x < width && glyph.bitmap[(x, row)] {
+                            x += 1;
+                        }
+                        runs.push((pen + glyph.offset.x as i32 + start as i32, y, (x - start) as i32));
+                    }
+                }
+            }
+        }
+        runs
+    }
+
+    /// reads a font in the [BDF](https://en.wikipedia.org/wiki/Glyph_Bitmap_Distribution_Format) format.
+    /// Glyphs without a unicode encoding are skipped, `DEFAULT_CHAR` becomes the missing glyph if the font has one
+    pub fn from_bdf(text: &str) -> Result<Font, FontError> {
+        BdfParser::default().parse(text)
+    }
+    pub fn load_bdf<P: AsRef<Path>>(path: P) -> Result<Font, FontError> {
+        let text = fs::read_to_string(path)?;
+        Self::from_bdf(&text)
+    }
+}
+
+/// (width, height, x offset, y offset) like BDF's BBX
+type BoundingBox = (usize, usize, i16, i16);
+
+#[derive(Default)]
+struct BdfParser {
+    font_box: Option<BoundingBox>,
+    font_advance: Option<u16>,
+    ascent: Option<u16>,
+    descent: Option<u16>,
+    default_char: Option<u32>,
+    glyphs: Vec<(Option<u32>, Glyph)>,
+}
+impl BdfParser {
+    fn parse(mut self, text: &str) -> Result<Font, FontError> {
+        let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line.trim()));
+        let mut started = false;
+        while let Some((line_number, line)) = lines.next() {
+            let error = |message: &str| FontError::Bdf { line: line_number, message: message.to_owned() };
+            let mut words = line.split_whitespace();
+            let Some(keyword) = words.next() else {
+                continue
+            };
+            let numbers = || line.split_whitespace().skip(1).map(|word| word.parse::<i64>()).collect::<Result<Vec<_>, _>>()
+                .map_err(|_| error("expected numbers"));
+            match keyword {
+                "STARTFONT" => started = true,
+                _ if !started => return Err(error("missing STARTFONT")),
+                "FONTBOUNDINGBOX" => self.font_box = Some(bounding_box(&numbers()?).ok_or_else(|| error("bad FONTBOUNDINGBOX"))?),
+                "DWIDTH" => self.font_advance = Some(first_u16(&numbers()?).ok_or_else(|| error("bad DWIDTH"))?),
+                "FONT_ASCENT" => self.ascent = Some(first_u16(&numbers()?).ok_or_else(|| error("bad FONT_ASCENT"))?),
+                "FONT_DESCENT" => self.descent = Some(first_u16(&numbers()?).ok_or_else(|| error("bad FONT_DESCENT"))?),
+                "DEFAULT_CHAR" => self.default_char = numbers()?.first().and_then(|n| u32::try_from(*n).ok()),
+                "STARTCHAR" => self.parse_char(&mut lines, line_number)?,
+                "ENDFONT" => return self.finish().map_err(|message| error(&message)),
+                _ => {}
+            }
+        }
+        Err(FontError::Bdf { line: text.lines().count(), message: "missing ENDFONT".to_owned() })
+    }
+
+    /// reads from after STARTCHAR to ENDCHAR
+    fn parse_char<'a>(&mut self, lines: &mut impl Iterator<Item = (usize, &'a str)>, start_line: usize) -> Result<(), FontError> {
+        let mut encoding = None;
+        let mut advance = self.font_advance;
+        let mut bbx = self.font_box;
+        let mut bitmap = None;
+        let mut last_line = start_line;
+        while let Some((line_number, line)) = lines.next() {
+            last_line = line_number;
+            let error = |message: &str| FontError::Bdf { line: line_number, message: message.to_owned() };
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap_or("");
+            let numbers = || words.clone().map(|word| word.parse::<i64>()).collect::<Result<Vec<_>, _>>()
+                .map_err(|_| error("expected numbers"));
+            match keyword {
+                // -1 means the glyph isn't in the standard encoding
+                "ENCODING" => encoding = numbers()?.first().and_then(|n| u32::try_from(*n).ok()),
+                "DWIDTH" => advance = Some(first_u16(&numbers()?).ok_or_else(|| error("bad DWIDTH"))?),
+                "BBX" => bbx = Some(bounding_box(&numbers()?).ok_or_else(|| error("bad BBX"))?),
+                "BITMAP" => {
+                    let (width, height, ..) = bbx.ok_or_else(|| error("BITMAP before BBX"))?;
+                    let mut rows = Vec::with_capacity(height);
+                    for _ in 0..height {
+                        let (line_number, row) = lines.next().ok_or_else(|| error("bitmap ended early"))?;
+                        rows.push(parse_hex_row(row).ok_or(FontError::Bdf { line: line_number, message: "bad bitmap row".to_owned() })?);
+                    }
+                    bitmap = Some(Vec2d::new_with_fill((width, height), |(x, y)| {
+                        rows[y].get(x / 8).is_some_and(|byte| byte & (0x80 >> (x % 8)) != 0)
+                    }));
+                }
+                "ENDCHAR" => {
+                    let (width, height, x, y) = bbx.ok_or_else(|| error("glyph without BBX"))?;
+                    let glyph = Glyph {
+                        bitmap: bitmap.unwrap_or_else(|| Vec2d::new_with_fill((width, height), |_| false)),
+                        offset: D2::from((x, y)),
+                        // bounding_box keeps the width small enough
+                        advance: advance.unwrap_or(width as u16),
+                    };
+                    self.glyphs.push((encoding, glyph));
+                    return Ok(())
+                }
+                _ => {}
+            }
+        }
+        Err(FontError::Bdf { line: last_line, message: "missing ENDCHAR".to_owned() })
+    }
+
+    fn finish(self) -> Result<Font, String> {
+        // fall back to the font bounding box when the properties are missing
+        let (_, box_height, _, box_y) = self.font_box.unwrap_or((0, 0, 0, 0));
+        let box_ascent = (box_height as i32 + box_y as i32).max(0);
+        let ascent = self.ascent.map_or_else(|| u16::try_from(box_ascent), Ok).map_err(|_| "font is too tall")?;
+        let descent = self.descent.map_or_else(|| u16::try_from(-(box_y as i32)), Ok).map_err(|_| "font is too tall")?;
+        let line_height = ascent.checked_add(descent).ok_or("FONT_ASCENT + FONT_DESCENT is too big")?;
+        let mut font = Font::new(ascent, descent, line_height);
+        for (encoding, glyph) in self.glyphs {
+            let Some(encoding) = encoding else {
+                continue
+            };
+            if Some(encoding) == self.default_char {
+                font.missing = glyph.clone();
+            }
+            if let Some(c) = char::from_u32(encoding) {
+                font.insert(c, glyph);
+            }
+        }
+        Ok(font)
+    }
+}
+
+/// biggest glyph width or height a BDF font can have, more than any real bitmap font needs
+const MAX_GLYPH_SIZE: usize = 1024;
+
+fn bounding_box(numbers: &[i64]) -> Option<BoundingBox> {
+    match numbers {
+        [width, height, x, y] => Some((
+            usize::try_from(*width).ok().filter(|width| *width <= MAX_GLYPH_SIZE)?,
+            usize::try_from(*height).ok().filter(|height| *height <= MAX_GLYPH_SIZE)?,
+            i16::try_from(*x).ok()?,
+            i16::try_from(*y).ok()?,
+        )),
+        _ => None,
+    }
+}
+fn first_u16(numbers: &[i64]) -> Option<u16> {
+    numbers.first().and_then(|n| u16::try_from(*n).ok())
+}
+/// a row of a BDF bitmap, hex digits with the leftmost pixel in the highest bit of the first byte
+fn parse_hex_row(row: &str) -> Option<Vec<u8>> {
+    let digits = row.trim().as_bytes();
+    digits.chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok().map(|byte| if pair.len() == 1 { byte << 4 } else { byte }))
+        .collect()
+}
+
+/// the glyphs of [Font::default_5x5], each set of characters shares the glyph after it
+const DEFAULT_GLYPHS: &[(&str, [&str; 5])] = &[
+    ("0", [".###.", "#...#", "#.#.#", "#...#", ".###."]),
+    ("1", [".##..", "..#..", "..#..", "..#..", ".###."]),
+    ("2", [".###.", "#...#", "..##.", ".#...", "#####"]),
+    ("3", ["####.", "....#", "####.", "....#", "####."]),
+    ("4", ["..##.", ".#.#.", "#####", "...#.", "...#."]),
+    ("5", ["#####", "#....", "####.", "....#", "####."]),
+    ("6", [".####", "#....", "####.", "#...#", ".###."]),
+    ("7", ["#####", "....#", "...#.", "..#..", "..#.."]),
+    ("8", [".###.", "#...#", ".###.", "#...#", ".###."]),
+    ("9", [".###.", "#...#", ".####", "....#", ".###."]),
+    ("a", [".###.", "#...#", "#####", "#...#", "#...#"]),
+    ("b", ["####.", "#...#", "####.", "#...#", "####."]),
+    ("c", [".####", "#....", "#....", "#....", ".####"]),
+    ("d", ["####.", "#...#", "#...#", "#...#", "####."]),
+    ("e", ["#####", "#....", "#####", "#....", "#####"]),
+    ("f", ["#####", "#....", "#####", "#....", "#...."]),
+    ("g", [".####", "#....", "#....", "#...#", ".####"]),
+    ("h", ["#...#", "#...#", "#####", "#...#", "#...#"]),
+    ("i", ["#####", "..#..", "..#..", "..#..", "#####"]),
+    ("j", ["#####", "..#..", "..#..", "..#..", "###.."]),
+    ("k", ["#...#", "#..#.", "###..", "#..#.", "#...#"]),
+    ("l", ["#....", "#....", "#....", "#....", "#####"]),
+    ("m", ["#...#", "##.##", "#.#.#", "#...#", "#...#"]),
+    ("n", ["#...#", "##..#", "#.#.#", "#..##", "#...#"]),
+    ("o", [".###.", "#...#", "#...#", "#...#", ".###."]),
+    ("p", ["####.", "#...#", "####.", "#....", "#...."]),
+    ("q", [".###.", "#...#", "#...#", "#..##", ".####"]),
+    ("r", ["####.", "#...#", "####.", "#.#..", "#..#."]),
+    ("s", [".####", "#....", ".###.", "....#", "####."]),
+    ("t", ["#####", "..#..", "..#..", "..#..", "..#.."]),
+    ("u", ["#...#", "#...#", "#...#", "#...#", ".###."]),
+    ("v", ["#...#", "#...#", ".#.#.", ".#.#.", "..#.."]),
+    ("w", ["#...#", "#...#", "#.#.#", "#.#.#", ".###."]),
+    ("x", ["#...#", ".#.#.", "..#..", ".#.#.", "#...#"]),
+    ("y", ["#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ("z", ["#####", "...#.", "..#..", ".#...", "#####"]),
+    (" ", [".....", ".....", ".....", ".....", "....."]),
+    (".", [".....", ".....", ".....", ".....", ".#..."]),
+    (",", [".....", ".....", ".....", "..#..", ".#..."]),
+    ("!", ["..#..", "..#..", "..#..", ".....", "..#.."]),
+    ("?", [".##..", "...#.", "..#..", ".....", "..#.."]),
+    ("[", [".##..", ".#...", ".#...", ".#...", ".##.."]),
+    ("]", ["..##.", "...#.", "...#.", "...#.", "..##."]),
+    ("(", ["..#..", ".#...", ".#...", ".#...", "..#.."]),
+    (")", ["..#..", "...#.", "...#.", "...#.", "..#.."]),
+    ("{", ["..##.", "..#..", ".##..", "..#..", "..##."]),
+    ("}", [".##..", "..#..", "..##.", "..#..", ".##.."]),
+    ("<", ["...#.", "..#..", ".#...", "..#..", "...#."]),
+    (">", [".#...", "..#..", "...#.", "..#..", ".#..."]),
+    ("+", ["..#..", "..#..", "#####", "..#..", "..#.."]),
+    ("-", [".....", ".....", "#####", ".....", "....."]),
+    (":", [".....", "..#..", ".....", "..#..", "....."]),
+    ("/", ["....#", "...#.", "..#..", ".#...", "#...."]),
+    ("%", ["#...#", "...#.", "..#..", ".#...", "#...#"]),
+    ("\"", [".#.#.", ".#.#.", ".....", ".....", "....."]),
+    ("'", ["..#..", "..#..", ".....", ".....", "....."]),
+    ("`", [".#...", "..#..", ".....", ".....", "....."]),
+    ("#", [".#.#.", "#####", ".#.#.", "#####", ".#.#."]),
+    ("$", [".####", "#.#..", ".###.", "..#.#", "####."]),
+    ("&", [".##..", "#..#.", ".##.#", "#..#.", ".##.#"]),
+    ("*", ["#.#.#", ".###.", "#####", ".###.", "#.#.#"]),
+    (";", [".....", "..#..", ".....", "..#..", ".#..."]),
+    ("=", [".....", "#####", ".....", "#####", "....."]),
+    ("@", [".###.", "#...#", "#.###", "#.##.", ".####"]),
+    ("\\", ["#....", ".#...", "..#..", "...#.", "....#"]),
+    ("^", ["..#..", ".#.#.", "#...#", ".....", "....."]),
+    ("_", [".....", ".....", ".....", ".....", "#####"]),
+    ("|", ["..#..", "..#..", "..#..", "..#..", "..#.."]),
+    ("~", [".....", ".#...", "#.#.#", "...#.", "....."]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FONT: &str = "\
+STARTFONT 2.1
+FONT -test-
+SIZE 8 75 75
+FONTBOUNDINGBOX 4 7 0 -2
+STARTPROPERTIES 3
+FONT_ASCENT 5
+FONT_DESCENT 2
+DEFAULT_CHAR 63
+ENDPROPERTIES
+CHARS 4
+STARTCHAR A
+ENCODING 65
+DWIDTH 5 0
+BBX 3 4 1 0
+BITMAP
+40
+A0
+E0
+A0
+ENDCHAR
+STARTCHAR g
+ENCODING 103
+BBX 2 3 0 -2
+BITMAP
+C
+4
+8
+ENDCHAR
+STARTCHAR question
+ENCODING 63
+DWIDTH 4 0
+BBX 1 1 0 0
+BITMAP
+80
+ENDCHAR
+STARTCHAR unencoded
+ENCODING -1
+BBX 1 1 0 0
+BITMAP
+80
+ENDCHAR
+ENDFONT
+";
+
+    fn rows(glyph: &Glyph) -> Vec<String> {
+        let (width, height) = glyph.size();
+        (0..height).map(|y| (0..width).map(|x| if glyph.bitmap[(x, y)] { '#' } else { '.' }).collect()).collect()
+    }
+    fn bdf_error_line(text: &str) -> usize {
+        match Font::from_bdf(text) {
+            Err(FontError::Bdf { line, .. }) => line,
+            other => panic!("expected a bdf error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn default_font_has_all_of_ascii() {
+        let font = Font::default_5x5();
+        for c in (b' '..=b'~').map(char::from) {
+            assert!(font.has_glyph(c), "{:?} is missing", c);
+        }
+        assert_eq!(rows(font.glyph('a')), rows(font.glyph('A')));
+        assert!(!font.has_glyph('\u{e9}'));
+        assert_eq!(font.glyph('\u{e9}').size(), font.missing.size());
+    }
+
+    #[test]
+    fn reads_bdf() {
+        let font = Font::from_bdf(FONT).unwrap();
+        assert_eq!((font.ascent, font.descent, font.line_height, font.height()), (5, 2, 7, 7));
+
+        let a = font.glyph('A');
+        assert_eq!(rows(a), [".#.", "#.#", "###", "#.#"]);
+        assert_eq!((a.offset, a.advance), (D2::from((1, 0)), 5));
+        // no DWIDTH of its own and none for the font, so it's as wide as its box
+        let g = font.glyph('g');
+        assert_eq!(rows(g), ["##", ".#", "#."]);
+        assert_eq!((g.offset, g.advance), (D2::from((0, -2)), 2));
+
+        assert_eq!(rows(&font.missing), ["#"]);
+        assert_eq!(font.missing.advance, 4);
+        assert!(!font.has_glyph('B'));
+        assert_eq!(font.glyph('B').advance, 4);
+    }
+
+    #[test]
+    fn bdf_falls_back_to_the_font_bounding_box() {
+        let text = FONT.replace("FONT_ASCENT 5\n", "").replace("FONT_DESCENT 2\n", "");
+        let font = Font::from_bdf(&text).unwrap();
+        assert_eq!((font.ascent, font.descent), (5, 2));
+    }
+
+    #[test]
+    fn bad_bdf() {
+        assert_eq!(bdf_error_line("FONT x\nSTARTFONT 2.1\nENDFONT"), 1);
+        assert_eq!(bdf_error_line("STARTFONT 2.1\nFONT x"), 2);
+        assert_eq!(bdf_error_line("STARTFONT 2.1\nFONT_ASCENT 60000\nFONT_DESCENT 60000\nENDFONT"), 4);
+        assert_eq!(bdf_error_line("STARTFONT 2.1\nFONT_ASCENT -1\nENDFONT"), 2);
+        assert_eq!(bdf_error_line("STARTFONT 2.1\nFONTBOUNDINGBOX 1 1 0 -40000\nENDFONT"), 2);
+        assert_eq!(bdf_error_line("STARTFONT 2.1\nFONTBOUNDINGBOX 1 1000 0 32000\nFONT_DESCENT 40000\nENDFONT"), 4);
+        assert_eq!(bdf_error_line("STARTFONT 2.1\nSTARTCHAR a\nBBX 100000000 100000000 0 0\nENDCHAR\nENDFONT"), 3);
+        assert_eq!(bdf_error_line("STARTFONT 2.1\nSTARTCHAR a\nBITMAP\nENDCHAR\nENDFONT"), 3);
+        assert_eq!(bdf_error_line("STARTFONT 2.1\nSTARTCHAR a\nBBX 8 2 0 0\nBITMAP\nFF\nZZ\nENDCHAR\nENDFONT"), 6);
+        assert_eq!(bdf_error_line("STARTFONT 2.1\nSTARTCHAR a\nBBX 8 2 0 0\nBITMAP\nFF"), 4);
+        assert_eq!(bdf_error_line("STARTFONT 2.1\nSTARTCHAR a\nENCODING 65"), 3);
+        assert_eq!(bdf_error_line("STARTFONT 2.1\nDWIDTH x 0\nENDFONT"), 2);
+    }
+
+    #[test]
+    fn measuring_text() {
+        let mut font = Font::from_bdf(FONT).unwrap();
+        assert_eq!(font.pen_positions("AgA").collect::<Vec<_>>(), [('A', 0), ('g', 5), ('A', 7)]);
+        font.set_kerning('A', 'g', -1);
+        assert_eq!(font.pen_positions("AgA").collect::<Vec<_>>(), [('A', 0), ('g', 4), ('A', 6)]);
+        assert_eq!(font.advance_width("AgA"), 11);
+        // the last A starts its pixels 1 in and is 3 wide
+        assert_eq!(font.text_width("AgA"), 10);
+        assert_eq!(font.text_size("A\nAg"), (6, 14));
+        assert_eq!(font.text_size(""), (0, 7));
+    }
+}
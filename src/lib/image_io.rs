@@ -0,0 +1,680 @@
+use std::{fs, io, path::Path};
+
+use crate::draw_functions::Color;
+use crate::render_target::RenderTarget;
+use crate::vec2d::Vec2d;
+
+// Image files are stored top row first, which is already how a [Vec2d] is stored because the drawing
+// functions flip y in [draw_pixel](crate::draw_pixel). So nothing needs flipping here except for BMP,
+// which is stored bottom row first.
+
+/// the image file formats that can be written. All of them except PNG can be read back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// binary `P6` portable pixmap, drops alpha
+    Ppm,
+    /// 32 bit uncompressed bitmap with alpha in the 4th byte
+    Bmp,
+    Qoi,
+    /// RGBA PNG with the image data stored uncompressed, so the files are big
+    Png,
+}
+impl ImageFormat {
+    /// guesses the format from the extension of `path`
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "ppm" => Some(ImageFormat::Ppm),
+            "bmp" => Some(ImageFormat::Bmp),
+            "qoi" => Some(ImageFormat::Qoi),
+            "png" => Some(ImageFormat::Png),
+            _ => None,
+        }
+    }
+    /// guesses the format from the first few bytes of a file
+    pub fn from_magic(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"P6") || bytes.starts_with(b"P3") {
+            Some(ImageFormat::Ppm)
+        } else if bytes.starts_with(b"BM") {
+            Some(ImageFormat::Bmp)
+        } else if bytes.starts_with(b"qoif") {
+            Some(ImageFormat::Qoi)
+        } else if bytes.starts_with(&PNG_SIGNATURE) {
+            Some(ImageFormat::Png)
+        } else {
+            None
+        }
+    }
+    pub fn encode<T: RenderTarget + ?Sized>(self, img: &T) -> Vec<u8> {
+        match self {
+            ImageFormat::Ppm => encode_ppm(img),
+            ImageFormat::Bmp => encode_bmp(img),
+            ImageFormat::Qoi => encode_qoi(img),
+            ImageFormat::Png => encode_png(img),
+        }
+    }
+    pub fn decode(self, bytes: &[u8]) -> io::Result<Vec2d<Color>> {
+        match self {
+            ImageFormat::Ppm => decode_ppm(bytes),
+            ImageFormat::Bmp => decode_bmp(bytes),
+            ImageFormat::Qoi => decode_qoi(bytes),
+            ImageFormat::Png => Err(invalid_data("reading PNG files is not supported")),
+        }
+    }
+}
+
+/// writes `img` to `path` in the format its extension asks for
+pub fn save_image<T: RenderTarget + ?Sized, P: AsRef<Path>>(img: &T, path: P) -> io::Result<()> {
+    let Some(format) = ImageFormat::from_path(&path) else {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "unknown image file extension"))
+    };
+    fs::write(path, format.encode(img))
+}
+
+/// reads a PPM, BMP or QOI file, the format is taken from the start of the file and not the extension
+pub fn load_image<P: AsRef<Path>>(path: P) -> io::Result<Vec2d<Color>> {
+    let bytes = fs::read(path)?;
+    let Some(format) = ImageFormat::from_magic(&bytes) else {
+        return Err(invalid_data("unknown image format"))
+    };
+    format.decode(&bytes)
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// every pixel of `img` top row first
+fn pixels<T: RenderTarget + ?Sized>(img: &T) -> impl Iterator<Item = Color> + '_ {
+    let (width, height) = img.size();
+    (0..height).flat_map(move |y| {
+        (0..width).map(move |x| img.get_pixel((x, y)).unwrap_or_else(Color::new_transparent))
+    })
+}
+
+/// checks the image isn't too big for the formats to describe or for this machine to hold
+fn checked_len(width: usize, height: usize, bytes_per_pixel: usize) -> io::Result<usize> {
+    width.checked_mul(height)
+        .and_then(|len| len.checked_mul(bytes_per_pixel))
+        .filter(|len| *len <= isize::MAX as usize)
+        .ok_or_else(|| invalid_data("image is too large"))
+}
+
+//----------- PPM
+
+pub fn encode_ppm<T: RenderTarget + ?Sized>(img: &T) -> Vec<u8> {
+    let (width, height) = img.size();
+    let mut bytes = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    bytes.reserve(width * height * 3);
+    for color in pixels(img) {
+        bytes.extend_from_slice(&[color.r, color.g, color.b]);
+    }
+    bytes
+}
+
+/// reads binary (`P6`) and plain text (`P3`) pixmaps, every pixel is opaque
+pub fn decode_ppm(bytes: &[u8]) -> io::Result<Vec2d<Color>> {
+    let binary = match bytes.get(..2) {
+        Some(b"P6") => true,
+        Some(b"P3") => false,
+        _ => return Err(invalid_data("not a PPM file")),
+    };
+    let mut i = 2;
+    // the header is whitespace separated numbers that can have comments between them
+    let next_number = |i: &mut usize| -> io::Result<u32> {
+        loop {
+            match bytes.get(*i) {
+                Some(b'#') => {
+                    while !matches!(bytes.get(*i), Some(b'\n') | None) {
+                        *i += 1;
+                    }
+                }
+                Some(c) if c.is_ascii_whitespace() => *i += 1,
+                _ => break,
+            }
+        }
+        let start = *i;
+        while bytes.get(*i).is_some_and(|c| c.is_ascii_digit()) {
+            *i += 1;
+        }
+        std::str::from_utf8(&bytes[start..*i]).ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| invalid_data("bad number in PPM file"))
+    };
+    let width = next_number(&mut i)? as usize;
+    let height = next_number(&mut i)? as usize;
+    let max_value = next_number(&mut i)?;
+    if max_value == 0 || max_value > 255 {
+        return Err(invalid_data("only PPM files with 8 bit channels are supported"))
+    }
+    let len = checked_len(width, height, 3)?;
+    let channels: Vec<u8> = if binary {
+        // exactly one whitespace byte separates the header from the pixels
+        let start = i + 1;
+        let channels = bytes.get(start..start + len).ok_or_else(|| invalid_data("PPM file is too short"))?;
+        channels.iter().map(|c| (*c as u32).min(max_value) as u8).collect()
+    } else {
+        // every number takes at least two bytes, don't trust the header for more than the file can hold
+        let mut channels = Vec::with_capacity(len.min(bytes.len() / 2));
+        for _ in 0..len {
+            channels.push(next_number(&mut i)?.min(max_value) as u8);
+        }
+        channels
+    };
+    // scale to 0..=255 if the file uses a smaller max value
+    let scale = |c: u8| ((c as u32 * 255 + max_value / 2) / max_value) as u8;
+    Ok(Vec2d::new_with_fill((width, height), |(x, y)| {
+        let i = (y * width + x) * 3;
+        Color::new(scale(channels[i]), scale(channels[i + 1]), scale(channels[i + 2]))
+    }))
+}
+
+//----------- BMP
+
+const BMP_HEADER_LEN: usize = 14 + 40;
+
+pub fn encode_bmp<T: RenderTarget + ?Sized>(img: &T) -> Vec<u8> {
+    let (width, height) = img.size();
+    let image_len = width * height * 4;
+    let mut bytes = Vec::with_capacity(BMP_HEADER_LEN + image_len);
+    // file header
+    bytes.extend_from_slice(b"BM");
+    bytes.extend_from_slice(&((BMP_HEADER_LEN + image_len) as u32).to_le_bytes());
+    bytes.extend_from_slice(&[0; 4]);
+    bytes.extend_from_slice(&(BMP_HEADER_LEN as u32).to_le_bytes());
+    // BITMAPINFOHEADER
+    bytes.extend_from_slice(&40u32.to_le_bytes());
+    bytes.extend_from_slice(&(width as i32).to_le_bytes());
+    // a positive height means the rows are stored bottom first
+    bytes.extend_from_slice(&(height as i32).to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&32u16.to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // no compression
+    bytes.extend_from_slice(&(image_len as u32).to_le_bytes());
+    bytes.extend_from_slice(&2835i32.to_le_bytes()); // 72 dpi
+    bytes.extend_from_slice(&2835i32.to_le_bytes());
+    bytes.extend_from_slice(&[0; 8]);
+    for y in (0..height).rev() {
+        for x in 0..width {
+            let color = img.get_pixel((x, y)).unwrap_or_else(Color::new_transparent);
+            bytes.extend_from_slice(&[color.b, color.g, color.r, color.alpha]);
+        }
+    }
+    bytes
+}
+
+/// reads uncompressed 24 and 32 bit bitmaps stored either bottom or top row first, and 32 bit bitfields ones
+/// whose masks are the plain BGRA layout.
+/// 32 bit files where every alpha byte is 0 are treated as opaque, since most programs leave it unused
+pub fn decode_bmp(bytes: &[u8]) -> io::Result<Vec2d<Color>> {
+    let u32_at = |i: usize| -> io::Result<u32> {
+        bytes.get(i..i + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(|| invalid_data("BMP file is too short"))
+    };
+    if !bytes.starts_with(b"BM") {
+        return Err(invalid_data("not a BMP file"))
+    }
+    let data_start = u32_at(10)? as usize;
+    let width = u32_at(18)? as i32;
+    let height = u32_at(22)? as i32;
+    let bits = u32_at(28)? & 0xFFFF;
+    let compression = u32_at(30)?;
+    // set if a bitfields header says there is no alpha channel
+    let mut no_alpha_mask = false;
+    // 3 is bitfields, only accepted with the masks that are the same as no compression
+    if compression == 3 {
+        let masks = (u32_at(54)?, u32_at(58)?, u32_at(62)?);
+        if bits != 32 || masks != (0x00FF_0000, 0x0000_FF00, 0x0000_00FF) {
+            return Err(invalid_data("only BMP bitfields in BGRA order are supported"))
+        }
+        // headers newer than BITMAPINFOHEADER have an alpha mask after the color masks
+        if u32_at(14)? >= 56 {
+            match u32_at(66)? {
+                0 => no_alpha_mask = true,
+                0xFF00_0000 => {}
+                _ => return Err(invalid_data("only BMP bitfields in BGRA order are supported")),
+            }
+        }
+    } else if compression != 0 {
+        return Err(invalid_data("compressed BMP files are not supported"))
+    }
+    let bytes_per_pixel = match bits {
+        24 => 3,
+        32 => 4,
+        _ => return Err(invalid_data("only 24 and 32 bit BMP files are supported")),
+    };
+    if width < 0 {
+        return Err(invalid_data("BMP file has a negative width"))
+    }
+    let bottom_first = height > 0;
+    let (width, height) = (width as usize, height.unsigned_abs() as usize);
+    if width == 0 || height == 0 {
+        return Ok(Vec2d::new_with_fill((width, height), |_| Color::new_transparent()))
+    }
+    // rows are padded to 4 bytes
+    let row_len = checked_len(width, 1, bytes_per_pixel)?.div_ceil(4) * 4;
+    let len = checked_len(row_len, height, 1)?;
+    let data = bytes.get(data_start..data_start.saturating_add(len))
+        .ok_or_else(|| invalid_data("BMP file is too short"))?;
+    let ignore_alpha = bytes_per_pixel == 3 || no_alpha_mask || data.chunks(row_len).all(|row| {
+        row[..width * 4].chunks(4).all(|p| p[3] == 0)
+    });
+    Ok(Vec2d::new_with_fill((width, height), |(x, y)| {
+        let row = if bottom_first { height - 1 - y } else { y };
+        let p = &data[row * row_len + x * bytes_per_pixel..];
+        let alpha = if ignore_alpha { 255 } else { p[3] };
+        Color::new_with_alpha(p[2], p[1], p[0], alpha)
+    }))
+}
+
+//----------- QOI
+
+const QOI_OP_INDEX: u8 = 0x00;
+const QOI_OP_DIFF: u8 = 0x40;
+const QOI_OP_LUMA: u8 = 0x80;
+const QOI_OP_RUN: u8 = 0xC0;
+const QOI_OP_RGB: u8 = 0xFE;
+const QOI_OP_RGBA: u8 = 0xFF;
+const QOI_MASK: u8 = 0xC0;
+const QOI_END: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+
+fn qoi_hash(color: Color) -> usize {
+    (color.r as usize * 3 + color.g as usize * 5 + color.b as usize * 7 + color.alpha as usize * 11) % 64
+}
+
+/// see <https://qoiformat.org/qoi-specification.pdf>, always written with 4 channels
+pub fn encode_qoi<T: RenderTarget + ?Sized>(img: &T) -> Vec<u8> {
+    let (width, height) = img.size();
+    let mut bytes = Vec::with_capacity(14 + width * height + QOI_END.len());
+    bytes.extend_from_slice(b"qoif");
+    bytes.extend_from_slice(&(width as u32).to_be_bytes());
+    bytes.extend_from_slice(&(height as u32).to_be_bytes());
+    bytes.extend_from_slice(&[4, 0]); // RGBA, sRGB
+
+    let mut index = [Color::new_transparent(); 64];
+    let mut prev = Color::new(0, 0, 0);
+    let mut run = 0u8;
+    for color in pixels(img) {
+        if color == prev {
+            run += 1;
+            if run == 62 {
+                bytes.push(QOI_OP_RUN | (run - 1));
+                run = 0;
+            }
+            continue
+        }
+        if run > 0 {
+            bytes.push(QOI_OP_RUN | (run - 1));
+            run = 0;
+        }
+        let hash = qoi_hash(color);
+        if index[hash] == color {
+            bytes.push(QOI_OP_INDEX | hash as u8);
+        } else {
+            index[hash] = color;
+            if color.alpha == prev.alpha {
+                let dr = color.r.wrapping_sub(prev.r) as i8;
+                let dg = color.g.wrapping_sub(prev.g) as i8;
+                let db = color.b.wrapping_sub(prev.b) as i8;
+                let dr_dg = dr.wrapping_sub(dg);
+                let db_dg = db.wrapping_sub(dg);
+                if (-2..=1).contains(&dr) && (-2..=1).contains(&dg) && (-2..=1).contains(&db) {
+                    bytes.push(QOI_OP_DIFF | ((dr + 2) as u8) << 4 | ((dg + 2) as u8) << 2 | (db + 2) as u8);
+                } else if (-32..=31).contains(&dg) && (-8..=7).contains(&dr_dg) && (-8..=7).contains(&db_dg) {
+                    bytes.push(QOI_OP_LUMA | (dg + 32) as u8);
+                    bytes.push(((dr_dg + 8) as u8) << 4 | (db_dg + 8) as u8);
+                } else {
+                    bytes.extend_from_slice(&[QOI_OP_RGB, color.r, color.g, color.b]);
+                }
+            } else {
+                bytes.extend_from_slice(&[QOI_OP_RGBA, color.r, color.g, color.b, color.alpha]);
+            }
+        }
+        prev = color;
+    }
+    if run > 0 {
+        bytes.push(QOI_OP_RUN | (run - 1));
+    }
+    bytes.extend_from_slice(&QOI_END);
+    bytes
+}
+
+pub fn decode_qoi(bytes: &[u8]) -> io::Result<Vec2d<Color>> {
+    if bytes.len() < 14 || !bytes.starts_with(b"qoif") {
+        return Err(invalid_data("not a QOI file"))
+    }
+    let width = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
+    let height = u32::from_be_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize;
+    let len = checked_len(width, height, 1)?;
+    // every pixel takes at least a bit, so refuse sizes the file can't possibly hold before allocating
+    if len / 62 > bytes.len() {
+        return Err(invalid_data("QOI file is too short"))
+    }
+    let too_short = || invalid_data("QOI file is too short");
+
+    let mut pixels = Vec::with_capacity(len);
+    let mut index = [Color::new_transparent(); 64];
+    let mut color = Color::new(0, 0, 0);
+    let mut i = 14;
+    while pixels.len() < len {
+        let op = *bytes.get(i).ok_or_else(too_short)?;
+        i += 1;
+        if op == QOI_OP_RGB || op == QOI_OP_RGBA {
+            let n = if op == QOI_OP_RGB { 3 } else { 4 };
+            let c = bytes.get(i..i + n).ok_or_else(too_short)?;
+            color = Color::new_with_alpha(c[0], c[1], c[2], if n == 4 { c[3] } else { color.alpha });
+            i += n;
+        } else {
+            match op & QOI_MASK {
+                QOI_OP_INDEX => color = index[op as usize],
+                QOI_OP_DIFF => {
+                    color.r = color.r.wrapping_add((op >> 4) & 3).wrapping_sub(2);
+                    color.g = color.g.wrapping_add((op >> 2) & 3).wrapping_sub(2);
+                    color.b = color.b.wrapping_add(op & 3).wrapping_sub(2);
+                }
+                QOI_OP_LUMA => {
+                    let next = *bytes.get(i).ok_or_else(too_short)?;
+                    i += 1;
+                    let dg = (op & 0x3F).wrapping_sub(32);
+                    color.r = color.r.wrapping_add(dg).wrapping_add(next >> 4).wrapping_sub(8);
+                    color.g = color.g.wrapping_add(dg);
+                    color.b = color.b.wrapping_add(dg).wrapping_add(next & 0xF).wrapping_sub(8);
+                }
+                _ => {
+                    // QOI_OP_RUN, the current pixel gets pushed once more below
+                    let run = (op & 0x3F) as usize;
+                    for _ in 0..run.min(len - pixels.len() - 1) {
+                        pixels.push(color);
+                    }
+                }
+            }
+        }
+        index[qoi_hash(color)] = color;
+        pixels.push(color);
+    }
+    Ok(Vec2d::new_with_fill((width, height), |(x, y)| pixels[y * width + x]))
+}
+
+//----------- PNG
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+/// the most a stored deflate block can hold
+const MAX_STORED_BLOCK: usize = 0xFFFF;
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 is the most bytes that can be summed before `b` could overflow
+    for chunk in bytes.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+fn push_png_chunk(bytes: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = bytes.len();
+    bytes.extend_from_slice(kind);
+    bytes.extend_from_slice(data);
+    let crc = crc32(&bytes[start..]);
+    bytes.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// 8 bit RGBA without compression, only stored deflate blocks are used so no compression library is needed
+pub fn encode_png<T: RenderTarget + ?Sized>(img: &T) -> Vec<u8> {
+    let (width, height) = img.size();
+    // every row starts with a filter type byte, 0 is no filter
+    let mut raw = Vec::with_capacity(height * (1 + width * 4));
+    for y in 0..height {
+        raw.push(0);
+        for x in 0..width {
+            let color = img.get_pixel((x, y)).unwrap_or_else(Color::new_transparent);
+            raw.extend_from_slice(&[color.r, color.g, color.b, color.alpha]);
+        }
+    }
+
+    // zlib stream: header, stored deflate blocks, adler32 of the uncompressed data
+    let mut zlib = Vec::with_capacity(raw.len() + raw.len() / MAX_STORED_BLOCK * 5 + 11);
+    zlib.extend_from_slice(&[0x78, 0x01]);
+    let mut blocks = raw.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        // an empty image still needs one final block
+        zlib.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        zlib.push(last as u8);
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&(width as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(height as u32).to_be_bytes());
+    // bit depth 8, color type 6 (RGBA), default compression, filter and no interlacing
+    ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut bytes = PNG_SIGNATURE.to_vec();
+    push_png_chunk(&mut bytes, b"IHDR", &ihdr);
+    push_png_chunk(&mut bytes, b"IDAT", &zlib);
+    push_png_chunk(&mut bytes, b"IEND", &[]);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// runs, small and big steps, repeats and a bit of transparency so every QOI op gets used
+    fn test_image(alpha: bool) -> Vec2d<Color> {
+        Vec2d::new_with_fill((7, 5), |(x, y)| match (x, y) {
+            (0..=3, 0) => Color::new(10, 20, 30),
+            (_, 1) => Color::new(10 + x as u8, 20 + x as u8, 30 - x as u8),
+            (_, 2) => Color::new(x as u8 * 40, 255 - x as u8 * 30, 7),
+            (x, 3) if x % 2 == 0 => Color::new(10, 20, 30),
+            (_, 4) if alpha => Color::new_with_alpha(200, 100, x as u8, x as u8 * 30),
+            _ => Color::new(x as u8, y as u8, 99),
+        })
+    }
+    fn assert_same(a: &Vec2d<Color>, b: &Vec2d<Color>) {
+        assert_eq!(a.len(), b.len());
+        for y in 0..a.len().1 {
+            for x in 0..a.len().0 {
+                assert_eq!(a[(x, y)], b[(x, y)], "pixel {:?}", (x, y));
+            }
+        }
+    }
+    /// every cut of the file errors or at least doesn't panic, and cuts before `needed` bytes always error
+    fn assert_truncated_fails(format: ImageFormat, bytes: &[u8], needed: usize) {
+        for cut in 0..bytes.len() {
+            let result = format.decode(&bytes[..cut]);
+            if cut < needed {
+                assert!(result.is_err(), "{:?} cut to {} bytes decoded", format, cut);
+            }
+        }
+    }
+
+    #[test]
+    fn ppm_round_trip_drops_alpha() {
+        let img = test_image(true);
+        let bytes = encode_ppm(&img);
+        assert_eq!(ImageFormat::from_magic(&bytes), Some(ImageFormat::Ppm));
+        let opaque = Vec2d::new_with_fill(img.len(), |pt| {
+            let c = img[pt];
+            Color::new(c.r, c.g, c.b)
+        });
+        assert_same(&decode_ppm(&bytes).unwrap(), &opaque);
+        assert_truncated_fails(ImageFormat::Ppm, &bytes, bytes.len());
+    }
+
+    #[test]
+    fn plain_ppm() {
+        let img = decode_ppm(b"P3\n# a comment\n2 1\n15\n15 0 0\n0 7 30").unwrap();
+        assert_eq!(img.len(), (2, 1));
+        assert_eq!(img[(0, 0)], Color::new(255, 0, 0));
+        // 7 of 15 rounds to 119, values over the max are clamped
+        assert_eq!(img[(1, 0)], Color::new(0, 119, 255));
+        assert!(decode_ppm(b"P3 2 1 255 1 2 3 4 5").is_err());
+        assert!(decode_ppm(b"P3 100000 100000 255\n1 2 3").is_err());
+        assert!(decode_ppm(b"P6 1 1 0\n\0\0\0").is_err());
+        assert!(decode_ppm(b"P6 1 1 65535\n\0\0\0\0\0\0").is_err());
+        assert!(decode_ppm(b"P6 99999999999 99999999999 255\n").is_err());
+        assert!(decode_ppm(b"P5 1 1 255\n\0").is_err());
+    }
+
+    #[test]
+    fn binary_ppm_channels_over_the_max_are_clamped() {
+        let img = decode_ppm(b"P6 2 1 100\n\xC8\x32\x64\xFF\x00\x65").unwrap();
+        assert_eq!(img[(0, 0)], Color::new(255, 128, 255));
+        assert_eq!(img[(1, 0)], Color::new(255, 0, 255));
+    }
+
+    #[test]
+    fn bmp_round_trip() {
+        let img = test_image(true);
+        let bytes = encode_bmp(&img);
+        assert_eq!(ImageFormat::from_magic(&bytes), Some(ImageFormat::Bmp));
+        assert_same(&decode_bmp(&bytes).unwrap(), &img);
+        assert_truncated_fails(ImageFormat::Bmp, &bytes, bytes.len());
+    }
+
+    #[test]
+    fn bmp_with_unused_alpha_is_opaque() {
+        let img = test_image(false);
+        let mut bytes = encode_bmp(&img);
+        for pixel in bytes[BMP_HEADER_LEN..].chunks_mut(4) {
+            pixel[3] = 0;
+        }
+        assert_same(&decode_bmp(&bytes).unwrap(), &img);
+    }
+
+    /// `bytes` from [encode_bmp] turned into a bitfields file with a header `header_len` long.
+    /// The masks go right after a BITMAPINFOHEADER and are part of the longer headers
+    fn with_bitfields(bytes: &[u8], header_len: u32, masks: &[u32]) -> Vec<u8> {
+        let mut fields: Vec<u8> = masks.iter().flat_map(|mask| mask.to_le_bytes()).collect();
+        fields.resize((header_len as usize - 40).max(12), 0);
+        let mut bitfields = bytes[..BMP_HEADER_LEN].to_vec();
+        bitfields.extend_from_slice(&fields);
+        bitfields.extend_from_slice(&bytes[BMP_HEADER_LEN..]);
+        bitfields[10..14].copy_from_slice(&((BMP_HEADER_LEN + fields.len()) as u32).to_le_bytes());
+        bitfields[14..18].copy_from_slice(&header_len.to_le_bytes());
+        bitfields[30..34].copy_from_slice(&3u32.to_le_bytes());
+        bitfields
+    }
+
+    #[test]
+    fn bmp_bitfields_only_in_bgra_order() {
+        let img = test_image(true);
+        let bytes = encode_bmp(&img);
+        let bgr = [0x00FF_0000, 0x0000_FF00, 0x0000_00FF];
+        assert_same(&decode_bmp(&with_bitfields(&bytes, 40, &bgr)).unwrap(), &img);
+        assert_same(&decode_bmp(&with_bitfields(&bytes, 108, &[bgr[0], bgr[1], bgr[2], 0xFF00_0000])).unwrap(), &img);
+        // no alpha mask means the alpha bytes are unused
+        let opaque = decode_bmp(&with_bitfields(&bytes, 108, &[bgr[0], bgr[1], bgr[2], 0])).unwrap();
+        assert!(opaque.get_flat1().iter().all(|c| c.alpha == 255));
+
+        assert!(decode_bmp(&with_bitfields(&bytes, 40, &[0xFF00_0000, 0x00FF_0000, 0x0000_FF00])).is_err());
+        assert!(decode_bmp(&with_bitfields(&bytes, 40, &[0x0000_00FF, 0x0000_FF00, 0x00FF_0000])).is_err());
+        assert!(decode_bmp(&with_bitfields(&bytes, 108, &[bgr[0], bgr[1], bgr[2], 0x0000_00FF])).is_err());
+        // 565 and the like
+        assert!(decode_bmp(&with_bitfields(&bytes, 40, &[0xF800, 0x07E0, 0x001F])).is_err());
+    }
+
+    #[test]
+    fn qoi_round_trip() {
+        for alpha in [false, true] {
+            let img = test_image(alpha);
+            let bytes = encode_qoi(&img);
+            assert_eq!(ImageFormat::from_magic(&bytes), Some(ImageFormat::Qoi));
+            assert_same(&decode_qoi(&bytes).unwrap(), &img);
+            // the end marker isn't needed to read the pixels
+            assert_truncated_fails(ImageFormat::Qoi, &bytes, bytes.len() - QOI_END.len());
+        }
+        let mut huge = b"qoif".to_vec();
+        huge.extend_from_slice(&[0x7F, 0xFF, 0xFF, 0xFF, 0x7F, 0xFF, 0xFF, 0xFF, 4, 0]);
+        assert!(decode_qoi(&huge).is_err());
+    }
+
+    /// there is no PNG reader, so this takes apart the stored deflate blocks by hand
+    fn png_pixels(bytes: &[u8]) -> (u32, u32, Vec<u8>) {
+        assert!(bytes.starts_with(&PNG_SIGNATURE));
+        let mut i = PNG_SIGNATURE.len();
+        let (mut size, mut zlib) = ((0, 0), Vec::new());
+        while i < bytes.len() {
+            let len = u32::from_be_bytes(bytes[i..i + 4].try_into().unwrap()) as usize;
+            let kind = &bytes[i + 4..i + 8];
+            let data = &bytes[i + 8..i + 8 + len];
+            let crc = u32::from_be_bytes(bytes[i + 8 + len..i + 12 + len].try_into().unwrap());
+            assert_eq!(crc, crc32(&bytes[i + 4..i + 8 + len]), "crc of {:?}", kind);
+            match kind {
+                b"IHDR" => {
+                    size = (u32::from_be_bytes(data[0..4].try_into().unwrap()), u32::from_be_bytes(data[4..8].try_into().unwrap()));
+                    assert_eq!(&data[8..], &[8, 6, 0, 0, 0]);
+                }
+                b"IDAT" => zlib.extend_from_slice(data),
+                b"IEND" => assert_eq!(i + 12 + len, bytes.len()),
+                _ => panic!("unexpected chunk {:?}", kind),
+            }
+            i += 12 + len;
+        }
+        let mut raw = Vec::new();
+        let mut j = 2;
+        loop {
+            let last = zlib[j] & 1 == 1;
+            let len = u16::from_le_bytes([zlib[j + 1], zlib[j + 2]]) as usize;
+            assert_eq!(!len as u16, u16::from_le_bytes([zlib[j + 3], zlib[j + 4]]));
+            raw.extend_from_slice(&zlib[j + 5..j + 5 + len]);
+            j += 5 + len;
+            if last {
+                break
+            }
+        }
+        assert_eq!(u32::from_be_bytes(zlib[j..j + 4].try_into().unwrap()), adler32(&raw));
+        (size.0, size.1, raw)
+    }
+
+    #[test]
+    fn png_is_stored_rgba() {
+        let img = test_image(true);
+        let (width, height, raw) = png_pixels(&encode_png(&img));
+        assert_eq!((width as usize, height as usize), img.len());
+        for (y, row) in raw.chunks(1 + width as usize * 4).enumerate() {
+            assert_eq!(row[0], 0, "filter type");
+            for (x, p) in row[1..].chunks(4).enumerate() {
+                assert_eq!(Color::new_with_alpha(p[0], p[1], p[2], p[3]), img[(x, y)]);
+            }
+        }
+        // more than one deflate block
+        let big = Vec2d::new_with_fill((200, 100), |(x, y)| Color::new(x as u8, y as u8, 0));
+        let (_, _, raw) = png_pixels(&encode_png(&big));
+        assert_eq!(raw.len(), 100 * (1 + 200 * 4));
+        let (_, _, raw) = png_pixels(&encode_png(&Vec2d::<Color>::new_with_fill((0, 0), |_| Color::black())));
+        assert!(raw.is_empty());
+        assert!(ImageFormat::Png.decode(&encode_png(&img)).is_err());
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E60398);
+    }
+}
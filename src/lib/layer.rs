@@ -0,0 +1,59 @@
+use crate::blend::{mul_255, BlendMode};
+use crate::draw_functions::Color;
+use crate::render_target::RenderTarget;
+use crate::vec2d::Vec2d;
+
+/// offscreen transparent image to draw a plane into before compositing it onto the real target.
+/// Drawing a translucent plane straight onto the target fades each primitive separately, so where two
+/// primitives overlap the plane looks darker. Drawing it into a layer at full opacity and then
+/// compositing the layer at the plane's opacity fades the plane as a whole
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub img: Vec2d<Color>,
+}
+impl Layer {
+    /// should be the same size as the target it will get composited onto. To reuse a layer
+    /// clear it with [Color::new_transparent]
+    pub fn new(len: (usize, usize)) -> Self {
+        Self { img: Vec2d::new_with_fill(len, |_| Color::new_transparent()) }
+    }
+    pub fn len(&self) -> (usize, usize) {
+        self.img.len()
+    }
+    /// blends every pixel of the layer onto `target` with its alpha multiplied by `opacity`.
+    /// The top left pixels line up, anything outside of `target` is ignored
+    pub fn composite<T: RenderTarget + ?Sized>(&self, target: &mut T, opacity: u8, blend_mode: BlendMode) {
+        let (width, height) = self.len();
+        let (target_width, target_height) = target.size();
+        for y in 0..height.min(target_height) {
+            for x in 0..width.min(target_width) {
+                let Some(color) = self.img.get((x, y)) else {
+                    continue
+                };
+                let alpha = mul_255(color.alpha as u32, opacity as u32) as u8;
+                if alpha != 0 || blend_mode == BlendMode::Replace {
+                    target.blend_pixel((x, y), Color::new_with_alpha(color.r, color.g, color.b, alpha), blend_mode);
+                }
+            }
+        }
+    }
+}
+impl RenderTarget for Layer {
+    type Pixel = Color;
+
+    fn size(&self) -> (usize, usize) {
+        self.img.size()
+    }
+    fn get_pixel(&self, coords: (usize, usize)) -> Option<Color> {
+        self.img.get_pixel(coords)
+    }
+    fn put_pixel(&mut self, coords: (usize, usize), color: Color) {
+        self.img.put_pixel(coords, color);
+    }
+    fn row_mut(&mut self, y: usize) -> Option<&mut [Color]> {
+        self.img.row_mut(y)
+    }
+    fn clear(&mut self, color: Color) {
+        self.img.clear(color);
+    }
+}
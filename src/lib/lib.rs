@@ -1,13 +1,43 @@
+pub mod blend;
 pub mod cam_data;
 pub mod coordinates;
 pub mod draw_functions;
+pub mod font;
+pub mod image_io;
+pub mod layer;
+pub mod path;
+pub mod pixel_format;
 pub mod point_conversion;
+pub mod presenter;
+pub mod render_target;
+pub mod scene;
+pub mod scene_file;
 pub mod shape;
+pub mod stroke_font;
+pub mod svg;
+pub mod svg_import;
+pub mod text_layout;
+pub mod texture;
 pub mod vec2d;
 
+pub use blend::*;
 pub use cam_data::*;
 pub use coordinates::*;
 pub use draw_functions::*;
+pub use font::*;
+pub use image_io::*;
+pub use layer::*;
+pub use path::*;
+pub use pixel_format::*;
 // pub use point_conversion::*;
+pub use presenter::*;
+pub use render_target::*;
+pub use scene::*;
+pub use scene_file::*;
 pub use shape::*;
+pub use stroke_font::*;
+pub use svg::*;
+pub use svg_import::*;
+pub use text_layout::*;
+pub use texture::*;
 pub use vec2d::*;
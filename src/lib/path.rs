@@ -0,0 +1,264 @@
+use fixed::types::I32F32;
+use serde::{Serialize, Deserialize};
+
+use crate::coordinates::*;
+
+/// which parts of a [Path] with overlapping or nested outlines get filled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FillRule {
+    /// inside if the outlines wind around the point more times one way than the other,
+    /// holes need to be drawn the opposite direction of the outline around them
+    #[default]
+    NonZero,
+    /// inside if a line from the point crosses the outlines an odd number of times, every nested outline is a hole
+    EvenOdd,
+}
+impl FillRule {
+    /// `winding` is how many times the outlines go counterclockwise around a point minus clockwise
+    pub fn is_inside(&self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+/// one step of a [Path], all points are rel to the draw plane
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PathCommand {
+    /// starts a new outline
+    MoveTo(D2<D1>),
+    LineTo(D2<D1>),
+    /// quadratic bezier with one control point, then the end
+    QuadTo(D2<D1>, D2<D1>),
+    /// cubic bezier with two control points, then the end
+    CubicTo(D2<D1>, D2<D1>, D2<D1>),
+    /// circular arc around `center` starting at the current point, positive `sweep` goes counterclockwise
+    ArcTo { center: D2<D1>, sweep: Angle },
+    /// connects back to the start of the outline
+    Close,
+}
+
+/// an outline of a [Path] split into straight lines
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contour {
+    pub points: Vec<D2<D1>>,
+    /// the last point connects back to the first
+    pub closed: bool,
+}
+
+/// any number of outlines made of lines, curves and arcs. Drawn with [Cam::stroke_path](crate::Cam::stroke_path)
+/// and [Cam::fill_path](crate::Cam::fill_path).
+///
+/// ```ignore
+/// let footprint = Path::new()
+///     .move_to(D2::from((D1::from(0), D1::from(0))))
+///     .line_to(D2::from((D1::from(20), D1::from(0))))
+///     .arc_to(D2::from((D1::from(20), D1::from(10))), Angle::pi())
+///     .line_to(D2::from((D1::from(0), D1::from(20))))
+///     .close();
+/// ```
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Path {
+    commands: Vec<PathCommand>,
+}
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// a closed outline through `points`
+    pub fn polygon(points: &[D2<D1>]) -> Self {
+        let mut path = Self::new();
+        for (i, pt) in points.iter().enumerate() {
+            path = if i == 0 { path.move_to(*pt) } else { path.line_to(*pt) };
+        }
+        if points.is_empty() { path } else { path.close() }
+    }
+
+    pub fn move_to(mut self, pt: D2<D1>) -> Self {
+        self.commands.push(PathCommand::MoveTo(pt));
+        self
+    }
+    /// at the start of a path this starts the outline at `pt` like [Path::move_to]
+    pub fn line_to(mut self, pt: D2<D1>) -> Self {
+        self.commands.push(PathCommand::LineTo(pt));
+        self
+    }
+    pub fn quad_to(mut self, control: D2<D1>, end: D2<D1>) -> Self {
+        self.commands.push(PathCommand::QuadTo(control, end));
+        self
+    }
+    pub fn cubic_to(mut self, control1: D2<D1>, control2: D2<D1>, end: D2<D1>) -> Self {
+        self.commands.push(PathCommand::CubicTo(control1, control2, end));
+        self
+    }
+    /// arc around `center` from the current point, positive `sweep` goes counterclockwise.
+    /// The arc ends wherever turning the current point by `sweep` around `center` puts it
+    pub fn arc_to(mut self, center: D2<D1>, sweep: Angle) -> Self {
+        self.commands.push(PathCommand::ArcTo { center, sweep });
+        self
+    }
+    pub fn close(mut self) -> Self {
+        self.commands.push(PathCommand::Close);
+        self
+    }
+
+    pub fn commands(&self) -> &[PathCommand] {
+        &self.commands
+    }
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// every outline split into lines that are never more than `tolerance` away from the curves.
+    /// Outlines with only one point are skipped. Curves at the start of a path start at the origin
+    pub fn contours(&self, tolerance: D1) -> Vec<Contour> {
+        let mut contours = Vec::new();
+        let mut points: Vec<D2<D1>> = Vec::new();
+        // where the next outline starts if there is no move_to, the start of the last one like in svg
+        let mut start = None;
+        let mut finish = |points: &mut Vec<D2<D1>>, closed: bool| {
+            if points.len() > 1 {
+                contours.push(Contour { points: std::mem::take(points), closed });
+            }
+            points.clear();
+        };
+        for command in &self.commands {
+            let command = match *command {
+                // nothing to draw a line from yet
+                PathCommand::LineTo(pt) if points.is_empty() && start.is_none() => PathCommand::MoveTo(pt),
+                command => command,
+            };
+            if points.is_empty() && !matches!(command, PathCommand::MoveTo(_) | PathCommand::Close) {
+                points.push(start.unwrap_or(D2::zero()));
+            }
+            let current = points.last().copied().unwrap_or(start.unwrap_or(D2::zero()));
+            match command {
+                PathCommand::MoveTo(pt) => {
+                    finish(&mut points, false);
+                    points.push(pt);
+                    start = Some(pt);
+                }
+                PathCommand::LineTo(pt) => points.push(pt),
+                PathCommand::QuadTo(control, end) => {
+                    let cubic = quad_to_cubic([current, control, end]);
+                    points.extend(D2::flatten_cubic_bezier(cubic, tolerance).into_iter().skip(1));
+                }
+                PathCommand::CubicTo(control1, control2, end) => {
+                    let cubic = [current, control1, control2, end];
+                    points.extend(D2::flatten_cubic_bezier(cubic, tolerance).into_iter().skip(1));
+                }
+                PathCommand::ArcTo { center, sweep } => {
+                    for cubic in arc_to_cubics(current, center, sweep) {
+                        points.extend(D2::flatten_cubic_bezier(cubic, tolerance).into_iter().skip(1));
+                    }
+                }
+                PathCommand::Close => {
+                    // the closing line is implied
+                    if points.len() > 1 && points.first() == points.last() {
+                        points.pop();
+                    }
+                    finish(&mut points, true);
+                }
+            }
+        }
+        finish(&mut points, false);
+        contours
+    }
+}
+
+/// the same curve as a cubic bezier
+pub fn quad_to_cubic(points: [D2<D1>; 3]) -> [D2<D1>; 4] {
+    let [p0, p1, p2] = points;
+    let two_thirds = D2::from(D1::two() / D1::from(3));
+    [p0, p0 + (p1 - p0) * two_thirds, p2 + (p1 - p2) * two_thirds, p2]
+}
+
+/// cubic beziers that follow a circular arc from `from` around `center`, one for every quarter turn or less
+pub fn arc_to_cubics(from: D2<D1>, center: D2<D1>, sweep: Angle) -> Vec<[D2<D1>; 4]> {
+    let pieces = (sweep.val.abs() / Angle::half_pi().val).ceil().to_num::<u32>().max(1);
+    let step = Angle::from(sweep.val / I32F32::from_num(pieces));
+    // how far along the tangent the control points go, 4/3 tan(step / 4) of the radius
+    let quarter = Angle::from(step.val / I32F32::from_num(4));
+    let k = D1::from(quarter.sin() / quarter.cos()) * D1::from(4) / D1::from(3);
+    let tangent = |radius: D2<D1>| D2::from((-radius.y, radius.x)) * D2::from(k);
+
+    let mut cubics = Vec::with_capacity(pieces as usize);
+    let mut radius = from - center;
+    for _ in 0..pieces {
+        let next = radius.rotate(step);
+        cubics.push([center + radius, center + radius + tangent(radius), center + next - tangent(next), center + next]);
+        radius = next;
+    }
+    cubics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draw_functions::for_each_path_pixel;
+
+    fn pt(x: i32, y: i32) -> D2<D1> {
+        D2::from((D1::from(x), D1::from(y)))
+    }
+    fn square(min: i32, max: i32, clockwise: bool) -> Vec<D2<D1>> {
+        let mut points = vec![pt(min, min), pt(max, min), pt(max, max), pt(min, max)];
+        if clockwise {
+            points.reverse();
+        }
+        points
+    }
+    fn filled(contours: &[Vec<D2<D1>>], fill_rule: FillRule) -> Vec<(i32, i32)> {
+        let contours: Vec<&[D2<D1>]> = contours.iter().map(|points| points.as_slice()).collect();
+        let mut pixels = Vec::new();
+        for_each_path_pixel((10, 10), &contours, fill_rule, |pixel| pixels.push(pixel));
+        pixels.sort();
+        pixels
+    }
+
+    #[test]
+    fn leading_line_to_starts_the_outline() {
+        let contours = Path::new().line_to(pt(5, 5)).line_to(pt(10, 5)).contours(D1::one());
+        assert_eq!(contours, [Contour { points: vec![pt(5, 5), pt(10, 5)], closed: false }]);
+    }
+
+    #[test]
+    fn line_to_after_close_starts_at_the_last_outline() {
+        let path = Path::polygon(&[pt(1, 1), pt(4, 1), pt(4, 4)]).line_to(pt(0, 9));
+        let contours = path.contours(D1::one());
+        assert_eq!(contours[0], Contour { points: vec![pt(1, 1), pt(4, 1), pt(4, 4)], closed: true });
+        assert_eq!(contours[1], Contour { points: vec![pt(1, 1), pt(0, 9)], closed: false });
+    }
+
+    #[test]
+    fn arcs_end_where_turned() {
+        let contours = Path::new().move_to(pt(2, 0)).arc_to(pt(0, 0), Angle::pi()).contours(D1::one() / D1::from(100));
+        let end = *contours[0].points.last().unwrap();
+        assert!((end - pt(-2, 0)).x.abs() < D1::one() / D1::from(100) && end.y.abs() < D1::one() / D1::from(100), "{:?}", end);
+    }
+
+    #[test]
+    fn nested_outlines_going_the_same_way() {
+        let contours = [square(1, 9, false), square(3, 7, false)];
+        // the hole winds twice, which is still inside for nonzero
+        assert_eq!(filled(&contours, FillRule::NonZero), filled(&[square(1, 9, false)], FillRule::NonZero));
+        let even_odd = filled(&contours, FillRule::EvenOdd);
+        assert!(even_odd.contains(&(1, 1)) && even_odd.contains(&(8, 8)));
+        assert!(!even_odd.contains(&(3, 3)) && !even_odd.contains(&(6, 6)));
+        assert_eq!(even_odd.len(), 64 - 16);
+    }
+
+    #[test]
+    fn nested_outlines_going_opposite_ways() {
+        let contours = [square(1, 9, false), square(3, 7, true)];
+        let non_zero = filled(&contours, FillRule::NonZero);
+        assert_eq!(non_zero, filled(&contours, FillRule::EvenOdd));
+        assert_eq!(non_zero.len(), 64 - 16);
+        assert!(!non_zero.contains(&(5, 5)));
+
+        // a third outline inside the hole fills again for both rules
+        let contours = [square(1, 9, false), square(3, 7, true), square(4, 6, false)];
+        assert!(filled(&contours, FillRule::NonZero).contains(&(5, 5)));
+        assert!(filled(&contours, FillRule::EvenOdd).contains(&(5, 5)));
+    }
+}
@@ -0,0 +1,169 @@
+use serde::{Serialize, Deserialize};
+
+use crate::draw_functions::Color;
+use crate::render_target::RenderTarget;
+use crate::vec2d::Vec2d;
+
+/// how a [Color] is packed into a u32. The first channel in the name is in the most significant bits,
+/// so `Argb8888` is `0xAARRGGBB`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PixelFormat {
+    /// 0RGB8888, the top byte is always 0 and alpha is dropped. What minifb and [Color::to_u32] use
+    Xrgb8888,
+    Argb8888,
+    Rgba8888,
+    Bgra8888,
+    /// 16 bits in the low half of the u32, no alpha
+    Rgb565,
+}
+impl PixelFormat {
+    pub fn has_alpha(self) -> bool {
+        match self {
+            PixelFormat::Xrgb8888 | PixelFormat::Rgb565 => false,
+            PixelFormat::Argb8888 | PixelFormat::Rgba8888 | PixelFormat::Bgra8888 => true,
+        }
+    }
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Rgb565 => 2,
+            _ => 4,
+        }
+    }
+    pub fn encode(self, color: Color) -> u32 {
+        let (r, g, b, a) = (color.r as u32, color.g as u32, color.b as u32, color.alpha as u32);
+        match self {
+            PixelFormat::Xrgb8888 => (r << 16) | (g << 8) | b,
+            PixelFormat::Argb8888 => (a << 24) | (r << 16) | (g << 8) | b,
+            PixelFormat::Rgba8888 => (r << 24) | (g << 16) | (b << 8) | a,
+            PixelFormat::Bgra8888 => (b << 24) | (g << 16) | (r << 8) | a,
+            PixelFormat::Rgb565 => {
+                // round to the nearest representable level instead of truncating
+                let r5 = (r * 31 + 127) / 255;
+                let g6 = (g * 63 + 127) / 255;
+                let b5 = (b * 31 + 127) / 255;
+                (r5 << 11) | (g6 << 5) | b5
+            }
+        }
+    }
+    /// formats without alpha decode as fully opaque
+    pub fn decode(self, pixel: u32) -> Color {
+        let byte = |shift: u32| ((pixel >> shift) & 0xFF) as u8;
+        match self {
+            PixelFormat::Xrgb8888 => Color::new(byte(16), byte(8), byte(0)),
+            PixelFormat::Argb8888 => Color::new_with_alpha(byte(16), byte(8), byte(0), byte(24)),
+            PixelFormat::Rgba8888 => Color::new_with_alpha(byte(24), byte(16), byte(8), byte(0)),
+            PixelFormat::Bgra8888 => Color::new_with_alpha(byte(8), byte(16), byte(24), byte(0)),
+            PixelFormat::Rgb565 => {
+                let r5 = (pixel >> 11) & 0x1F;
+                let g6 = (pixel >> 5) & 0x3F;
+                let b5 = pixel & 0x1F;
+                // copy the top bits into the empty low bits so that 31 becomes 255 and not 248
+                Color::new(
+                    ((r5 << 3) | (r5 >> 2)) as u8,
+                    ((g6 << 2) | (g6 >> 4)) as u8,
+                    ((b5 << 3) | (b5 >> 2)) as u8,
+                )
+            }
+        }
+    }
+}
+
+/// image stored in any [PixelFormat]. Use this instead of a plain `Vec2d<u32>` (which is always
+/// [PixelFormat::Xrgb8888]) when the alpha channel needs to survive
+#[derive(Debug, Clone)]
+pub struct PixelBuffer {
+    pub pixels: Vec2d<u32>,
+    pub format: PixelFormat,
+}
+impl PixelBuffer {
+    /// every pixel starts out as `fill`
+    pub fn new(len: (usize, usize), format: PixelFormat, fill: Color) -> Self {
+        let pixel = format.encode(fill);
+        Self {
+            pixels: Vec2d::new_with_fill(len, |_| pixel),
+            format,
+        }
+    }
+    /// top row first, each pixel written most significant byte first with [PixelFormat::bytes_per_pixel]
+    /// bytes, so `Rgba8888` comes out as the R, G, B, A byte order image encoders expect
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let bytes_per_pixel = self.format.bytes_per_pixel();
+        let mut bytes = Vec::with_capacity(self.pixels.get_flat1().len() * bytes_per_pixel);
+        for pixel in self.pixels.get_flat1() {
+            bytes.extend_from_slice(&pixel.to_be_bytes()[4 - bytes_per_pixel..]);
+        }
+        bytes
+    }
+    /// converts every pixel to another format
+    pub fn convert(&self, format: PixelFormat) -> PixelBuffer {
+        let mut pixels = self.pixels.clone();
+        pixels.get_mut_flat1().iter_mut().for_each(|p| *p = format.encode(self.format.decode(*p)));
+        PixelBuffer { pixels, format }
+    }
+}
+impl RenderTarget for PixelBuffer {
+    type Pixel = u32;
+
+    fn size(&self) -> (usize, usize) {
+        self.pixels.len()
+    }
+    fn get_pixel(&self, coords: (usize, usize)) -> Option<Color> {
+        if coords.0 >= self.pixels.len().0 {
+            return None
+        }
+        self.pixels.get(coords).map(|p| self.format.decode(*p))
+    }
+    fn put_pixel(&mut self, coords: (usize, usize), color: Color) {
+        let pixel = self.format.encode(color);
+        if let Some(p) = self.pixels.get_mut(coords) {
+            *p = pixel;
+        }
+    }
+    fn row_mut(&mut self, y: usize) -> Option<&mut [u32]> {
+        self.pixels.row_mut(y)
+    }
+    fn clear(&mut self, color: Color) {
+        self.pixels.get_mut_flat1().fill(self.format.encode(color));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draw_functions::{from_rgb, from_rgba};
+
+    #[test]
+    fn formats_round_trip() {
+        let color = Color::new_with_alpha(0x12, 0x34, 0x56, 0x78);
+        assert_eq!(PixelFormat::Xrgb8888.encode(color), 0x00123456);
+        assert_eq!(PixelFormat::Argb8888.encode(color), 0x78123456);
+        assert_eq!(PixelFormat::Rgba8888.encode(color), 0x12345678);
+        assert_eq!(PixelFormat::Bgra8888.encode(color), 0x56341278);
+        for format in [PixelFormat::Argb8888, PixelFormat::Rgba8888, PixelFormat::Bgra8888] {
+            assert_eq!(format.decode(format.encode(color)), color, "{:?}", format);
+        }
+        // alpha is dropped and comes back opaque
+        assert_eq!(PixelFormat::Xrgb8888.decode(PixelFormat::Xrgb8888.encode(color)), Color::new(0x12, 0x34, 0x56));
+    }
+
+    #[test]
+    fn rgb565_rounds_to_the_nearest_level() {
+        let format = PixelFormat::Rgb565;
+        assert_eq!(format.encode(Color::new(255, 255, 255)), 0xFFFF);
+        assert_eq!(format.encode(Color::new(0, 0, 0)), 0);
+        // 4 is closer to level 0 (0) than level 1 (8), 5 is closer to level 1
+        assert_eq!(format.encode(Color::new(4, 0, 0)), 0);
+        assert_eq!(format.encode(Color::new(5, 0, 0)), 1 << 11);
+        assert_eq!(format.encode(Color::new(0, 2, 0)), 0);
+        assert_eq!(format.encode(Color::new(0, 3, 0)), 1 << 5);
+        assert_eq!(format.decode(0xFFFF), Color::new(255, 255, 255));
+        assert_eq!(format.decode(format.encode(Color::new(128, 64, 200))), Color::new(132, 65, 198));
+    }
+
+    #[test]
+    fn free_packers_agree_on_channel_order() {
+        assert_eq!(from_rgb((0x12, 0x34, 0x56)), from_rgba((0x12, 0x34, 0x56, 0)));
+        assert_eq!(from_rgba((0x12, 0x34, 0x56, 0x78)), PixelFormat::Argb8888.encode(Color::new_with_alpha(0x12, 0x34, 0x56, 0x78)));
+        assert_eq!(from_rgb((0x12, 0x34, 0x56)), Color::new(0x12, 0x34, 0x56).to_u32());
+    }
+}
@@ -0,0 +1,123 @@
+use serde::{Serialize, Deserialize};
+
+use crate::cam_data::CamData;
+use crate::draw_functions::Color;
+use crate::vec2d::Vec2d;
+
+/// how the low resolution buffer gets stretched to the window size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ScaleFilter {
+    /// stretches to fill the whole window, each window pixel takes the closest buffer pixel
+    #[default]
+    Nearest,
+    /// only scales by whole numbers so every buffer pixel becomes a square of the same size,
+    /// the leftover space is filled with black and the image is centered
+    Integer,
+    /// stretches to fill the whole window and blends the four closest buffer pixels, smooth but blurry
+    Bilinear,
+}
+
+/// owns a buffer of [CamData::buffsize] to draw into and scales it up to the window. Each frame call
+/// [Presenter::resize], draw into `buffer` with a [Cam](crate::Cam) or [Scene](crate::Scene), then [Presenter::present]
+#[derive(Debug, Clone)]
+pub struct Presenter {
+    pub buffer: Vec2d<u32>,
+    pub filter: ScaleFilter,
+}
+impl Presenter {
+    pub fn new(cam_data: &CamData, filter: ScaleFilter) -> Self {
+        Self {
+            buffer: Vec2d::new_with_fill(cam_data.buffsize(), |_| 0),
+            filter,
+        }
+    }
+    /// makes a new buffer if the buffsize changed (the window got resized or [CamData::change_resolution]
+    /// was called). Returns true if it did, anything sized to the buffer like a depth buffer needs remaking too
+    pub fn resize(&mut self, cam_data: &CamData) -> bool {
+        if self.buffer.len() == cam_data.buffsize() {
+            return false
+        }
+        self.buffer = Vec2d::new_with_fill(cam_data.buffsize(), |_| 0);
+        true
+    }
+    /// scales the buffer up into `window` with `self.filter`
+    pub fn present(&self, window: &mut Vec2d<u32>) {
+        match self.filter {
+            ScaleFilter::Nearest => scale_nearest(&self.buffer, window),
+            ScaleFilter::Integer => scale_integer(&self.buffer, window),
+            ScaleFilter::Bilinear => scale_bilinear(&self.buffer, window),
+        }
+    }
+}
+
+/// index of the `src_len` sized row or column that the center of pixel `i` out of `dst_len` lands in
+fn nearest_index(i: usize, src_len: usize, dst_len: usize) -> usize {
+    (((2 * i + 1) * src_len) / (2 * dst_len)).min(src_len - 1)
+}
+
+/// stretches `src` over all of `dst`
+pub fn scale_nearest<T: Copy>(src: &Vec2d<T>, dst: &mut Vec2d<T>) {
+    let (src_width, src_height) = src.len();
+    let (dst_width, dst_height) = dst.len();
+    if src_width == 0 || src_height == 0 {
+        return
+    }
+    let columns: Vec<usize> = (0..dst_width).map(|x| nearest_index(x, src_width, dst_width)).collect();
+    for y in 0..dst_height {
+        let src_y = nearest_index(y, src_height, dst_height);
+        let src_row = &src.get_flat1()[src_y * src_width..(src_y + 1) * src_width];
+        let dst_row = &mut dst.get_mut_flat1()[y * dst_width..(y + 1) * dst_width];
+        for (p, src_x) in dst_row.iter_mut().zip(&columns) {
+            *p = src_row[*src_x];
+        }
+    }
+}
+
+/// scales `src` by the largest whole number that fits inside of `dst` (at least 1) and copies it to the
+/// middle of `dst`, the rest of `dst` becomes black
+pub fn scale_integer(src: &Vec2d<u32>, dst: &mut Vec2d<u32>) {
+    let (src_width, src_height) = src.len();
+    let (dst_width, dst_height) = dst.len();
+    if src_width == 0 || src_height == 0 {
+        return
+    }
+    let scale = (dst_width / src_width).min(dst_height / src_height).max(1);
+    dst.get_mut_flat1().fill(Color::black().to_u32());
+    dst.copy_to_middle_from_vec2d_cropped(&Vec2d::new_scaled_from(src, (scale, scale)));
+}
+
+/// stretches `src` over all of `dst` blending between pixels. Uses 16 bit fixed point so the result
+/// is the same on every machine
+pub fn scale_bilinear(src: &Vec2d<u32>, dst: &mut Vec2d<u32>) {
+    let (src_width, src_height) = src.len();
+    let (dst_width, dst_height) = dst.len();
+    if src_width == 0 || src_height == 0 {
+        return
+    }
+    // position of the center of a dst pixel in src pixels (shifted left 16), clamped to the src pixel centers.
+    // Returns the index of the pixel before it and how far it is towards the next one out of 65536
+    let sample = |i: usize, src_len: usize, dst_len: usize| -> (usize, u64) {
+        let pos = (((2 * i + 1) * src_len) << 16) / (2 * dst_len);
+        let pos = pos.saturating_sub(1 << 15).min((src_len - 1) << 16);
+        (pos >> 16, (pos & 0xFFFF) as u64)
+    };
+    let channels = |pixel: u32| [(pixel >> 16) & 0xFF, (pixel >> 8) & 0xFF, pixel & 0xFF].map(|c| c as u64);
+    let columns: Vec<(usize, u64)> = (0..dst_width).map(|x| sample(x, src_width, dst_width)).collect();
+    for y in 0..dst_height {
+        let (y0, ty) = sample(y, src_height, dst_height);
+        let y1 = (y0 + 1).min(src_height - 1);
+        for (x, &(x0, tx)) in columns.iter().enumerate() {
+            let x1 = (x0 + 1).min(src_width - 1);
+            let (c00, c10) = (channels(src[(x0, y0)]), channels(src[(x1, y0)]));
+            let (c01, c11) = (channels(src[(x0, y1)]), channels(src[(x1, y1)]));
+            let mut out = 0u32;
+            for i in 0..3 {
+                let top = c00[i] * (65536 - tx) + c10[i] * tx;
+                let bottom = c01[i] * (65536 - tx) + c11[i] * tx;
+                let c = (top * (65536 - ty) + bottom * ty + (1 << 31)) >> 32;
+                out = (out << 8) | c as u32;
+            }
+            dst[(x, y)] = out;
+        }
+    }
+}
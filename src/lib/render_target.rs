@@ -0,0 +1,377 @@
+use crate::blend::{mul_255, BlendMode};
+use crate::coordinates::*;
+use crate::draw_functions::{test_and_set_depth, Color, Projection};
+use crate::path::FillRule;
+use crate::vec2d::Vec2d;
+
+/// a shape a [Cam](crate::Cam) is about to draw, handed to [RenderTarget::draw_vector] before it gets rasterized.
+/// Coords are rel to the draw plane, the [Projection] that comes with it turns them into screen coords
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VectorShape<'a> {
+    /// z is the height above the draw plane
+    Line(D3, D3),
+    Polyline { points: &'a [D2<D1>], closed: bool },
+    /// the inside of outlines that are all closed
+    Fill { contours: &'a [&'a [D2<D1>]], fill_rule: FillRule },
+    Circle { center: D2<D1>, radius: D1, filled: bool },
+    /// a filled flat polygon that doesn't have to lie in the draw plane, z is the height above it
+    Face(&'a [D3]),
+    /// text that is the same size no matter how far away the plane is, see [Cam::draw_text](crate::Cam::draw_text).
+    /// `coords` are the bottom left of the first line or the middle of the text if `center` is set,
+    /// `line_height` is in screen pixels
+    Text { coords: D2<D1>, text: &'a str, line_height: D1, center: bool },
+}
+
+/// anything [Cam](crate::Cam) and the free drawing functions can draw into.
+///
+/// Coords here are raw storage coords like [Vec2d] indexes, (0, 0) is the top left pixel.
+/// The drawing functions work rel to bottom left of screen and flip y before calling these
+pub trait RenderTarget {
+    /// what a single stored pixel looks like, used by [RenderTarget::row_mut]
+    type Pixel: Copy;
+
+    /// (width, height) in pixels
+    fn size(&self) -> (usize, usize);
+    /// returns None if `coords` is out of bounds
+    fn get_pixel(&self, coords: (usize, usize)) -> Option<Color>;
+    /// overwrites the pixel, does nothing if `coords` is out of bounds
+    fn put_pixel(&mut self, coords: (usize, usize), color: Color);
+
+    /// combines `color` with the pixel using `blend_mode`
+    fn blend_pixel(&mut self, coords: (usize, usize), color: Color, blend_mode: BlendMode) {
+        match blend_mode {
+            BlendMode::Replace => self.put_pixel(coords, color),
+            BlendMode::Normal if color.alpha == 255 => self.put_pixel(coords, color),
+            _ => {
+                if let Some(dst) = self.get_pixel(coords) {
+                    self.put_pixel(coords, blend_mode.blend(color, dst));
+                }
+            }
+        }
+    }
+    /// lets targets that store shapes instead of pixels, like [SvgTarget](crate::SvgTarget), take the shapes a cam draws.
+    /// `color` already has the alpha of the cam in it. Returns true if the shape was taken, then the cam doesn't rasterize it
+    fn draw_vector(&mut self, _shape: &VectorShape, _color: Color, _projection: &Projection) -> bool {
+        false
+    }
+    /// direct access to one row of pixels, for targets that store their rows contiguously
+    fn row_mut(&mut self, _y: usize) -> Option<&mut [Self::Pixel]> {
+        None
+    }
+    /// overwrites every pixel
+    fn clear(&mut self, color: Color) {
+        let (width, height) = self.size();
+        for y in 0..height {
+            for x in 0..width {
+                self.put_pixel((x, y), color);
+            }
+        }
+    }
+}
+
+/// pixels packed as 0RGB, what minifb expects. See [PixelBuffer](crate::PixelBuffer) for other formats
+impl RenderTarget for Vec2d<u32> {
+    type Pixel = u32;
+
+    fn size(&self) -> (usize, usize) {
+        self.len()
+    }
+    fn get_pixel(&self, coords: (usize, usize)) -> Option<Color> {
+        if coords.0 >= self.len().0 {
+            return None
+        }
+        self.get(coords).map(|p| Color::new_from_u32(*p))
+    }
+    fn put_pixel(&mut self, coords: (usize, usize), color: Color) {
+        if let Some(p) = self.get_mut(coords) {
+            *p = color.to_u32();
+        }
+    }
+    fn row_mut(&mut self, y: usize) -> Option<&mut [u32]> {
+        let width = self.len().0;
+        if y >= self.len().1 {
+            return None
+        }
+        Some(&mut self.get_mut_flat1()[y * width..(y + 1) * width])
+    }
+    fn clear(&mut self, color: Color) {
+        self.get_mut_flat1().fill(color.to_u32());
+    }
+}
+
+impl RenderTarget for Vec2d<Color> {
+    type Pixel = Color;
+
+    fn size(&self) -> (usize, usize) {
+        self.len()
+    }
+    fn get_pixel(&self, coords: (usize, usize)) -> Option<Color> {
+        if coords.0 >= self.len().0 {
+            return None
+        }
+        self.get(coords).copied()
+    }
+    fn put_pixel(&mut self, coords: (usize, usize), color: Color) {
+        if let Some(p) = self.get_mut(coords) {
+            *p = color;
+        }
+    }
+    fn row_mut(&mut self, y: usize) -> Option<&mut [Color]> {
+        let width = self.len().0;
+        if y >= self.len().1 {
+            return None
+        }
+        Some(&mut self.get_mut_flat1()[y * width..(y + 1) * width])
+    }
+    fn clear(&mut self, color: Color) {
+        self.get_mut_flat1().fill(color);
+    }
+}
+
+/// a rectangle inside of another target, drawing to it never touches pixels outside of the rectangle
+#[derive(Debug)]
+pub struct SubTarget<'a, T: RenderTarget + ?Sized> {
+    pub target: &'a mut T,
+    offset: (usize, usize),
+    size: (usize, usize),
+}
+impl<'a, T: RenderTarget + ?Sized> SubTarget<'a, T> {
+    /// `offset` is the top left corner of the rectangle inside of `target`.
+    /// The rectangle gets cropped if it doesn't fit inside of `target`
+    pub fn new(target: &'a mut T, offset: (usize, usize), size: (usize, usize)) -> Self {
+        let parent = target.size();
+        let offset = (offset.0.min(parent.0), offset.1.min(parent.1));
+        let size = (size.0.min(parent.0 - offset.0), size.1.min(parent.1 - offset.1));
+        Self { target, offset, size }
+    }
+    pub fn offset(&self) -> (usize, usize) {
+        self.offset
+    }
+    fn to_parent(&self, coords: (usize, usize)) -> Option<(usize, usize)> {
+        if coords.0 >= self.size.0 || coords.1 >= self.size.1 {
+            return None
+        }
+        Some((coords.0 + self.offset.0, coords.1 + self.offset.1))
+    }
+}
+impl<T: RenderTarget + ?Sized> RenderTarget for SubTarget<'_, T> {
+    type Pixel = T::Pixel;
+
+    fn size(&self) -> (usize, usize) {
+        self.size
+    }
+    fn get_pixel(&self, coords: (usize, usize)) -> Option<Color> {
+        self.target.get_pixel(self.to_parent(coords)?)
+    }
+    fn put_pixel(&mut self, coords: (usize, usize), color: Color) {
+        if let Some(coords) = self.to_parent(coords) {
+            self.target.put_pixel(coords, color);
+        }
+    }
+    fn blend_pixel(&mut self, coords: (usize, usize), color: Color, blend_mode: BlendMode) {
+        if let Some(coords) = self.to_parent(coords) {
+            self.target.blend_pixel(coords, color, blend_mode);
+        }
+    }
+    fn row_mut(&mut self, y: usize) -> Option<&mut [T::Pixel]> {
+        if y >= self.size.1 {
+            return None
+        }
+        let (x0, x1) = (self.offset.0, self.offset.0 + self.size.0);
+        let row = self.target.row_mut(y + self.offset.1)?;
+        Some(&mut row[x0..x1])
+    }
+}
+
+/// doesn't store an image, just remembers every write in order. Useful for checking what got drawn
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordingTarget {
+    size: (usize, usize),
+    /// every pixel write in the order it happened, blended writes are recorded with the color that was blended
+    pub writes: Vec<((usize, usize), Color)>,
+}
+impl RecordingTarget {
+    pub fn new(size: (usize, usize)) -> Self {
+        Self { size, writes: Vec::new() }
+    }
+}
+impl RenderTarget for RecordingTarget {
+    type Pixel = Color;
+
+    fn size(&self) -> (usize, usize) {
+        self.size
+    }
+    /// the last color written to `coords`
+    fn get_pixel(&self, coords: (usize, usize)) -> Option<Color> {
+        self.writes.iter().rev().find(|(pt, _)| *pt == coords).map(|(_, color)| *color)
+    }
+    fn put_pixel(&mut self, coords: (usize, usize), color: Color) {
+        if coords.0 < self.size.0 && coords.1 < self.size.1 {
+            self.writes.push((coords, color));
+        }
+    }
+    fn blend_pixel(&mut self, coords: (usize, usize), color: Color, _blend_mode: BlendMode) {
+        self.put_pixel(coords, color);
+    }
+}
+
+/// wraps a target so that everything blended into it uses `blend_mode` instead of whatever the caller asked for.
+/// This is how the free drawing functions, which always blend with [BlendMode::Normal], get other blend modes
+#[derive(Debug)]
+pub struct Blended<'a, T: RenderTarget + ?Sized> {
+    pub target: &'a mut T,
+    pub blend_mode: BlendMode,
+    /// multiplied into the alpha of every blended color, 255 leaves colors unchanged
+    pub opacity: u8,
+}
+impl<'a, T: RenderTarget + ?Sized> Blended<'a, T> {
+    pub fn new(target: &'a mut T, blend_mode: BlendMode) -> Self {
+        Self { target, blend_mode, opacity: 255 }
+    }
+    pub fn with_opacity(mut self, opacity: u8) -> Self {
+        self.opacity = opacity;
+        self
+    }
+}
+impl<T: RenderTarget + ?Sized> RenderTarget for Blended<'_, T> {
+    type Pixel = T::Pixel;
+
+    fn size(&self) -> (usize, usize) {
+        self.target.size()
+    }
+    fn get_pixel(&self, coords: (usize, usize)) -> Option<Color> {
+        self.target.get_pixel(coords)
+    }
+    fn put_pixel(&mut self, coords: (usize, usize), color: Color) {
+        self.target.put_pixel(coords, color);
+    }
+    fn blend_pixel(&mut self, coords: (usize, usize), color: Color, _blend_mode: BlendMode) {
+        let alpha = mul_255(color.alpha as u32, self.opacity as u32) as u8;
+        self.target.blend_pixel(coords, Color::new_with_alpha(color.r, color.g, color.b, alpha), self.blend_mode);
+    }
+    fn draw_vector(&mut self, shape: &VectorShape, color: Color, projection: &Projection) -> bool {
+        let alpha = mul_255(color.alpha as u32, self.opacity as u32) as u8;
+        self.target.draw_vector(shape, Color::new_with_alpha(color.r, color.g, color.b, alpha), projection)
+    }
+    fn row_mut(&mut self, y: usize) -> Option<&mut [T::Pixel]> {
+        self.target.row_mut(y)
+    }
+    fn clear(&mut self, color: Color) {
+        self.target.clear(color);
+    }
+}
+
+/// wraps a target so that pixels only get drawn where they are at least as high as what the depth buffer has,
+/// see [test_and_set_depth]. This is how the free drawing functions a [Cam](crate::Cam) calls respect its depth buffer
+#[derive(Debug)]
+pub(crate) struct DepthTested<'a, T: RenderTarget + ?Sized> {
+    pub target: &'a mut T,
+    pub depth: &'a mut Vec2d<D1>,
+    /// height of everything drawn through this
+    pub z: D1,
+}
+impl<T: RenderTarget + ?Sized> DepthTested<'_, T> {
+    fn test(&mut self, coords: (usize, usize)) -> bool {
+        // the depth buffer is rel to bottom left of screen
+        let y_up = self.target.size().1.checked_sub(1).and_then(|y| y.checked_sub(coords.1));
+        match (i32::try_from(coords.0), y_up.and_then(|y| i32::try_from(y).ok())) {
+            (Ok(x), Some(y)) => test_and_set_depth(self.depth, (x, y), self.z),
+            _ => false,
+        }
+    }
+}
+impl<T: RenderTarget + ?Sized> RenderTarget for DepthTested<'_, T> {
+    type Pixel = T::Pixel;
+
+    fn size(&self) -> (usize, usize) {
+        self.target.size()
+    }
+    fn get_pixel(&self, coords: (usize, usize)) -> Option<Color> {
+        self.target.get_pixel(coords)
+    }
+    fn put_pixel(&mut self, coords: (usize, usize), color: Color) {
+        if self.test(coords) {
+            self.target.put_pixel(coords, color);
+        }
+    }
+    fn blend_pixel(&mut self, coords: (usize, usize), color: Color, blend_mode: BlendMode) {
+        if self.test(coords) {
+            self.target.blend_pixel(coords, color, blend_mode);
+        }
+    }
+    fn clear(&mut self, color: Color) {
+        self.target.clear(color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cam_data::CamData;
+    use crate::draw_functions::Cam;
+
+    /// can't be read back, so anything that isn't a plain put_pixel gets lost
+    struct WriteOnly(Vec<Color>);
+    impl RenderTarget for WriteOnly {
+        type Pixel = Color;
+
+        fn size(&self) -> (usize, usize) {
+            (1, 1)
+        }
+        fn get_pixel(&self, _coords: (usize, usize)) -> Option<Color> {
+            None
+        }
+        fn put_pixel(&mut self, _coords: (usize, usize), color: Color) {
+            self.0.push(color);
+        }
+    }
+
+    #[test]
+    fn sub_targets_offset_and_clip() {
+        let mut img = Vec2d::new_with_fill((8, 8), |_| Color::black());
+        let mut sub = SubTarget::new(&mut img, (2, 3), (4, 4));
+        sub.put_pixel((0, 0), Color::white());
+        sub.put_pixel((3, 3), Color::white());
+        sub.put_pixel((4, 0), Color::white());
+        sub.put_pixel((0, 4), Color::white());
+        assert_eq!(sub.get_pixel((0, 0)), Some(Color::white()));
+        assert_eq!(sub.get_pixel((4, 0)), None);
+        assert_eq!(sub.row_mut(1).map(|row| row.len()), Some(4));
+        assert_eq!(sub.row_mut(4), None);
+
+        let white: Vec<_> = (0..8).flat_map(|y| (0..8).map(move |x| (x, y))).filter(|pt| img.get(*pt) == Some(&Color::white())).collect();
+        assert_eq!(white, [(2, 3), (5, 6)]);
+
+        // cropped to what is left of the parent
+        let sub = SubTarget::new(&mut img, (6, 7), (4, 4));
+        assert_eq!((sub.offset(), sub.size()), ((6, 7), (2, 1)));
+        let sub = SubTarget::new(&mut img, (10, 10), (4, 4));
+        assert_eq!(sub.size(), (0, 0));
+    }
+
+    #[test]
+    fn recording_targets_record_what_a_cam_draws() {
+        let cam_data = CamData::new((8, 8), 1);
+        let mut recording = RecordingTarget::new((8, 8));
+        let pos = Pos::new(D3::from((D1::zero(), D1::zero(), D1::from(-10))), Angle::zero());
+        let mut cam = Cam::new_rel_to_pos(&mut recording, &cam_data, pos, 128).unwrap();
+        let z = cam.zero_pos.z();
+        cam.plot((1, 2), z, Color::white());
+        cam.plot((3, 0), z, Color::new(255, 0, 0));
+        cam.plot((8, 0), z, Color::white());
+        // y is flipped and the alpha of the cam is in every color
+        assert_eq!(recording.writes, [((1, 5), Color::new_with_alpha(255, 255, 255, 128)), ((3, 7), Color::new_with_alpha(255, 0, 0, 128))]);
+        assert_eq!(recording.get_pixel((1, 5)), Some(Color::new_with_alpha(255, 255, 255, 128)));
+        assert_eq!(recording.get_pixel((0, 0)), None);
+    }
+
+    #[test]
+    fn blending_only_reads_the_target_when_it_has_to() {
+        let translucent = Color::new_with_alpha(255, 0, 0, 100);
+        let mut target = WriteOnly(Vec::new());
+        target.blend_pixel((0, 0), translucent, BlendMode::Replace);
+        target.blend_pixel((0, 0), Color::white(), BlendMode::Normal);
+        target.blend_pixel((0, 0), translucent, BlendMode::Normal);
+        assert_eq!(target.0, [translucent, Color::white()]);
+    }
+}
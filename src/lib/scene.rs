@@ -0,0 +1,238 @@
+use crate::cam_data::*;
+use crate::coordinates::*;
+use crate::blend::BlendMode;
+use crate::draw_functions::*;
+use crate::font::Font;
+use crate::layer::Layer;
+use crate::path::{FillRule, Path};
+use crate::render_target::RenderTarget;
+use crate::shape::*;
+use crate::text_layout::TextAlign;
+
+/// something that can be drawn on a draw plane, all coords are rel to the plane.
+/// Serialized in the scene file format, see [SceneDocument](crate::SceneDocument)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Primitive {
+    Shape(Shape2D<D1>, Color),
+    FilledShape(Shape2D<D1>, Color),
+    Line((D2<D1>, D2<D1>), Color),
+    /// outline connecting the points in order, the last point connects back to the first
+    Polygon(Vec<D2<D1>>, Color),
+    FilledPolygon(Vec<D2<D1>>, Color),
+    /// same arguments as [Cam::draw_text]
+    Text {
+        coords: D2<D1>,
+        text: String,
+        color: Color,
+        dimensions: D2<u16>,
+        center: bool,
+        background: bool,
+    },
+    /// same arguments as [Cam::draw_world_text], always uses [Font::builtin]
+    WorldText {
+        coords: D2<D1>,
+        text: String,
+        color: Color,
+        height: D1,
+        angle: Angle,
+        center: bool,
+    },
+    /// same arguments as [Cam::draw_stroke_text]
+    StrokeText {
+        anchor: D2<D1>,
+        text: String,
+        height: D1,
+        angle: Angle,
+        alignment: TextAlign,
+        color: Color,
+    },
+    // new variants go at the end so binary scene files keep their variant indices, the same goes for PrimitiveWire
+    Path(Path, Color),
+    FilledPath(Path, FillRule, Color),
+    /// z is the height above the plane, see [Cam::draw_shape3d]
+    Shape3D(Shape3D, Shape3DStyle),
+}
+
+/// a list of primitives that all get drawn relative to the same position
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawPlane {
+    pub pos: Pos, // position of the plane relative to world origin
+    pub alpha: u8,
+    /// if set and `alpha` isn't 255, the plane is drawn into an offscreen [Layer] first so that
+    /// overlapping primitives don't fade each other
+    pub isolated: bool,
+    pub primitives: Vec<Primitive>,
+}
+impl DrawPlane {
+    pub fn new(pos: Pos) -> Self {
+        Self {
+            pos,
+            alpha: 255,
+            isolated: false,
+            primitives: Vec::new(),
+        }
+    }
+    pub fn push(&mut self, primitive: Primitive) {
+        self.primitives.push(primitive);
+    }
+    /// draws every primitive of the plane in the order they were pushed
+    pub fn draw<T: RenderTarget + ?Sized>(&self, cam: &mut Cam<T>) {
+        for primitive in &self.primitives {
+            match primitive {
+                Primitive::Shape(shape, color) => cam.draw_shape(*shape, *color),
+                Primitive::FilledShape(shape, color) => cam.fill_shape(*shape, *color),
+                Primitive::Line(line, color) => cam.draw_line(*line, *color),
+                Primitive::Polygon(points, color) => cam.draw_polyline(points, true, *color),
+                Primitive::FilledPolygon(points, color) => cam.fill_polygon(points, *color),
+                Primitive::Path(path, color) => cam.stroke_path(path, *color),
+                Primitive::FilledPath(path, fill_rule, color) => cam.fill_path(path, *fill_rule, *color),
+                Primitive::Shape3D(shape, style) => cam.draw_shape3d(*shape, *style),
+                Primitive::Text { coords, text, color, dimensions, center, background } => {
+                    cam.draw_text(*coords, text, Font::builtin(), *color, *dimensions, *center, *background)
+                }
+                Primitive::WorldText { coords, text, color, height, angle, center } => {
+                    cam.draw_world_text(*coords, text, Font::builtin(), *color, *height, *angle, *center)
+                }
+                Primitive::StrokeText { anchor, text, height, angle, alignment, color } => {
+                    cam.draw_stroke_text(*anchor, text, *height, *angle, *alignment, *color)
+                }
+            }
+        }
+    }
+}
+
+/// retained list of draw planes. Instead of making a [Cam] for every plane by hand each frame,
+/// fill a scene once and call [Scene::render]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Scene {
+    pub planes: Vec<DrawPlane>,
+}
+impl Scene {
+    pub fn new() -> Self {
+        Self { planes: Vec::new() }
+    }
+    /// returns the index of the plane so more primitives can be added to it later
+    pub fn push_plane(&mut self, plane: DrawPlane) -> usize {
+        self.planes.push(plane);
+        self.planes.len() - 1
+    }
+
+    /// draws every plane back to front (painter's algorithm), so higher planes end up on top of lower ones.
+    /// Planes that [Cam::new_rel_to_pos] rejects (at or above the camera) are skipped
+    pub fn render<T: RenderTarget + ?Sized>(&self, cam_data: &CamData, img: &mut T) {
+        let mut order: Vec<(D1, &DrawPlane)> = self.planes.iter()
+            .map(|plane| (cam_data.zero_pos(plane.pos).z(), plane))
+            .collect();
+        // stable so planes at the same height keep the order they were pushed in
+        order.sort_by_key(|(z, _)| *z);
+
+        // shared by every isolated plane, only allocated if there is one
+        let mut layer: Option<Layer> = None;
+        for (_, plane) in order {
+            if plane.isolated && plane.alpha != 255 {
+                let layer = layer.get_or_insert_with(|| Layer::new(img.size()));
+                layer.clear(Color::new_transparent());
+                if let Some(mut cam) = Cam::new_rel_to_pos(layer, cam_data, plane.pos, 255) {
+                    plane.draw(&mut cam);
+                    layer.composite(img, plane.alpha, BlendMode::Normal);
+                }
+            } else if let Some(mut cam) = Cam::new_rel_to_pos(img, cam_data, plane.pos, plane.alpha) {
+                plane.draw(&mut cam);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render_target::RecordingTarget;
+    use crate::vec2d::Vec2d;
+
+    /// a plane at height `z` with a square that covers the whole screen
+    fn covering_plane(z: D1, color: Color) -> DrawPlane {
+        let mut plane = DrawPlane::new(Pos::new(D3::from((D1::zero(), D1::zero(), z)), Angle::zero()));
+        let corners = [(-100, -100), (100, -100), (100, 100), (-100, 100)];
+        plane.push(Primitive::FilledPolygon(corners.iter().map(|&(x, y)| D2::from((D1::from(x), D1::from(y)))).collect(), color));
+        plane
+    }
+
+    #[test]
+    fn higher_planes_win_no_matter_the_order() {
+        let cam_data = CamData::new((16, 16), 1);
+        let mut scene = Scene::new();
+        scene.push_plane(covering_plane(D1::from(-10), Color::new(0, 0, 255)));
+        scene.push_plane(covering_plane(D1::from(-20), Color::new(255, 0, 0)));
+        let mut img = Vec2d::new_with_fill((16, 16), |_| 0);
+        scene.render(&cam_data, &mut img);
+        assert_eq!(img.get((8, 8)), Some(&Color::new(0, 0, 255).to_u32()));
+    }
+
+    #[test]
+    fn planes_above_the_camera_are_skipped() {
+        let cam_data = CamData::new((16, 16), 1);
+        let focal_len = cam_data.focal_len();
+        let mut scene = Scene::new();
+        scene.push_plane(covering_plane(focal_len + D1::from(5), Color::new(0, 0, 255)));
+        scene.push_plane(covering_plane(focal_len, Color::new(0, 255, 0)));
+        scene.push_plane(covering_plane(D1::from(-10), Color::new(255, 0, 0)));
+        let mut img = Vec2d::new_with_fill((16, 16), |_| 0);
+        scene.render(&cam_data, &mut img);
+        assert!(img.get_flat1().iter().all(|p| *p == Color::new(255, 0, 0).to_u32()));
+    }
+
+    fn d2(x: i32, y: i32) -> D2<D1> {
+        D2::from((D1::from(x), D1::from(y)))
+    }
+
+    #[test]
+    fn plane_alpha_fades_every_primitive() {
+        let cam_data = CamData::new((64, 64), 1);
+        let mut plane = covering_plane(D1::from(-10), Color::white());
+        plane.alpha = 128;
+        let white = Color::white();
+        let square = vec![d2(-5, -5), d2(5, -5), d2(5, 5), d2(-5, 5)];
+        plane.primitives.extend([
+            Primitive::Shape(Shape2D::Circle((D2::zero(), D1::from(5))), white),
+            Primitive::FilledShape(Shape2D::Rect((d2(-3, -3), d2(3, 3))), white),
+            Primitive::Line((d2(-10, 0), d2(10, 3)), white),
+            Primitive::Polygon(square.clone(), white),
+            Primitive::Text { coords: D2::zero(), text: "ab".to_string(), color: white, dimensions: D2::from((1, 1)), center: true, background: true },
+            Primitive::WorldText { coords: D2::zero(), text: "ab".to_string(), color: white, height: D1::from(4), angle: Angle::zero(), center: true },
+            Primitive::StrokeText { anchor: D2::zero(), text: "ab".to_string(), height: D1::from(4), angle: Angle::zero(), alignment: TextAlign::Center, color: white },
+            Primitive::Path(Path::polygon(&square), white),
+            Primitive::FilledPath(Path::polygon(&square), FillRule::NonZero, white),
+            Primitive::Shape3D(Shape3D::RectPrism((D3::zero(), D3::from((D1::from(2), D1::from(2), D1::from(2))))), Shape3DStyle::Wireframe(white)),
+        ]);
+        for primitive in plane.primitives.clone() {
+            let mut single = plane.clone();
+            single.primitives = vec![primitive.clone()];
+            let mut scene = Scene::new();
+            scene.push_plane(single);
+            let mut recording = RecordingTarget::new((64, 64));
+            scene.render(&cam_data, &mut recording);
+            assert!(!recording.writes.is_empty(), "{:?}", primitive);
+            assert!(recording.writes.iter().all(|(_, color)| color.alpha == 128), "{:?}", primitive);
+        }
+    }
+
+    #[test]
+    fn isolated_planes_fade_as_a_whole() {
+        let cam_data = CamData::new((16, 16), 1);
+        let render = |isolated: bool| {
+            let mut plane = covering_plane(D1::from(-10), Color::white());
+            plane.push(Primitive::FilledShape(Shape2D::Rect((d2(-100, -100), d2(100, 100))), Color::white()));
+            plane.alpha = 128;
+            plane.isolated = isolated;
+            let mut scene = Scene::new();
+            scene.push_plane(plane);
+            let mut img = Vec2d::new_with_fill((16, 16), |_| Color::black());
+            scene.render(&cam_data, &mut img);
+            img[(8, 8)]
+        };
+        let once = BlendMode::Normal.blend(Color::new_with_alpha(255, 255, 255, 128), Color::black());
+        let twice = BlendMode::Normal.blend(Color::new_with_alpha(255, 255, 255, 128), once);
+        assert_eq!(render(true), once);
+        assert_eq!(render(false), twice);
+    }
+}
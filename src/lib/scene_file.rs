@@ -0,0 +1,791 @@
+use std::{error, fmt, fs, io, path};
+
+use fixed::types::{I32F32, I36F28};
+use serde::{de, Deserializer, Serialize, Deserialize, Serializer};
+
+use crate::cam_data::*;
+use crate::coordinates::*;
+use crate::draw_functions::{Color, Shape3DStyle};
+use crate::path::{FillRule, Path, PathCommand};
+use crate::presenter::{Presenter, ScaleFilter};
+use crate::scene::{DrawPlane, Primitive, Scene};
+use crate::shape::{Shape2D, Shape3D};
+use crate::svg::SvgTarget;
+use crate::text_layout::TextAlign;
+use crate::vec2d::Vec2d;
+
+/// the version written into every scene file. Bump it whenever the layout of [SceneDocument]
+/// (or anything inside of it) changes so old files get a clear error instead of garbage
+pub const SCENE_FILE_VERSION: u32 = 1;
+
+/// binary scene files start with this followed by the version as a little endian u32
+const BINARY_MAGIC: [u8; 4] = *b"PDSC";
+
+/// biggest width or height a scene file can ask for
+pub const MAX_SCENE_WINSIZE: usize = 1 << 14;
+
+/// the camera settings of a scene file, only what someone would want to write by hand.
+/// Loading a document checks them with [SceneCamera::validate]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SceneCamera {
+    /// output size in pixels
+    pub winsize: (usize, usize),
+    /// render at `winsize / buffscale` and scale up, rounded up to a power of 2
+    pub buffscale: usize,
+    /// zoom is `2^zoom_exponent` times whatever `buffscale` already zooms out by
+    pub zoom_exponent: i8,
+    /// position of the camera relative to world origin
+    pub pos: Pos,
+    /// [CamData::near_dist], the default is used if None
+    pub near_dist: Option<D1>,
+    pub filter: ScaleFilter,
+}
+impl SceneCamera {
+    pub fn new(winsize: (usize, usize)) -> Self {
+        Self {
+            winsize,
+            buffscale: 1,
+            zoom_exponent: 0,
+            pos: Pos::zero(),
+            near_dist: None,
+            filter: ScaleFilter::Nearest,
+        }
+    }
+    /// `cam_data.current_surf_abs_pos` is not kept, the camera ends up relative to world origin
+    pub fn from_cam_data(cam_data: &CamData) -> Self {
+        Self {
+            winsize: cam_data.winsize(),
+            buffscale: cam_data.buffscale(),
+            // `CamData::change_resolution` changes the zoom exponent along with buffscale
+            zoom_exponent: cam_data.zoom_exponent - cam_data.buffscale().trailing_zeros() as i8,
+            pos: cam_data.cam_pos_rel,
+            near_dist: Some(cam_data.near_dist()),
+            filter: ScaleFilter::Nearest,
+        }
+    }
+    /// makes sure the camera can render, `zoom_exponent` has to be in
+    /// [SMALLEST_ZOOM_EXPONENT]`..=`[LARGEST_ZOOM_EXPONENT] and `near_dist` in [MIN_NEAR_DIST]`..=`[CamData::view_dist]
+    pub fn validate(&self) -> Result<(), SceneFileError> {
+        let bad = |message: String| Err(SceneFileError::BadCamera(message));
+        let (width, height) = self.winsize;
+        if width == 0 || height == 0 || width > MAX_SCENE_WINSIZE || height > MAX_SCENE_WINSIZE {
+            return bad(format!("winsize {:?} has to be between 1 and {} each way", self.winsize, MAX_SCENE_WINSIZE))
+        }
+        if self.buffscale == 0 {
+            return bad("buffscale has to be at least 1".to_owned())
+        }
+        if !(SMALLEST_ZOOM_EXPONENT..=LARGEST_ZOOM_EXPONENT).contains(&self.zoom_exponent) {
+            return bad(format!(
+                "zoom_exponent {} has to be between {} and {}", self.zoom_exponent, SMALLEST_ZOOM_EXPONENT, LARGEST_ZOOM_EXPONENT
+            ))
+        }
+        if let Some(near_dist) = self.near_dist {
+            let view_dist = CamData::new((1, 1), 1).view_dist();
+            if near_dist < MIN_NEAR_DIST || near_dist > view_dist {
+                return bad(format!("near_dist {} has to be between {} and {}", near_dist.val, MIN_NEAR_DIST.val, view_dist.val))
+            }
+        }
+        Ok(())
+    }
+    /// a `zoom_exponent` out of range is clamped, see [SceneCamera::validate]
+    pub fn to_cam_data(&self) -> CamData {
+        let mut cam_data = CamData::new(self.winsize, 1);
+        while cam_data.buffscale() < self.buffscale.min(MAX_CAMERA_SCALE) {
+            cam_data.change_resolution(true, self.winsize);
+        }
+        cam_data.zoom_exponent += self.zoom_exponent.clamp(SMALLEST_ZOOM_EXPONENT, LARGEST_ZOOM_EXPONENT);
+        cam_data.set_zoom(cam_data.target_zoom());
+        cam_data.cam_pos_rel = self.pos;
+        if let Some(near_dist) = self.near_dist {
+            cam_data.set_near_dist(near_dist);
+        }
+        cam_data
+    }
+}
+
+/// everything needed to render a scene without any Rust code, see [SceneDocument::load] and [SceneDocument::save].
+///
+/// [Scene], [DrawPlane], [Primitive] and [SceneCamera] are always serialized in the scene file format, where numbers
+/// are plain numbers in text formats so they can be edited by hand. Everything else keeps its derived format
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneDocument {
+    /// always [SCENE_FILE_VERSION] for documents that loaded successfully
+    pub version: u32,
+    pub camera: SceneCamera,
+    #[serde(default = "Color::black")]
+    pub background: Color,
+    pub scene: Scene,
+}
+
+/// how a [SceneDocument] is stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SceneEncoding {
+    /// [RON](https://github.com/ron-rs/ron), meant to be written and read by people
+    Text,
+    /// bincode, smaller and faster to load
+    Binary,
+}
+
+#[derive(Debug)]
+pub enum SceneFileError {
+    Io(io::Error),
+    Text(ron::error::SpannedError),
+    Binary(bincode::Error),
+    /// the file was written by a different version of the format
+    UnsupportedVersion(u32),
+    /// the file parsed but its camera can't render, see [SceneCamera::validate]
+    BadCamera(String),
+}
+impl fmt::Display for SceneFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneFileError::Io(e) => write!(f, "{}", e),
+            SceneFileError::Text(e) => write!(f, "bad scene file: {}", e),
+            SceneFileError::Binary(e) => write!(f, "bad binary scene file: {}", e),
+            SceneFileError::UnsupportedVersion(version) => write!(
+                f, "scene file is version {} but only version {} is supported", version, SCENE_FILE_VERSION
+            ),
+            SceneFileError::BadCamera(message) => write!(f, "bad scene camera: {}", message),
+        }
+    }
+}
+impl error::Error for SceneFileError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            SceneFileError::Io(e) => Some(e),
+            SceneFileError::Text(e) => Some(e),
+            SceneFileError::Binary(e) => Some(e),
+            SceneFileError::UnsupportedVersion(_) | SceneFileError::BadCamera(_) => None,
+        }
+    }
+}
+impl From<io::Error> for SceneFileError {
+    fn from(e: io::Error) -> Self {
+        SceneFileError::Io(e)
+    }
+}
+
+/// just enough of a document to read the version of any version
+#[derive(Deserialize)]
+struct VersionProbe {
+    version: u32,
+}
+
+impl SceneDocument {
+    pub fn new(camera: SceneCamera, scene: Scene) -> Self {
+        Self {
+            version: SCENE_FILE_VERSION,
+            camera,
+            background: Color::black(),
+            scene,
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let config = ron::ser::PrettyConfig::new().indentor("    ".to_owned()).depth_limit(4);
+        // nothing in a document can fail to serialize, there are no maps with non string keys
+        ron::ser::to_string_pretty(self, config).expect("scene documents always serialize")
+    }
+    pub fn from_text(text: &str) -> Result<Self, SceneFileError> {
+        match ron::from_str::<SceneDocument>(text) {
+            Ok(document) if document.version == SCENE_FILE_VERSION => {
+                document.camera.validate()?;
+                Ok(document)
+            }
+            Ok(document) => Err(SceneFileError::UnsupportedVersion(document.version)),
+            // a different version probably failed because the layout changed, say that instead of
+            // pointing at whatever field didn't match
+            Err(e) => match ron::from_str::<VersionProbe>(text) {
+                Ok(probe) if probe.version != SCENE_FILE_VERSION => Err(SceneFileError::UnsupportedVersion(probe.version)),
+                _ => Err(SceneFileError::Text(e)),
+            },
+        }
+    }
+
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut bytes = BINARY_MAGIC.to_vec();
+        bytes.extend_from_slice(&SCENE_FILE_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut bytes, self).expect("scene documents always serialize");
+        bytes
+    }
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, SceneFileError> {
+        if !bytes.starts_with(&BINARY_MAGIC) || bytes.len() < 8 {
+            return Err(SceneFileError::Binary(Box::new(bincode::ErrorKind::Custom("not a binary scene file".to_owned()))))
+        }
+        let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        if version != SCENE_FILE_VERSION {
+            return Err(SceneFileError::UnsupportedVersion(version))
+        }
+        let document: SceneDocument = bincode::deserialize(&bytes[8..]).map_err(SceneFileError::Binary)?;
+        document.camera.validate()?;
+        Ok(document)
+    }
+
+    pub fn save<P: AsRef<path::Path>>(&self, path: P, encoding: SceneEncoding) -> Result<(), SceneFileError> {
+        let bytes = match encoding {
+            SceneEncoding::Text => self.to_text().into_bytes(),
+            SceneEncoding::Binary => self.to_binary(),
+        };
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+    /// reads either encoding, binary files are told apart by their first few bytes
+    pub fn load<P: AsRef<path::Path>>(path: P) -> Result<Self, SceneFileError> {
+        let bytes = fs::read(path)?;
+        if bytes.starts_with(&BINARY_MAGIC) {
+            return Self::from_binary(&bytes)
+        }
+        let text = String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Self::from_text(&text)
+    }
+
+    /// draws the scene at `self.camera.winsize`
+    pub fn render(&self) -> Vec2d<u32> {
+        let cam_data = self.camera.to_cam_data();
+        let mut presenter = Presenter::new(&cam_data, self.camera.filter);
+        presenter.buffer.get_mut_flat1().fill(self.background.to_u32());
+        self.scene.render(&cam_data, &mut presenter.buffer);
+        let mut img = Vec2d::new_with_fill(self.camera.winsize, |_| 0);
+        presenter.present(&mut img);
+        img
+    }
+    /// draws the scene as an svg document the size of the buffer the camera would draw into, see [SvgTarget]
+    pub fn render_svg(&self) -> SvgTarget {
+        let cam_data = self.camera.to_cam_data();
+        let mut svg = SvgTarget::new(cam_data.buffsize()).with_background(self.background);
+        self.scene.render(&cam_data, &mut svg);
+        svg
+    }
+}
+
+// the scene file format. Mirrors of the scene types with every D1 and Angle swapped for a number that is written
+// plainly in human readable formats. Binary formats get the raw bits, the same bytes the derived impls write
+
+/// a [D1] in a scene file. Numbers that don't fit in an f64 exactly are written as strings, so nothing is ever rounded
+#[derive(Debug, Clone, Copy)]
+struct Number(D1);
+/// an [Angle] in a scene file, in radians
+#[derive(Debug, Clone, Copy)]
+struct AngleNumber(Angle);
+
+macro_rules! serde_fixed_number {
+    ($T:ident, $Inner:ident, $Fixed:ty) => {
+        impl Serialize for $T {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let val = self.0.val;
+                if !serializer.is_human_readable() {
+                    return val.to_bits().serialize(serializer)
+                }
+                let float: f64 = val.to_num();
+                if <$Fixed>::checked_from_num(float) == Some(val) {
+                    serializer.serialize_f64(float)
+                } else {
+                    serializer.serialize_str(&val.to_string())
+                }
+            }
+        }
+        impl<'de> Deserialize<'de> for $T {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct Visitor;
+                impl de::Visitor<'_> for Visitor {
+                    type Value = $Fixed;
+
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        f.write_str("a number")
+                    }
+                    fn visit_i64<E: de::Error>(self, v: i64) -> Result<$Fixed, E> {
+                        <$Fixed>::checked_from_num(v).ok_or_else(|| E::custom("number out of range"))
+                    }
+                    fn visit_u64<E: de::Error>(self, v: u64) -> Result<$Fixed, E> {
+                        <$Fixed>::checked_from_num(v).ok_or_else(|| E::custom("number out of range"))
+                    }
+                    fn visit_f64<E: de::Error>(self, v: f64) -> Result<$Fixed, E> {
+                        <$Fixed>::checked_from_num(v).ok_or_else(|| E::custom("number out of range"))
+                    }
+                    fn visit_str<E: de::Error>(self, v: &str) -> Result<$Fixed, E> {
+                        v.parse().map_err(|e| E::custom(format_args!("bad number `{}`: {}", v, e)))
+                    }
+                }
+                let val = if deserializer.is_human_readable() {
+                    deserializer.deserialize_any(Visitor)?
+                } else {
+                    <$Fixed>::from_bits(i64::deserialize(deserializer)?)
+                };
+                Ok($T($Inner { val }))
+            }
+        }
+    };
+}
+serde_fixed_number!(Number, D1, I36F28);
+serde_fixed_number!(AngleNumber, Angle, I32F32);
+
+/// converts a type to and from the form it has in scene files
+trait Wire {
+    type Wire;
+    fn to_wire(&self) -> Self::Wire;
+    fn from_wire(wire: Self::Wire) -> Self;
+}
+
+/// makes the scene file format the serde format of scene types
+macro_rules! serde_through_wire {
+    ($($T:ty),*) => {$(
+        impl Serialize for $T {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.to_wire().serialize(serializer)
+            }
+        }
+        impl<'de> Deserialize<'de> for $T {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                <$T as Wire>::Wire::deserialize(deserializer).map(<$T>::from_wire)
+            }
+        }
+    )*};
+}
+serde_through_wire!(SceneCamera, Scene, DrawPlane, Primitive);
+
+impl Wire for D1 {
+    type Wire = Number;
+    fn to_wire(&self) -> Number {
+        Number(*self)
+    }
+    fn from_wire(wire: Number) -> Self {
+        wire.0
+    }
+}
+impl Wire for Angle {
+    type Wire = AngleNumber;
+    fn to_wire(&self) -> AngleNumber {
+        AngleNumber(*self)
+    }
+    fn from_wire(wire: AngleNumber) -> Self {
+        wire.0
+    }
+}
+impl<T: Wire> Wire for Vec<T> {
+    type Wire = Vec<T::Wire>;
+    fn to_wire(&self) -> Self::Wire {
+        self.iter().map(T::to_wire).collect()
+    }
+    fn from_wire(wire: Self::Wire) -> Self {
+        wire.into_iter().map(T::from_wire).collect()
+    }
+}
+impl<T: Wire> Wire for Option<T> {
+    type Wire = Option<T::Wire>;
+    fn to_wire(&self) -> Self::Wire {
+        self.as_ref().map(T::to_wire)
+    }
+    fn from_wire(wire: Self::Wire) -> Self {
+        wire.map(T::from_wire)
+    }
+}
+impl<A: Wire, B: Wire> Wire for (A, B) {
+    type Wire = (A::Wire, B::Wire);
+    fn to_wire(&self) -> Self::Wire {
+        (self.0.to_wire(), self.1.to_wire())
+    }
+    fn from_wire(wire: Self::Wire) -> Self {
+        (A::from_wire(wire.0), B::from_wire(wire.1))
+    }
+}
+impl Wire for D2<D1> {
+    type Wire = D2<Number>;
+    fn to_wire(&self) -> D2<Number> {
+        D2 { x: Number(self.x), y: Number(self.y) }
+    }
+    fn from_wire(wire: D2<Number>) -> Self {
+        D2 { x: wire.x.0, y: wire.y.0 }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "D3")]
+struct D3Wire {
+    x: Number,
+    y: Number,
+    z: Number,
+}
+impl Wire for D3 {
+    type Wire = D3Wire;
+    fn to_wire(&self) -> D3Wire {
+        D3Wire { x: Number(self.x), y: Number(self.y), z: Number(self.z) }
+    }
+    fn from_wire(wire: D3Wire) -> Self {
+        D3 { x: wire.x.0, y: wire.y.0, z: wire.z.0 }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Pos")]
+struct PosWire {
+    coords: D3Wire,
+    angle: AngleNumber,
+}
+impl Wire for Pos {
+    type Wire = PosWire;
+    fn to_wire(&self) -> PosWire {
+        PosWire { coords: self.coords.to_wire(), angle: AngleNumber(self.angle) }
+    }
+    fn from_wire(wire: PosWire) -> Self {
+        Pos::new(D3::from_wire(wire.coords), wire.angle.0)
+    }
+}
+fn pos_zero() -> PosWire {
+    Pos::zero().to_wire()
+}
+
+impl Wire for Shape2D<D1> {
+    type Wire = Shape2D<Number>;
+    fn to_wire(&self) -> Shape2D<Number> {
+        match self {
+            Shape2D::Rect(corners) => Shape2D::Rect(corners.to_wire()),
+            Shape2D::Circle(circle) => Shape2D::Circle(circle.to_wire()),
+        }
+    }
+    fn from_wire(wire: Shape2D<Number>) -> Self {
+        match wire {
+            Shape2D::Rect(corners) => Shape2D::Rect(Wire::from_wire(corners)),
+            Shape2D::Circle(circle) => Shape2D::Circle(Wire::from_wire(circle)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Shape3D")]
+enum Shape3DWire {
+    Point(D3Wire),
+    RectPrism((D3Wire, D3Wire)),
+    Sphere((D3Wire, Number)),
+}
+impl Wire for Shape3D {
+    type Wire = Shape3DWire;
+    fn to_wire(&self) -> Shape3DWire {
+        match self {
+            Shape3D::Point(pt) => Shape3DWire::Point(pt.to_wire()),
+            Shape3D::RectPrism(corners) => Shape3DWire::RectPrism(corners.to_wire()),
+            Shape3D::Sphere(sphere) => Shape3DWire::Sphere(sphere.to_wire()),
+        }
+    }
+    fn from_wire(wire: Shape3DWire) -> Self {
+        match wire {
+            Shape3DWire::Point(pt) => Shape3D::Point(D3::from_wire(pt)),
+            Shape3DWire::RectPrism(corners) => Shape3D::RectPrism(Wire::from_wire(corners)),
+            Shape3DWire::Sphere(sphere) => Shape3D::Sphere(Wire::from_wire(sphere)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "PathCommand")]
+enum PathCommandWire {
+    MoveTo(D2<Number>),
+    LineTo(D2<Number>),
+    QuadTo(D2<Number>, D2<Number>),
+    CubicTo(D2<Number>, D2<Number>, D2<Number>),
+    ArcTo { center: D2<Number>, sweep: AngleNumber },
+    Close,
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Path")]
+struct PathWire {
+    commands: Vec<PathCommandWire>,
+}
+impl Wire for Path {
+    type Wire = PathWire;
+    fn to_wire(&self) -> PathWire {
+        let commands = self.commands().iter().map(|command| match *command {
+            PathCommand::MoveTo(pt) => PathCommandWire::MoveTo(pt.to_wire()),
+            PathCommand::LineTo(pt) => PathCommandWire::LineTo(pt.to_wire()),
+            PathCommand::QuadTo(control, end) => PathCommandWire::QuadTo(control.to_wire(), end.to_wire()),
+            PathCommand::CubicTo(control1, control2, end) => {
+                PathCommandWire::CubicTo(control1.to_wire(), control2.to_wire(), end.to_wire())
+            }
+            PathCommand::ArcTo { center, sweep } => PathCommandWire::ArcTo { center: center.to_wire(), sweep: AngleNumber(sweep) },
+            PathCommand::Close => PathCommandWire::Close,
+        });
+        PathWire { commands: commands.collect() }
+    }
+    fn from_wire(wire: PathWire) -> Self {
+        wire.commands.into_iter().fold(Path::new(), |path, command| match command {
+            PathCommandWire::MoveTo(pt) => path.move_to(D2::from_wire(pt)),
+            PathCommandWire::LineTo(pt) => path.line_to(D2::from_wire(pt)),
+            PathCommandWire::QuadTo(control, end) => path.quad_to(D2::from_wire(control), D2::from_wire(end)),
+            PathCommandWire::CubicTo(control1, control2, end) => {
+                path.cubic_to(D2::from_wire(control1), D2::from_wire(control2), D2::from_wire(end))
+            }
+            PathCommandWire::ArcTo { center, sweep } => path.arc_to(D2::from_wire(center), sweep.0),
+            PathCommandWire::Close => path.close(),
+        })
+    }
+}
+
+/// same variants in the same order as [Primitive], binary files only store the index
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Primitive")]
+enum PrimitiveWire {
+    Shape(Shape2D<Number>, Color),
+    FilledShape(Shape2D<Number>, Color),
+    Line((D2<Number>, D2<Number>), Color),
+    Polygon(Vec<D2<Number>>, Color),
+    FilledPolygon(Vec<D2<Number>>, Color),
+    Text {
+        coords: D2<Number>,
+        text: String,
+        color: Color,
+        dimensions: D2<u16>,
+        center: bool,
+        background: bool,
+    },
+    WorldText {
+        coords: D2<Number>,
+        text: String,
+        color: Color,
+        height: Number,
+        angle: AngleNumber,
+        center: bool,
+    },
+    StrokeText {
+        anchor: D2<Number>,
+        text: String,
+        height: Number,
+        angle: AngleNumber,
+        #[serde(default)]
+        alignment: TextAlign,
+        color: Color,
+    },
+    Path(PathWire, Color),
+    FilledPath(PathWire, FillRule, Color),
+    Shape3D(Shape3DWire, Shape3DStyle),
+}
+impl Wire for Primitive {
+    type Wire = PrimitiveWire;
+    fn to_wire(&self) -> PrimitiveWire {
+        match self {
+            Primitive::Shape(shape, color) => PrimitiveWire::Shape(shape.to_wire(), *color),
+            Primitive::FilledShape(shape, color) => PrimitiveWire::FilledShape(shape.to_wire(), *color),
+            Primitive::Line(line, color) => PrimitiveWire::Line(line.to_wire(), *color),
+            Primitive::Polygon(points, color) => PrimitiveWire::Polygon(points.to_wire(), *color),
+            Primitive::FilledPolygon(points, color) => PrimitiveWire::FilledPolygon(points.to_wire(), *color),
+            Primitive::Text { coords, text, color, dimensions, center, background } => PrimitiveWire::Text {
+                coords: coords.to_wire(),
+                text: text.clone(),
+                color: *color,
+                dimensions: *dimensions,
+                center: *center,
+                background: *background,
+            },
+            Primitive::WorldText { coords, text, color, height, angle, center } => PrimitiveWire::WorldText {
+                coords: coords.to_wire(),
+                text: text.clone(),
+                color: *color,
+                height: Number(*height),
+                angle: AngleNumber(*angle),
+                center: *center,
+            },
+            Primitive::StrokeText { anchor, text, height, angle, alignment, color } => PrimitiveWire::StrokeText {
+                anchor: anchor.to_wire(),
+                text: text.clone(),
+                height: Number(*height),
+                angle: AngleNumber(*angle),
+                alignment: *alignment,
+                color: *color,
+            },
+            Primitive::Path(path, color) => PrimitiveWire::Path(path.to_wire(), *color),
+            Primitive::FilledPath(path, fill_rule, color) => PrimitiveWire::FilledPath(path.to_wire(), *fill_rule, *color),
+            Primitive::Shape3D(shape, style) => PrimitiveWire::Shape3D(shape.to_wire(), *style),
+        }
+    }
+    fn from_wire(wire: PrimitiveWire) -> Self {
+        match wire {
+            PrimitiveWire::Shape(shape, color) => Primitive::Shape(Wire::from_wire(shape), color),
+            PrimitiveWire::FilledShape(shape, color) => Primitive::FilledShape(Wire::from_wire(shape), color),
+            PrimitiveWire::Line(line, color) => Primitive::Line(Wire::from_wire(line), color),
+            PrimitiveWire::Polygon(points, color) => Primitive::Polygon(Wire::from_wire(points), color),
+            PrimitiveWire::FilledPolygon(points, color) => Primitive::FilledPolygon(Wire::from_wire(points), color),
+            PrimitiveWire::Text { coords, text, color, dimensions, center, background } => {
+                Primitive::Text { coords: D2::from_wire(coords), text, color, dimensions, center, background }
+            }
+            PrimitiveWire::WorldText { coords, text, color, height, angle, center } => {
+                Primitive::WorldText { coords: D2::from_wire(coords), text, color, height: height.0, angle: angle.0, center }
+            }
+            PrimitiveWire::StrokeText { anchor, text, height, angle, alignment, color } => {
+                Primitive::StrokeText { anchor: D2::from_wire(anchor), text, height: height.0, angle: angle.0, alignment, color }
+            }
+            PrimitiveWire::Path(path, color) => Primitive::Path(Path::from_wire(path), color),
+            PrimitiveWire::FilledPath(path, fill_rule, color) => Primitive::FilledPath(Path::from_wire(path), fill_rule, color),
+            PrimitiveWire::Shape3D(shape, style) => Primitive::Shape3D(Shape3D::from_wire(shape), style),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "DrawPlane")]
+struct DrawPlaneWire {
+    pos: PosWire,
+    #[serde(default = "opaque")]
+    alpha: u8,
+    #[serde(default)]
+    isolated: bool,
+    #[serde(default)]
+    primitives: Vec<PrimitiveWire>,
+}
+fn opaque() -> u8 {
+    255
+}
+impl Wire for DrawPlane {
+    type Wire = DrawPlaneWire;
+    fn to_wire(&self) -> DrawPlaneWire {
+        DrawPlaneWire { pos: self.pos.to_wire(), alpha: self.alpha, isolated: self.isolated, primitives: self.primitives.to_wire() }
+    }
+    fn from_wire(wire: DrawPlaneWire) -> Self {
+        DrawPlane { pos: Pos::from_wire(wire.pos), alpha: wire.alpha, isolated: wire.isolated, primitives: Wire::from_wire(wire.primitives) }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Scene")]
+struct SceneWire {
+    planes: Vec<DrawPlaneWire>,
+}
+impl Wire for Scene {
+    type Wire = SceneWire;
+    fn to_wire(&self) -> SceneWire {
+        SceneWire { planes: self.planes.to_wire() }
+    }
+    fn from_wire(wire: SceneWire) -> Self {
+        Scene { planes: Wire::from_wire(wire.planes) }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "SceneCamera")]
+struct SceneCameraWire {
+    winsize: (usize, usize),
+    #[serde(default = "one")]
+    buffscale: usize,
+    #[serde(default)]
+    zoom_exponent: i8,
+    #[serde(default = "pos_zero")]
+    pos: PosWire,
+    #[serde(default)]
+    near_dist: Option<Number>,
+    #[serde(default)]
+    filter: ScaleFilter,
+}
+fn one() -> usize {
+    1
+}
+impl Wire for SceneCamera {
+    type Wire = SceneCameraWire;
+    fn to_wire(&self) -> SceneCameraWire {
+        SceneCameraWire {
+            winsize: self.winsize,
+            buffscale: self.buffscale,
+            zoom_exponent: self.zoom_exponent,
+            pos: self.pos.to_wire(),
+            near_dist: self.near_dist.to_wire(),
+            filter: self.filter,
+        }
+    }
+    fn from_wire(wire: SceneCameraWire) -> Self {
+        SceneCamera {
+            winsize: wire.winsize,
+            buffscale: wire.buffscale,
+            zoom_exponent: wire.zoom_exponent,
+            pos: Pos::from_wire(wire.pos),
+            near_dist: Wire::from_wire(wire.near_dist),
+            filter: wire.filter,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_with_camera(camera: &str) -> String {
+        format!("(version: 1, camera: {}, scene: (planes: []))", camera)
+    }
+    fn bad_camera(camera: &str) -> bool {
+        matches!(SceneDocument::from_text(&text_with_camera(camera)), Err(SceneFileError::BadCamera(_)))
+    }
+
+    #[test]
+    fn text_round_trip_writes_plain_numbers() {
+        let mut plane = DrawPlane::new(Pos::new(D3::from((D1::from(1.5), D1::zero(), D1::from(-10))), Angle::zero()));
+        plane.push(Primitive::FilledShape(Shape2D::Circle((D2::zero(), D1::from(5))), Color::white()));
+        let mut scene = Scene::new();
+        scene.push_plane(plane);
+        let document = SceneDocument::new(SceneCamera::new((64, 48)), scene);
+
+        let text = document.to_text();
+        assert!(text.contains("x: 1.5"), "{}", text);
+        assert_eq!(SceneDocument::from_text(&text).unwrap(), document);
+        assert_eq!(SceneDocument::from_binary(&document.to_binary()).unwrap(), document);
+        // the format doesn't depend on how the document is written
+        assert_eq!(SceneDocument::from_text(&ron::to_string(&document).unwrap()).unwrap(), document);
+        assert_eq!(ron::from_str::<SceneDocument>(&text).unwrap(), document);
+    }
+
+    #[test]
+    fn numbers_too_precise_for_floats_are_written_as_strings() {
+        let precise = D1 { val: I36F28::from_bits(i64::MAX) };
+        let path = Path::new().move_to(D2::zero()).quad_to(D2::from((precise, D1::one())), D2::one())
+            .cubic_to(D2::zero(), D2::one(), D2::from((D1::from(2), D1::zero())))
+            .arc_to(D2::zero(), Angle::pi()).line_to(D2::one()).close();
+        let mut plane = DrawPlane::new(Pos::zero());
+        plane.push(Primitive::FilledPath(path, FillRule::EvenOdd, Color::white()));
+        let mut scene = Scene::new();
+        scene.push_plane(plane);
+        let document = SceneDocument::new(SceneCamera::new((64, 48)), scene);
+
+        let text = document.to_text();
+        assert!(text.contains(&format!("\"{}\"", precise.val)), "{}", text);
+        assert_eq!(SceneDocument::from_text(&text).unwrap(), document);
+        assert_eq!(SceneDocument::from_binary(&document.to_binary()).unwrap(), document);
+    }
+
+    #[test]
+    fn numbers_outside_scene_files_keep_the_derived_format() {
+        let text = ron::to_string(&D1::one()).unwrap();
+        assert_eq!(text, "(val:(bits:268435456))");
+        assert_eq!(ron::from_str::<D1>(&text).unwrap(), D1::one());
+        assert!(ron::from_str::<D1>("1").is_err());
+        assert_eq!(bincode::serialize(&D1::one()).unwrap(), bincode::serialize(&D1::one().val.to_bits()).unwrap());
+    }
+
+    #[test]
+    fn primitives_keep_their_binary_variant_indices() {
+        let index = |primitive: Primitive| bincode::serialize(&primitive).unwrap()[..4].to_vec();
+        let text = Primitive::Text {
+            coords: D2::zero(),
+            text: String::new(),
+            color: Color::white(),
+            dimensions: D2::from((1, 1)),
+            center: false,
+            background: false,
+        };
+        assert_eq!(index(text), 5u32.to_le_bytes());
+        assert_eq!(index(Primitive::Path(Path::new(), Color::white())), 8u32.to_le_bytes());
+        assert_eq!(index(Primitive::Shape3D(Shape3D::Point(D3::zero()), Shape3DStyle::Wireframe(Color::white()))), 10u32.to_le_bytes());
+    }
+
+    #[test]
+    fn cameras_that_cant_render_are_rejected() {
+        assert!(SceneDocument::from_text(&text_with_camera("(winsize: (64, 48), zoom_exponent: 9, near_dist: Some(0.5))")).is_ok());
+        assert!(bad_camera("(winsize: (64, 48), zoom_exponent: 100)"));
+        assert!(bad_camera("(winsize: (64, 48), zoom_exponent: -11)"));
+        assert!(bad_camera("(winsize: (0, 48))"));
+        assert!(bad_camera("(winsize: (100000, 48))"));
+        assert!(bad_camera("(winsize: (64, 48), buffscale: 0)"));
+        assert!(bad_camera("(winsize: (64, 48), near_dist: Some(0))"));
+        assert!(bad_camera("(winsize: (64, 48), near_dist: Some(100000))"));
+
+        let mut camera = SceneCamera::new((64, 48));
+        camera.zoom_exponent = 100;
+        let document = SceneDocument::new(camera, Scene::new());
+        assert!(matches!(SceneDocument::from_binary(&document.to_binary()), Err(SceneFileError::BadCamera(_))));
+        // the clamp keeps cameras made in code from panicking
+        assert_eq!(camera.to_cam_data().zoom_exponent, LARGEST_ZOOM_EXPONENT);
+    }
+}
@@ -96,7 +96,7 @@ impl Direction {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Inclusive {
     Inclusive,
     Exclusive,
@@ -527,7 +527,7 @@ impl CornerStuff3D for (D3, D3) {
 
 
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Shape2D<T> {
     Rect((D2<T>, D2<T>)), // hitbox is a rectangle
     Circle((D2<T>, T)), // coords and radius
@@ -657,7 +657,7 @@ impl Shape2D<D1> {
 }
 
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Shape3D {
     Point(D3), // hitbox is a point
     RectPrism((D3, D3)), // hitbox is a rectangle
@@ -0,0 +1,364 @@
+use std::{fmt, fs, io, path::Path};
+
+use crate::blend::BlendMode;
+use crate::coordinates::*;
+use crate::draw_functions::{Color, Projection};
+use crate::path::FillRule;
+use crate::render_target::{RenderTarget, VectorShape};
+
+/// what the coords of an [SvgTarget] are
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SvgSpace {
+    /// what the cam sees, in screen pixels with perspective. Pixels drawn without a shape (images, textures)
+    /// are kept as one pixel squares
+    Screen,
+    /// a top down view of a single plane at this position without perspective, in plane units.
+    /// The cam still has to be able to see the plane, but where it is doesn't matter. Pixels drawn without a shape are left out
+    Plane(Pos),
+}
+
+/// a target that keeps the shapes a [Cam](crate::Cam) draws instead of pixels and writes them as an svg document.
+/// Draw into it with a cam like any other target, then [SvgTarget::save] it
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgTarget {
+    size: (usize, usize),
+    space: SvgSpace,
+    /// fills the whole document before anything else
+    pub background: Option<Color>,
+    elements: Vec<String>,
+    /// min and max corner of everything drawn in svg coords, the view box of plane space documents
+    bounds: Option<(D2<D1>, D2<D1>)>,
+}
+impl SvgTarget {
+    /// a screen space document `size` pixels big, the cam drawing into it looks at its center
+    pub fn new(size: (usize, usize)) -> Self {
+        Self { size, space: SvgSpace::Screen, background: None, elements: Vec::new(), bounds: None }
+    }
+    /// a plane space document of the plane at `pos`, draw into it with a cam made for the same `pos`.
+    /// `size` is only what the cam sees as the screen size
+    pub fn new_plane(size: (usize, usize), pos: Pos) -> Self {
+        Self { space: SvgSpace::Plane(pos), ..Self::new(size) }
+    }
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+    pub fn space(&self) -> SvgSpace {
+        self.space
+    }
+    /// how many elements have been drawn
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    /// turns a point rel to the draw plane into svg coords, where y goes down
+    fn svg_coords(&self, pt: D3, projection: &Projection) -> Option<D2<D1>> {
+        let pt = match self.space {
+            SvgSpace::Screen => {
+                let screen = projection.project(pt)?;
+                D2::from((screen.x, D1::from(self.size.1) - screen.y))
+            }
+            SvgSpace::Plane(pos) => {
+                // turned the same way a cam that isn't turned itself shows the plane
+                let world = pt.xy().rotate(pos.angle) + pos.coords.xy();
+                D2::from((world.x, -world.y))
+            }
+        };
+        Some(pt)
+    }
+    fn svg_coords_all(&mut self, points: &[D2<D1>], projection: &Projection) -> Option<Vec<D2<D1>>> {
+        let points = points.iter().map(|pt| self.svg_coords(D3::from((*pt, D1::zero())), projection)).collect::<Option<Vec<_>>>()?;
+        for pt in &points {
+            self.grow_bounds(*pt, *pt);
+        }
+        Some(points)
+    }
+    /// how many svg units one plane unit is
+    fn scale(&self, projection: &Projection) -> Option<D1> {
+        match self.space {
+            SvgSpace::Screen => Some(D1::one() / projection.plane_units_per_pixel()?),
+            SvgSpace::Plane(_) => Some(D1::one()),
+        }
+    }
+    fn grow_bounds(&mut self, min: D2<D1>, max: D2<D1>) {
+        let (old_min, old_max) = self.bounds.unwrap_or((min, max));
+        self.bounds = Some((
+            D2::from((old_min.x.min(min.x), old_min.y.min(min.y))),
+            D2::from((old_max.x.max(max.x), old_max.y.max(max.y))),
+        ));
+    }
+    /// min corner and size of the document in svg coords
+    fn view_box(&self) -> (D2<D1>, D2<D1>) {
+        match (self.space, self.bounds) {
+            (SvgSpace::Plane(_), Some((min, max))) => {
+                // a little room so lines on the edge aren't cut in half
+                let margin = D2::one();
+                (min - margin, max - min + margin * D2::two())
+            }
+            (SvgSpace::Plane(_), None) => (D2::zero(), D2::zero()),
+            (SvgSpace::Screen, _) => (D2::zero(), D2::from((D1::from(self.size.0), D1::from(self.size.1)))),
+        }
+    }
+}
+
+impl RenderTarget for SvgTarget {
+    type Pixel = Color;
+
+    fn size(&self) -> (usize, usize) {
+        self.size
+    }
+    /// nothing is stored as pixels
+    fn get_pixel(&self, _coords: (usize, usize)) -> Option<Color> {
+        None
+    }
+    fn put_pixel(&mut self, coords: (usize, usize), color: Color) {
+        if self.space != SvgSpace::Screen || coords.0 >= self.size.0 || coords.1 >= self.size.1 {
+            return
+        }
+        self.elements.push(format!(r#"<rect x="{}" y="{}" width="1" height="1" {}/>"#, coords.0, coords.1, paint("fill", color)));
+    }
+    fn blend_pixel(&mut self, coords: (usize, usize), color: Color, _blend_mode: BlendMode) {
+        self.put_pixel(coords, color);
+    }
+    /// drops everything drawn so far and makes `color` the background
+    fn clear(&mut self, color: Color) {
+        self.elements.clear();
+        self.bounds = None;
+        self.background = Some(color);
+    }
+    fn draw_vector(&mut self, shape: &VectorShape, color: Color, projection: &Projection) -> bool {
+        let element = match *shape {
+            VectorShape::Line(a, b) => {
+                let (a, b) = match self.space {
+                    SvgSpace::Screen => match projection.clip_near((a, b)) {
+                        Some(line) => line,
+                        None => return true,
+                    },
+                    SvgSpace::Plane(_) => (a, b),
+                };
+                let (Some(a), Some(b)) = (self.svg_coords(a, projection), self.svg_coords(b, projection)) else {
+                    return true
+                };
+                self.grow_bounds(a, a);
+                self.grow_bounds(b, b);
+                format!(
+                    r#"<line x1="{}" y1="{}" x2="{}" y2="{}" {} {}/>"#,
+                    num(a.x), num(a.y), num(b.x), num(b.y), paint("stroke", color), STROKE
+                )
+            }
+            VectorShape::Polyline { points, closed } => {
+                let Some(points) = self.svg_coords_all(points, projection) else {
+                    return true
+                };
+                let tag = if closed { "polygon" } else { "polyline" };
+                format!(r#"<{} points="{}" fill="none" {} {}/>"#, tag, point_list(&points), paint("stroke", color), STROKE)
+            }
+            VectorShape::Fill { contours, fill_rule } => {
+                let mut d = String::new();
+                for contour in contours {
+                    let Some(points) = self.svg_coords_all(contour, projection) else {
+                        return true
+                    };
+                    if points.len() >= 3 {
+                        d += &format!("M{}Z", point_list(&points));
+                    }
+                }
+                let rule = match fill_rule {
+                    FillRule::NonZero => "nonzero",
+                    FillRule::EvenOdd => "evenodd",
+                };
+                format!(r#"<path d="{}" fill-rule="{}" {}/>"#, d, rule, paint("fill", color))
+            }
+            VectorShape::Face(points) => {
+                let points = match self.space {
+                    SvgSpace::Screen => projection.clip_polygon_near(points),
+                    SvgSpace::Plane(_) => points.to_vec(),
+                };
+                let Some(points) = points.iter().map(|pt| self.svg_coords(*pt, projection)).collect::<Option<Vec<_>>>() else {
+                    return true
+                };
+                if points.len() < 3 {
+                    return true
+                }
+                for pt in &points {
+                    self.grow_bounds(*pt, *pt);
+                }
+                format!(r#"<polygon points="{}" {}/>"#, point_list(&points), paint("fill", color))
+            }
+            VectorShape::Circle { center, radius, filled } => {
+                let (Some(center), Some(scale)) = (self.svg_coords(D3::from((center, D1::zero())), projection), self.scale(projection)) else {
+                    return true
+                };
+                let radius = radius * scale;
+                self.grow_bounds(center - D2::from(radius), center + D2::from(radius));
+                let style = if filled {
+                    paint("fill", color)
+                } else {
+                    format!(r#"fill="none" {} {}"#, paint("stroke", color), STROKE)
+                };
+                format!(r#"<circle cx="{}" cy="{}" r="{}" {}/>"#, num(center.x), num(center.y), num(radius), style)
+            }
+            VectorShape::Text { coords, text, line_height, center } => {
+                let Some(anchor) = self.svg_coords(D3::from((coords, D1::zero())), projection) else {
+                    return true
+                };
+                // text keeps its screen size, so in plane units it's as big as it was where the cam saw it
+                let line_height = match self.space {
+                    SvgSpace::Screen => line_height,
+                    SvgSpace::Plane(_) => match projection.plane_units_per_pixel() {
+                        Some(units) => line_height * units,
+                        None => return true,
+                    },
+                };
+                let lines: Vec<&str> = text.split('\n').collect();
+                // svg text sits on its baseline, which is taken to be the bottom of the line
+                let mut baseline = anchor.y;
+                if center {
+                    baseline += line_height * D1::from(lines.len()) / D1::two() - line_height * D1::from(lines.len() as i32 - 1);
+                }
+                self.grow_bounds(D2::from((anchor.x, baseline - line_height)), D2::from((anchor.x, baseline + line_height * D1::from(lines.len() as i32 - 1))));
+                let mut spans = String::new();
+                for (i, line) in lines.iter().enumerate() {
+                    let dy = if i == 0 { D1::zero() } else { line_height };
+                    spans += &format!(r#"<tspan x="{}" dy="{}">{}</tspan>"#, num(anchor.x), num(dy), escape(line));
+                }
+                format!(
+                    r#"<text x="{}" y="{}" font-family="monospace" font-size="{}" text-anchor="{}" xml:space="preserve" {}>{}</text>"#,
+                    num(anchor.x), num(baseline), num(line_height), if center { "middle" } else { "start" }, paint("fill", color), spans
+                )
+            }
+        };
+        self.elements.push(element);
+        true
+    }
+}
+
+/// the whole svg document
+impl fmt::Display for SvgTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (min, size) = self.view_box();
+        writeln!(f, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            f,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+            num(size.x), num(size.y), num(min.x), num(min.y), num(size.x), num(size.y)
+        )?;
+        if let Some(background) = self.background {
+            writeln!(
+                f,
+                r#"  <rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+                num(min.x), num(min.y), num(size.x), num(size.y), paint("fill", background)
+            )?;
+        }
+        for element in &self.elements {
+            writeln!(f, "  {}", element)?;
+        }
+        writeln!(f, "</svg>")
+    }
+}
+
+/// lines stay one pixel wide however the document is zoomed, like they are when rasterized
+const STROKE: &str = r#"stroke-width="1" vector-effect="non-scaling-stroke""#;
+
+/// `fill="#rrggbb"` or `stroke="#rrggbb"`, with an opacity if the color isn't opaque
+fn paint(kind: &str, color: Color) -> String {
+    let mut paint = format!(r##"{}="#{:02x}{:02x}{:02x}""##, kind, color.r, color.g, color.b);
+    if color.alpha != 255 {
+        paint += &format!(r#" {}-opacity="{}""#, kind, num(D1::from(color.alpha as i32) / D1::from(255)));
+    }
+    paint
+}
+
+/// at most 3 decimals without trailing zeros, also used for the labels of [Cam::draw_grid](crate::Cam::draw_grid)
+pub(crate) fn num(val: D1) -> String {
+    let text = format!("{:.3}", val.to_f32());
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0".to_owned() } else { text.to_owned() }
+}
+
+fn point_list(points: &[D2<D1>]) -> String {
+    points.iter().map(|pt| format!("{},{}", num(pt.x), num(pt.y))).collect::<Vec<_>>().join(" ")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cam_data::CamData;
+    use crate::draw_functions::Cam;
+    use crate::font::Font;
+
+    fn d2(x: i32, y: i32) -> D2<D1> {
+        D2::from((D1::from(x), D1::from(y)))
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(num(D1::from(3)), "3");
+        assert_eq!(num(D1::from(-2.5)), "-2.5");
+        assert_eq!(num(D1::one() / D1::from(3)), "0.333");
+        // rounds to nothing, without a sign
+        assert_eq!(num(D1::from(-0.0001)), "0");
+    }
+
+    #[test]
+    fn screen_documents_keep_what_the_cam_sees() {
+        let cam_data = CamData::new((40, 20), 1);
+        let plane = Pos::new(D3::from((D1::zero(), D1::zero(), D1::from(-10))), Angle::zero());
+        let mut svg = SvgTarget::new((40, 20));
+        let mut cam = Cam::new_rel_to_pos(&mut svg, &cam_data, plane, 128).unwrap();
+        let top = cam.project(D3::from((d2(0, 5), D1::zero()))).unwrap();
+        cam.draw_line((d2(0, 0), d2(0, 5)), Color::white());
+        cam.fill_polygon(&[d2(0, 0), d2(5, 0), d2(0, 5)], Color::new(255, 0, 0));
+        cam.fill_circle(D2::zero(), D1::from(2), Color::white());
+        cam.draw_text(D2::zero(), "a<b & c", Font::builtin(), Color::white(), D2::from((1, 1)), false, false);
+        assert_eq!(svg.len(), 4);
+
+        let text = svg.to_string();
+        assert!(text.contains(r#"viewBox="0 0 40 20""#), "{}", text);
+        // the middle of the screen, y goes down in svg
+        let line = format!(r##"<line x1="20" y1="10" x2="20" y2="{}" stroke="#ffffff" stroke-opacity="0.502""##, num(D1::from(20) - top.y));
+        assert!(text.contains(&line), "{}", text);
+        assert!(text.contains(r##"fill-rule="evenodd" fill="#ff0000" fill-opacity="0.502"/>"##), "{}", text);
+        assert!(text.contains(r#"<circle cx="20" cy="10" r=""#), "{}", text);
+        assert!(text.contains(">a&lt;b &amp; c</tspan>"), "{}", text);
+    }
+
+    #[test]
+    fn plane_documents_are_in_plane_units() {
+        let cam_data = CamData::new((40, 20), 1);
+        let plane = Pos::new(D3::from((D1::from(3), D1::from(4), D1::from(-10))), Angle::zero());
+        let mut svg = SvgTarget::new_plane((40, 20), plane);
+        let mut cam = Cam::new_rel_to_pos(&mut svg, &cam_data, plane, 255).unwrap();
+        cam.draw_line((d2(0, 0), d2(2, 1)), Color::white());
+        cam.fill_polygon(&[d2(0, 0), d2(2, 0), d2(0, 2)], Color::white());
+        cam.fill_circle(D2::zero(), D1::from(2), Color::new_with_alpha(255, 255, 255, 51));
+
+        let text = svg.to_string();
+        // moved to where the plane is, y goes down in svg
+        assert!(text.contains(r##"<line x1="3" y1="-4" x2="5" y2="-5" stroke="#ffffff""##), "{}", text);
+        assert!(text.contains(r#"<path d="M3,-4 5,-4 3,-6Z""#), "{}", text);
+        assert!(text.contains(r##"<circle cx="3" cy="-4" r="2" fill="#ffffff" fill-opacity="0.2"/>"##), "{}", text);
+        // everything drawn with a margin of 1 around it
+        assert!(text.contains(r#"viewBox="0 -7 6 6""#), "{}", text);
+
+        let mut cam = Cam::new_rel_to_pos(&mut svg, &cam_data, plane, 255).unwrap();
+        cam.draw_text(D2::zero(), "a", Font::builtin(), Color::white(), D2::from((1, 1)), false, false);
+        assert!(svg.to_string().contains(r#"<text x="3" y="-4""#), "{}", svg);
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        assert_eq!(escape("a & b < c > d"), "a &amp; b &lt; c &gt; d");
+        assert_eq!(escape("&lt;"), "&amp;lt;");
+    }
+}
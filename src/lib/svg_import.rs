@@ -0,0 +1,969 @@
+use std::{error, fmt, fs, io};
+
+use fixed::types::{I32F32, I36F28};
+
+use crate::coordinates::*;
+use crate::draw_functions::{Cam, Color};
+use crate::path::Path;
+use crate::render_target::RenderTarget;
+use crate::shape::{Corners, Shape2D};
+
+#[derive(Debug)]
+pub enum SvgImportError {
+    Io(io::Error),
+    /// the file isn't valid svg, or uses something this reader doesn't understand. Lines start at 1
+    Syntax { line: usize, message: String },
+}
+impl fmt::Display for SvgImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SvgImportError::Io(e) => write!(f, "could not read svg: {}", e),
+            SvgImportError::Syntax { line, message } => write!(f, "svg line {}: {}", line, message),
+        }
+    }
+}
+impl error::Error for SvgImportError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            SvgImportError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+impl From<io::Error> for SvgImportError {
+    fn from(e: io::Error) -> Self {
+        SvgImportError::Io(e)
+    }
+}
+
+/// one shape read out of an svg file, in plane coords
+#[derive(Debug, Clone, PartialEq)]
+pub enum SvgShape {
+    /// a rect or circle that is still a rect or circle after its transforms (nothing rotated, skewed or stretched unevenly)
+    Shape(Shape2D<D1>),
+    /// everything else, paths give one for every outline
+    Polyline { points: Vec<D2<D1>>, closed: bool },
+}
+impl SvgShape {
+    pub fn draw<T: RenderTarget + ?Sized>(&self, cam: &mut Cam<T>, color: Color) {
+        match self {
+            SvgShape::Shape(shape) => cam.draw_shape(*shape, color),
+            SvgShape::Polyline { points, closed } => cam.draw_polyline(points, *closed, color),
+        }
+    }
+    /// polylines that aren't closed are filled as if they were
+    pub fn fill<T: RenderTarget + ?Sized>(&self, cam: &mut Cam<T>, color: Color) {
+        match self {
+            SvgShape::Shape(shape) => cam.fill_shape(*shape, color),
+            SvgShape::Polyline { points, .. } => cam.fill_polygon(points, color),
+        }
+    }
+}
+
+/// reads the `<path>`, `<rect>`, `<circle>`, `<ellipse>`, `<line>`, `<polygon>` and `<polyline>` elements of an svg
+/// document with the `transform`s of them and the groups they are in. Styles, text, images and anything inside of
+/// `<defs>` are skipped.
+///
+/// y is flipped so the drawing is the same way up on a plane as in the editor, one svg unit is one plane unit.
+/// Curves are split into lines that are within `tolerance` of them
+pub fn parse_svg(text: &str, tolerance: D1) -> Result<Vec<SvgShape>, SvgImportError> {
+    let mut shapes = Vec::new();
+    // (element name, transform, hidden) of every open element
+    let flip = Transform { d: -D1::one(), ..Transform::identity() };
+    let mut stack: Vec<(&str, Transform, bool)> = Vec::new();
+    let mut reader = XmlReader { text, pos: 0 };
+    while let Some(token) = reader.next_token()? {
+        let tag = match token {
+            XmlToken::End(name, line) => {
+                match stack.pop() {
+                    Some((open, _, _)) if open == name => continue,
+                    _ => return Err(syntax(line, format!("unexpected </{}>", name))),
+                }
+            }
+            XmlToken::Start(tag) => tag,
+        };
+        let (parent, parent_hidden) = stack.last().map_or((flip, false), |(_, transform, hidden)| (*transform, *hidden));
+        let transform = match tag.attribute("transform") {
+            Some(list) => parse_transform(list).and_then(|own| parent.then(own)).map_err(|message| syntax(tag.line, message))?,
+            None => parent,
+        };
+        let hidden = parent_hidden
+            || NOT_DRAWN.contains(&tag.name)
+            || tag.attribute("display") == Some("none");
+        if !hidden {
+            read_element(&tag, transform, tolerance, &mut shapes).map_err(|message| syntax(tag.line, message))?;
+        }
+        if !tag.self_closing {
+            stack.push((tag.name, transform, hidden));
+        }
+    }
+    if let Some((name, _, _)) = stack.last() {
+        return Err(syntax(reader.line(text.len()), format!("<{}> is never closed", name)))
+    }
+    Ok(shapes)
+}
+
+/// [parse_svg] for a file
+pub fn load_svg<P: AsRef<std::path::Path>>(path: P, tolerance: D1) -> Result<Vec<SvgShape>, SvgImportError> {
+    let text = fs::read_to_string(path)?;
+    parse_svg(&text, tolerance)
+}
+
+/// elements whose children are only drawn when something refers to them
+const NOT_DRAWN: [&str; 6] = ["defs", "clipPath", "mask", "marker", "pattern", "symbol"];
+
+fn syntax(line: usize, message: String) -> SvgImportError {
+    SvgImportError::Syntax { line, message }
+}
+
+fn read_element(tag: &Tag, transform: Transform, tolerance: D1, shapes: &mut Vec<SvgShape>) -> Result<(), String> {
+    let number = |name: &str| -> Result<D1, String> {
+        match tag.attribute(name) {
+            Some(value) => {
+                let mut scanner = Scanner::new(value);
+                let val = scanner.number()?.ok_or_else(|| format!("{} of <{}> is not a number", name, tag.name))?;
+                Ok(val)
+            }
+            None => Ok(D1::zero()),
+        }
+    };
+    let mut path = Path::new();
+    match tag.name {
+        "rect" => {
+            let (x, y, width, height) = (number("x")?, number("y")?, number("width")?, number("height")?);
+            if width <= D1::zero() || height <= D1::zero() {
+                return Ok(())
+            }
+            // a missing radius is the same as the other one
+            let (rx, ry) = match (tag.attribute("rx"), tag.attribute("ry")) {
+                (None, None) => (D1::zero(), D1::zero()),
+                (Some(_), None) => (number("rx")?, number("rx")?),
+                (None, Some(_)) => (number("ry")?, number("ry")?),
+                _ => (number("rx")?, number("ry")?),
+            };
+            let rx = rx.min(width / D1::two());
+            let ry = ry.min(height / D1::two());
+            let (right, bottom) = (x.plus(width)?, y.plus(height)?);
+            let corners = (D2::from((x, y)), D2::from((right, bottom)));
+            if rx <= D1::zero() || ry <= D1::zero() {
+                if transform.is_axis_aligned() {
+                    shapes.push(SvgShape::Shape(Shape2D::Rect((transform.apply(corners.0)?, transform.apply(corners.1)?).fix_corners())));
+                    return Ok(())
+                }
+                let points = [corners.0, D2::from((corners.1.x, corners.0.y)), corners.1, D2::from((corners.0.x, corners.1.y))];
+                let points = points.iter().map(|pt| transform.apply(*pt)).collect::<Result<_, _>>()?;
+                shapes.push(SvgShape::Polyline { points, closed: true });
+                return Ok(())
+            }
+            // the radii are at most half the size, so everything below stays between the corners and can't overflow
+            let mut outline = PathBuilder::new(transform);
+            let radii = D2::from((rx, ry));
+            outline.move_to(D2::from((x + rx, y)))?;
+            outline.line_to(D2::from((right - rx, y)))?;
+            outline.arc_to(radii, Angle::zero(), false, true, D2::from((right, y + ry)))?;
+            outline.line_to(D2::from((right, bottom - ry)))?;
+            outline.arc_to(radii, Angle::zero(), false, true, D2::from((right - rx, bottom)))?;
+            outline.line_to(D2::from((x + rx, bottom)))?;
+            outline.arc_to(radii, Angle::zero(), false, true, D2::from((x, bottom - ry)))?;
+            outline.line_to(D2::from((x, y + ry)))?;
+            outline.arc_to(radii, Angle::zero(), false, true, D2::from((x + rx, y)))?;
+            outline.close();
+            path = outline.path;
+        }
+        "circle" | "ellipse" => {
+            let center = D2::from((number("cx")?, number("cy")?));
+            let radii = if tag.name == "circle" {
+                D2::from(number("r")?)
+            } else {
+                D2::from((number("rx")?, number("ry")?))
+            };
+            if radii.x <= D1::zero() || radii.y <= D1::zero() {
+                return Ok(())
+            }
+            if radii.x == radii.y && transform.is_axis_aligned() && transform.a.abs() == transform.d.abs() {
+                shapes.push(SvgShape::Shape(Shape2D::Circle((transform.apply(center)?, radii.x.times(transform.a.abs())?))));
+                return Ok(())
+            }
+            let mut outline = PathBuilder::new(transform);
+            let right = center.plus(D2::from((radii.x, D1::zero())))?;
+            let left = center.minus(D2::from((radii.x, D1::zero())))?;
+            outline.move_to(right)?;
+            outline.arc_to(radii, Angle::zero(), false, true, left)?;
+            outline.arc_to(radii, Angle::zero(), false, true, right)?;
+            outline.close();
+            path = outline.path;
+        }
+        "line" => {
+            let points = [D2::from((number("x1")?, number("y1")?)), D2::from((number("x2")?, number("y2")?))];
+            let points = points.iter().map(|pt| transform.apply(*pt)).collect::<Result<_, _>>()?;
+            shapes.push(SvgShape::Polyline { points, closed: false });
+        }
+        "polygon" | "polyline" => {
+            let mut scanner = Scanner::new(tag.attribute("points").unwrap_or(""));
+            let mut points = Vec::new();
+            while let Some(x) = scanner.number()? {
+                // an odd number of coords drops the last one, like browsers do
+                let Some(y) = scanner.number()? else {
+                    break
+                };
+                points.push(transform.apply(D2::from((x, y)))?);
+            }
+            if points.len() > 1 {
+                shapes.push(SvgShape::Polyline { points, closed: tag.name == "polygon" });
+            }
+        }
+        "path" => path = parse_path_data(tag.attribute("d").unwrap_or(""), transform)?,
+        _ => {}
+    }
+    for contour in path.contours(tolerance) {
+        shapes.push(SvgShape::Polyline { points: contour.points, closed: contour.closed });
+    }
+    Ok(())
+}
+
+/// the 2d affine transform svg uses, a point (x, y) goes to (a x + c y + e, b x + d y + f)
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Transform {
+    a: D1,
+    b: D1,
+    c: D1,
+    d: D1,
+    e: D1,
+    f: D1,
+}
+impl Transform {
+    fn identity() -> Self {
+        Self { a: D1::one(), b: D1::zero(), c: D1::zero(), d: D1::one(), e: D1::zero(), f: D1::zero() }
+    }
+    /// errors if the point ends up further than [MAX_COORD] from the origin
+    fn apply(&self, pt: D2<D1>) -> Result<D2<D1>, String> {
+        let x = self.a.times(pt.x)?.plus(self.c.times(pt.y)?)?.plus(self.e)?;
+        let y = self.b.times(pt.x)?.plus(self.d.times(pt.y)?)?.plus(self.f)?;
+        if x < -MAX_COORD || x > MAX_COORD || y < -MAX_COORD || y > MAX_COORD {
+            return Err(too_big())
+        }
+        Ok(D2::from((x, y)))
+    }
+    /// `other` first, then self
+    fn then(&self, other: Transform) -> Result<Transform, String> {
+        let dot = |p: D1, q: D1, r: D1, s: D1| -> Result<D1, String> { p.times(q)?.plus(r.times(s)?) };
+        Ok(Transform {
+            a: dot(self.a, other.a, self.c, other.b)?,
+            b: dot(self.b, other.a, self.d, other.b)?,
+            c: dot(self.a, other.c, self.c, other.d)?,
+            d: dot(self.b, other.c, self.d, other.d)?,
+            e: dot(self.a, other.e, self.c, other.f)?.plus(self.e)?,
+            f: dot(self.b, other.e, self.d, other.f)?.plus(self.f)?,
+        })
+    }
+    /// rects stay rects that line up with the axes
+    fn is_axis_aligned(&self) -> bool {
+        self.b.is_zero() && self.c.is_zero()
+    }
+}
+
+/// a `transform` attribute like `translate(10, 5) rotate(30)`
+fn parse_transform(list: &str) -> Result<Transform, String> {
+    let mut transform = Transform::identity();
+    let mut rest = list;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if rest.is_empty() {
+            return Ok(transform)
+        }
+        let (name, after) = rest.split_once('(').ok_or_else(|| format!("bad transform {:?}", list))?;
+        let (args, after) = after.split_once(')').ok_or_else(|| format!("bad transform {:?}", list))?;
+        rest = after;
+        let mut scanner = Scanner::new(args);
+        let mut args = Vec::new();
+        while let Some(arg) = scanner.number()? {
+            args.push(arg);
+        }
+        let zero = D1::zero();
+        let one = D1::one();
+        let step = match (name.trim(), args.as_slice()) {
+            ("matrix", &[a, b, c, d, e, f]) => Transform { a, b, c, d, e, f },
+            ("translate", &[x]) => Transform { e: x, ..Transform::identity() },
+            ("translate", &[x, y]) => Transform { e: x, f: y, ..Transform::identity() },
+            ("scale", &[s]) => Transform { a: s, d: s, ..Transform::identity() },
+            ("scale", &[x, y]) => Transform { a: x, d: y, ..Transform::identity() },
+            ("rotate", &[degrees]) => rotation(degrees),
+            ("rotate", &[degrees, x, y]) => {
+                let to = Transform { e: x, f: y, ..Transform::identity() };
+                let back = Transform { e: D1::zero().minus(x)?, f: D1::zero().minus(y)?, ..Transform::identity() };
+                to.then(rotation(degrees))?.then(back)?
+            }
+            ("skewX", &[degrees]) => Transform { a: one, b: zero, c: tan(degrees)?, d: one, e: zero, f: zero },
+            ("skewY", &[degrees]) => Transform { a: one, b: tan(degrees)?, c: zero, d: one, e: zero, f: zero },
+            (name, args) => return Err(format!("unknown transform {}({} numbers)", name, args.len())),
+        };
+        transform = transform.then(step)?;
+    }
+}
+
+fn radians(degrees: D1) -> Angle {
+    // whole turns first so big angles can't overflow
+    let degrees = D1 { val: degrees.val % I36F28::from_num(360) };
+    Angle::from(degrees * D1::from(Angle::pi()) / D1::from(180))
+}
+fn rotation(degrees: D1) -> Transform {
+    let angle = radians(degrees);
+    let (sin, cos) = (D1::from(angle.sin()), D1::from(angle.cos()));
+    Transform { a: cos, b: sin, c: -sin, d: cos, e: D1::zero(), f: D1::zero() }
+}
+fn tan(degrees: D1) -> Result<D1, String> {
+    let angle = radians(degrees);
+    let tan = angle.sin().checked_div(angle.cos()).and_then(I36F28::checked_from_num);
+    tan.map(|val| D1 { val }).ok_or_else(|| format!("can't skew by {} degrees", degrees.to_f32()))
+}
+
+/// turns svg path commands into a [Path] with the transform already applied, elliptical arcs become cubic beziers
+struct PathBuilder {
+    path: Path,
+    transform: Transform,
+    /// untransformed, like the coords of the commands
+    current: D2<D1>,
+    start: D2<D1>,
+}
+impl PathBuilder {
+    fn new(transform: Transform) -> Self {
+        Self { path: Path::new(), transform, current: D2::zero(), start: D2::zero() }
+    }
+    fn move_to(&mut self, pt: D2<D1>) -> Result<(), String> {
+        self.path = std::mem::take(&mut self.path).move_to(self.transform.apply(pt)?);
+        self.current = pt;
+        self.start = pt;
+        Ok(())
+    }
+    fn line_to(&mut self, pt: D2<D1>) -> Result<(), String> {
+        self.path = std::mem::take(&mut self.path).line_to(self.transform.apply(pt)?);
+        self.current = pt;
+        Ok(())
+    }
+    fn cubic_to(&mut self, control1: D2<D1>, control2: D2<D1>, end: D2<D1>) -> Result<(), String> {
+        let t = self.transform;
+        self.path = std::mem::take(&mut self.path).cubic_to(t.apply(control1)?, t.apply(control2)?, t.apply(end)?);
+        self.current = end;
+        Ok(())
+    }
+    fn quad_to(&mut self, control: D2<D1>, end: D2<D1>) -> Result<(), String> {
+        let t = self.transform;
+        self.path = std::mem::take(&mut self.path).quad_to(t.apply(control)?, t.apply(end)?);
+        self.current = end;
+        Ok(())
+    }
+    fn close(&mut self) {
+        self.path = std::mem::take(&mut self.path).close();
+        self.current = self.start;
+    }
+    /// an svg elliptical arc from the current point to `end`, see https://www.w3.org/TR/SVG11/implnote.html#ArcImplementationNotes
+    fn arc_to(&mut self, radii: D2<D1>, rotation: Angle, large_arc: bool, sweep: bool, end: D2<D1>) -> Result<(), String> {
+        let from = self.current;
+        let (mut rx, mut ry) = (radii.x.abs(), radii.y.abs());
+        if from == end {
+            return Ok(())
+        }
+        if rx.is_zero() || ry.is_zero() {
+            return self.line_to(end)
+        }
+        // the half way point rel to the middle, unrotated. Both ends are halved first so it can't overflow
+        let middle = from / D2::two() + end / D2::two();
+        let half = rotated(from / D2::two() - end / D2::two(), -rotation)?;
+        let (u, v) = (over(half.x, rx)?, over(half.y, ry)?);
+        // u and v get squared, so they are divided by the bigger one first. Tiny radii make them huge
+        // and huge radii make them small enough to round to 0 when squared
+        let biggest = u.abs().max(v.abs());
+        if biggest.is_zero() {
+            return self.line_to(end)
+        }
+        let (u_dir, v_dir) = (u / biggest, v / biggest);
+        // between 1 and 2, u^2 + v^2 is biggest^2 times this
+        let length_sq = u_dir * u_dir + v_dir * v_dir;
+        let mut coef = D1::zero();
+        let (u, v) = if biggest >= D1::one() || biggest * biggest * length_sq >= D1::one() {
+            // the radii are too small to reach, scale them up until they just do. The middle is then half way between the ends
+            let scale = biggest.times(length_sq.sqrt())?;
+            rx = rx.times(scale)?;
+            ry = ry.times(scale)?;
+            (u_dir, v_dir)
+        } else {
+            let lambda = biggest * biggest * length_sq;
+            coef = ((D1::one() - lambda) / length_sq).sqrt();
+            if large_arc == sweep {
+                coef = -coef;
+            }
+            (u, v)
+        };
+        // coef times u_dir and v_dir is never more than 1, so neither of these can be bigger than the radii
+        let center = rotated(D2::from((coef * v_dir * rx, -coef * u_dir * ry)), rotation)?.plus(middle)?;
+        let start_angle = D2::from((u - coef * v_dir, v + coef * u_dir)).to_angle();
+        let end_angle = D2::from((-u - coef * v_dir, -v + coef * u_dir)).to_angle();
+        let mut sweep_angle = end_angle - start_angle;
+        if !sweep && sweep_angle > Angle::zero() {
+            sweep_angle -= Angle::two_pi();
+        } else if sweep && sweep_angle < Angle::zero() {
+            sweep_angle += Angle::two_pi();
+        }
+
+        // one cubic for every quarter turn or less, on the unit circle first
+        let pieces = (sweep_angle.val.abs() / Angle::half_pi().val).ceil().to_num::<i32>().max(1);
+        let step = Angle::from(sweep_angle.val / I32F32::from_num(pieces));
+        let quarter = Angle::from(step.val / I32F32::from_num(4));
+        let k = D1::from(quarter.sin() / quarter.cos()) * D1::from(4) / D1::from(3);
+        let on_ellipse = |pt: D2<D1>| rotated(pt.times(D2::from((rx, ry)))?, rotation)?.plus(center);
+        let unit = |angle: Angle| -> D2<D1> { D2::from((D1::from(angle.cos()), D1::from(angle.sin()))) };
+        let mut angle = start_angle;
+        for i in 0..pieces {
+            let next = if i == pieces - 1 { start_angle + sweep_angle } else { angle + step };
+            let (p0, p3) = (unit(angle), unit(next));
+            let control1 = p0 + D2::from((-p0.y, p0.x)) * D2::from(k);
+            let control2 = p3 - D2::from((-p3.y, p3.x)) * D2::from(k);
+            // the last point is exactly `end` so rounding never leaves a gap
+            let last = if i == pieces - 1 { end } else { on_ellipse(p3)? };
+            self.cubic_to(on_ellipse(control1)?, on_ellipse(control2)?, last)?;
+            angle = next;
+        }
+        Ok(())
+    }
+}
+
+/// the `d` attribute of a `<path>`
+fn parse_path_data(data: &str, transform: Transform) -> Result<Path, String> {
+    let mut builder = PathBuilder::new(transform);
+    let mut scanner = Scanner::new(data);
+    let mut command = None;
+    // the second control point of the last curve, reflected by the smooth curve commands
+    let mut last_cubic: Option<D2<D1>> = None;
+    let mut last_quad: Option<D2<D1>> = None;
+    loop {
+        scanner.skip_separators();
+        let Some(&next) = scanner.bytes.get(scanner.pos) else {
+            return Ok(builder.path)
+        };
+        if next.is_ascii_alphabetic() {
+            scanner.pos += 1;
+            command = Some(next);
+        }
+        let Some(c) = command else {
+            return Err(format!("path data has to start with a command, not {:?}", next as char))
+        };
+        let relative = c.is_ascii_lowercase();
+        let origin = if relative { builder.current } else { D2::zero() };
+        let point = |scanner: &mut Scanner| -> Result<D2<D1>, String> {
+            let x = scanner.number()?.ok_or_else(|| format!("missing x in {}", c as char))?;
+            let y = scanner.number()?.ok_or_else(|| format!("missing y in {}", c as char))?;
+            origin.plus(D2::from((x, y)))
+        };
+        let (cubic, quad) = match c.to_ascii_uppercase() {
+            b'M' => {
+                builder.move_to(point(&mut scanner)?)?;
+                // more coords after a move are lines
+                command = Some(if relative { b'l' } else { b'L' });
+                (None, None)
+            }
+            b'L' => {
+                builder.line_to(point(&mut scanner)?)?;
+                (None, None)
+            }
+            b'H' | b'V' => {
+                let val = scanner.number()?.ok_or_else(|| format!("missing number in {}", c as char))?;
+                let current = builder.current;
+                let pt = if c.eq_ignore_ascii_case(&b'H') {
+                    D2::from((origin.x.plus(val)?, current.y))
+                } else {
+                    D2::from((current.x, origin.y.plus(val)?))
+                };
+                builder.line_to(pt)?;
+                (None, None)
+            }
+            b'C' => {
+                let (control1, control2, end) = (point(&mut scanner)?, point(&mut scanner)?, point(&mut scanner)?);
+                builder.cubic_to(control1, control2, end)?;
+                (Some(control2), None)
+            }
+            b'S' => {
+                let current = builder.current;
+                let control1 = match last_cubic {
+                    Some(control) => current.times(D2::two())?.minus(control)?,
+                    None => current,
+                };
+                let (control2, end) = (point(&mut scanner)?, point(&mut scanner)?);
+                builder.cubic_to(control1, control2, end)?;
+                (Some(control2), None)
+            }
+            b'Q' => {
+                let (control, end) = (point(&mut scanner)?, point(&mut scanner)?);
+                builder.quad_to(control, end)?;
+                (None, Some(control))
+            }
+            b'T' => {
+                let current = builder.current;
+                let control = match last_quad {
+                    Some(control) => current.times(D2::two())?.minus(control)?,
+                    None => current,
+                };
+                builder.quad_to(control, point(&mut scanner)?)?;
+                (None, Some(control))
+            }
+            b'A' => {
+                let missing = || format!("missing number in {}", c as char);
+                let rx = scanner.number()?.ok_or_else(missing)?;
+                let ry = scanner.number()?.ok_or_else(missing)?;
+                let rotation = radians(scanner.number()?.ok_or_else(missing)?);
+                let large_arc = scanner.flag()?;
+                let sweep = scanner.flag()?;
+                let end = point(&mut scanner)?;
+                builder.arc_to(D2::from((rx, ry)), rotation, large_arc, sweep, end)?;
+                (None, None)
+            }
+            b'Z' => {
+                builder.close();
+                // z takes no numbers, so anything after it needs its own command
+                command = None;
+                (None, None)
+            }
+            _ => return Err(format!("unknown path command {:?}", c as char)),
+        };
+        last_cubic = cubic;
+        last_quad = quad;
+    }
+}
+
+/// how far from the origin shapes can end up, far enough that flattening and drawing them can't overflow
+const MAX_COORD: D1 = D1::new_const("1000000000");
+
+fn too_big() -> String {
+    "numbers are too big".to_owned()
+}
+
+/// fixed point math that reports overflow instead of panicking, svg files can hold any numbers
+trait CheckedMath: Sized {
+    fn plus(self, other: Self) -> Result<Self, String>;
+    fn minus(self, other: Self) -> Result<Self, String>;
+    fn times(self, other: Self) -> Result<Self, String>;
+}
+impl CheckedMath for D1 {
+    fn plus(self, other: Self) -> Result<Self, String> {
+        checked(self.val.checked_add(other.val))
+    }
+    fn minus(self, other: Self) -> Result<Self, String> {
+        checked(self.val.checked_sub(other.val))
+    }
+    fn times(self, other: Self) -> Result<Self, String> {
+        checked(self.val.checked_mul(other.val))
+    }
+}
+/// component-wise
+impl CheckedMath for D2<D1> {
+    fn plus(self, other: Self) -> Result<Self, String> {
+        Ok(D2::from((self.x.plus(other.x)?, self.y.plus(other.y)?)))
+    }
+    fn minus(self, other: Self) -> Result<Self, String> {
+        Ok(D2::from((self.x.minus(other.x)?, self.y.minus(other.y)?)))
+    }
+    fn times(self, other: Self) -> Result<Self, String> {
+        Ok(D2::from((self.x.times(other.x)?, self.y.times(other.y)?)))
+    }
+}
+fn over(a: D1, b: D1) -> Result<D1, String> {
+    checked(a.val.checked_div(b.val))
+}
+/// the smallest number is left out too, so taking the abs of anything can't overflow
+fn checked(val: Option<I36F28>) -> Result<D1, String> {
+    val.filter(|val| *val != I36F28::MIN).map(|val| D1 { val }).ok_or_else(too_big)
+}
+/// `pt` turned counterclockwise by `angle`
+fn rotated(pt: D2<D1>, angle: Angle) -> Result<D2<D1>, String> {
+    let (sin, cos) = (D1::from(angle.sin()), D1::from(angle.cos()));
+    Ok(D2::from((pt.x.times(cos)?.minus(pt.y.times(sin)?)?, pt.x.times(sin)?.plus(pt.y.times(cos)?)?)))
+}
+
+/// reads svg number lists, which can be split by spaces, commas or nothing at all like `1.5.5-2`
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+impl<'a> Scanner<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { bytes: text.as_bytes(), pos: 0 }
+    }
+    fn skip_separators(&mut self) {
+        while self.bytes.get(self.pos).is_some_and(|b| b.is_ascii_whitespace() || *b == b',') {
+            self.pos += 1;
+        }
+    }
+    /// None if the next thing isn't a number
+    fn number(&mut self) -> Result<Option<D1>, String> {
+        self.skip_separators();
+        let start = self.pos;
+        let digits = |scanner: &mut Self| {
+            let from = scanner.pos;
+            while scanner.bytes.get(scanner.pos).is_some_and(|b| b.is_ascii_digit()) {
+                scanner.pos += 1;
+            }
+            scanner.pos > from
+        };
+        if matches!(self.bytes.get(self.pos), Some(b'+' | b'-')) {
+            self.pos += 1;
+        }
+        let mut any = digits(self);
+        if self.bytes.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            any |= digits(self);
+        }
+        if !any {
+            self.pos = start;
+            return Ok(None)
+        }
+        // an exponent only counts if it has digits, otherwise the e belongs to whatever comes next
+        let before_exponent = self.pos;
+        if matches!(self.bytes.get(self.pos), Some(b'e' | b'E')) {
+            self.pos += 1;
+            if matches!(self.bytes.get(self.pos), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            if !digits(self) {
+                self.pos = before_exponent;
+            }
+        }
+        let text = std::str::from_utf8(&self.bytes[start..self.pos]).unwrap_or_default();
+        let val: f64 = text.parse().map_err(|_| format!("bad number {:?}", text))?;
+        checked(I36F28::checked_from_num(val)).map(Some).map_err(|_| format!("{} is too big", text))
+    }
+    /// the large arc and sweep flags of arcs, which don't need anything between them
+    fn flag(&mut self) -> Result<bool, String> {
+        self.skip_separators();
+        let flag = match self.bytes.get(self.pos) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err("arc flags have to be 0 or 1".to_owned()),
+        };
+        self.pos += 1;
+        Ok(flag)
+    }
+}
+
+/// the start of an element
+struct Tag<'a> {
+    /// without a namespace prefix
+    name: &'a str,
+    attributes: Vec<(&'a str, String)>,
+    self_closing: bool,
+    line: usize,
+}
+impl Tag<'_> {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(key, _)| *key == name).map(|(_, value)| value.as_str())
+    }
+}
+
+enum XmlToken<'a> {
+    Start(Tag<'a>),
+    /// name and line of an end tag
+    End(&'a str, usize),
+}
+
+/// just enough xml to find the elements and attributes of an svg, text is skipped
+struct XmlReader<'a> {
+    text: &'a str,
+    pos: usize,
+}
+impl<'a> XmlReader<'a> {
+    fn line(&self, pos: usize) -> usize {
+        self.text[..pos].matches('\n').count() + 1
+    }
+    fn error(&self, pos: usize, message: &str) -> SvgImportError {
+        syntax(self.line(pos), message.to_owned())
+    }
+    /// moves past `end`, which has to come after the current position
+    fn skip_past(&mut self, start: usize, end: &str) -> Result<(), SvgImportError> {
+        match self.text[self.pos..].find(end) {
+            Some(i) => {
+                self.pos += i + end.len();
+                Ok(())
+            }
+            None => Err(self.error(start, &format!("missing {}", end))),
+        }
+    }
+    fn next_token(&mut self) -> Result<Option<XmlToken<'a>>, SvgImportError> {
+        let text = self.text;
+        loop {
+            let Some(i) = text[self.pos..].find('<') else {
+                self.pos = text.len();
+                return Ok(None)
+            };
+            let start = self.pos + i;
+            self.pos = start;
+            let rest = &text[start..];
+            if rest.starts_with("<!--") {
+                self.skip_past(start, "-->")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.skip_past(start, "]]>")?;
+            } else if rest.starts_with("<?") {
+                self.skip_past(start, "?>")?;
+            } else if rest.starts_with("<!") {
+                self.skip_past(start, ">")?;
+            } else if let Some(rest) = rest.strip_prefix("</") {
+                self.skip_past(start, ">")?;
+                let name = rest[..rest.find('>').unwrap_or(0)].trim();
+                return Ok(Some(XmlToken::End(local_name(name), self.line(start))))
+            } else {
+                return self.start_tag(start).map(|tag| Some(XmlToken::Start(tag)))
+            }
+        }
+    }
+    fn start_tag(&mut self, start: usize) -> Result<Tag<'a>, SvgImportError> {
+        let text = self.text;
+        let name_end = text[start + 1..]
+            .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .map_or(text.len(), |i| start + 1 + i);
+        let name = &text[start + 1..name_end];
+        if name.is_empty() {
+            return Err(self.error(start, "missing element name"))
+        }
+        let mut tag = Tag { name: local_name(name), attributes: Vec::new(), self_closing: false, line: self.line(start) };
+        self.pos = name_end;
+        loop {
+            let rest = text[self.pos..].trim_start();
+            self.pos = text.len() - rest.len();
+            if rest.starts_with("/>") {
+                self.pos += 2;
+                tag.self_closing = true;
+                return Ok(tag)
+            }
+            if rest.starts_with('>') {
+                self.pos += 1;
+                return Ok(tag)
+            }
+            let key_len = rest.find(|c: char| c.is_whitespace() || c == '=' || c == '>' || c == '/').unwrap_or(rest.len());
+            if key_len == 0 {
+                return Err(self.error(start, &format!("unfinished <{}>", name)))
+            }
+            let key = &rest[..key_len];
+            let after_key = rest[key_len..].trim_start();
+            let Some(after_equals) = after_key.strip_prefix('=') else {
+                return Err(self.error(start, &format!("attribute {} has no value", key)))
+            };
+            let after_equals = after_equals.trim_start();
+            let Some(quote) = after_equals.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+                return Err(self.error(start, &format!("value of {} needs quotes", key)))
+            };
+            let Some(value_len) = after_equals[1..].find(quote) else {
+                return Err(self.error(start, &format!("value of {} is never closed", key)))
+            };
+            tag.attributes.push((key, unescape(&after_equals[1..1 + value_len])));
+            self.pos = text.len() - after_equals.len() + value_len + 2;
+        }
+    }
+}
+
+/// `svg:path` is the same as `path`
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn unescape(value: &str) -> String {
+    if !value.contains('&') {
+        return value.to_owned()
+    }
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(i) = rest.find('&') {
+        out += &rest[..i];
+        rest = &rest[i..];
+        let Some(end) = rest.find(';') else {
+            break
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity.strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(|dec| dec.parse()))
+                .and_then(|code| code.ok())
+                .and_then(char::from_u32),
+        };
+        match c {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out + rest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(body: &str) -> Result<Vec<SvgShape>, SvgImportError> {
+        parse_svg(&format!("<svg xmlns=\"http://www.w3.org/2000/svg\">\n{}\n</svg>", body), D1::new_const("0.1"))
+    }
+    fn path(d: &str) -> Vec<SvgShape> {
+        parse(&format!("<path d=\"{}\"/>", d)).unwrap()
+    }
+    fn d2(x: f32, y: f32) -> D2<D1> {
+        D2::from((D1::from(x), D1::from(y)))
+    }
+    fn polyline(shape: &SvgShape) -> (Vec<(f32, f32)>, bool) {
+        match shape {
+            SvgShape::Polyline { points, closed } => (points.iter().map(|pt| pt.to_f32()).collect(), *closed),
+            SvgShape::Shape(shape) => panic!("expected a polyline, got {:?}", shape),
+        }
+    }
+    fn close_to(a: (f32, f32), b: (f32, f32)) -> bool {
+        (a.0 - b.0).abs() < 0.01 && (a.1 - b.1).abs() < 0.01
+    }
+    fn syntax_line(result: Result<Vec<SvgShape>, SvgImportError>) -> usize {
+        match result {
+            Err(SvgImportError::Syntax { line, .. }) => line,
+            other => panic!("expected a syntax error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn lines_and_y_flip() {
+        let shapes = path("M0 0 L10 0 V10 H0 Z");
+        assert_eq!(shapes.len(), 1);
+        assert_eq!(polyline(&shapes[0]), (vec![(0., 0.), (10., 0.), (10., -10.), (0., -10.)], true));
+    }
+
+    #[test]
+    fn relative_commands() {
+        let shapes = path("m5 5 l10 0 v10 h-10 z");
+        assert_eq!(polyline(&shapes[0]), (vec![(5., -5.), (15., -5.), (15., -15.), (5., -15.)], true));
+    }
+
+    #[test]
+    fn numbers_after_move_are_lines() {
+        let shapes = path("M0 0 10 0 10 10");
+        assert_eq!(polyline(&shapes[0]), (vec![(0., 0.), (10., 0.), (10., -10.)], false));
+        // numbers can run together
+        let shapes = path("M0,0L1.5.5-2-2");
+        assert_eq!(polyline(&shapes[0]), (vec![(0., 0.), (1.5, -0.5), (-2., 2.)], false));
+    }
+
+    #[test]
+    fn smooth_curves_reflect_the_last_control_point() {
+        assert_eq!(path("M0 0 C0 10 10 10 10 0 S20 -10 20 0"), path("M0 0 C0 10 10 10 10 0 C10 -10 20 -10 20 0"));
+        assert_eq!(path("M0 0 c0 10 10 10 10 0 s10 -10 10 0"), path("M0 0 C0 10 10 10 10 0 C10 -10 20 -10 20 0"));
+        assert_eq!(path("M0 0 Q5 10 10 0 T20 0"), path("M0 0 Q5 10 10 0 Q15 -10 20 0"));
+        assert_eq!(path("M0 0 q5 10 10 0 t10 0"), path("M0 0 Q5 10 10 0 Q15 -10 20 0"));
+        // without a curve before, the control point is the current point
+        assert_eq!(path("M0 0 L5 0 S10 5 15 0"), path("M0 0 L5 0 C5 0 10 5 15 0"));
+    }
+
+    #[test]
+    fn curves_end_on_their_end_point() {
+        let (points, _) = polyline(&path("M0 0 C0 10 10 10 10 0")[0]);
+        assert!(points.len() > 2);
+        assert_eq!(*points.last().unwrap(), (10., 0.));
+        // the middle of this curve is at y = 7.5, which is -7.5 after the flip
+        assert!(points.iter().any(|pt| pt.1 < -7.));
+        assert!(points.iter().all(|pt| pt.1 >= -7.6 && pt.1 <= 0.));
+    }
+
+    #[test]
+    fn arcs() {
+        let (points, _) = polyline(&path("M0 0 A10 10 0 0 1 20 0")[0]);
+        assert_eq!(*points.last().unwrap(), (20., 0.));
+        for pt in &points {
+            let dist = ((pt.0 - 10.).powi(2) + pt.1.powi(2)).sqrt();
+            assert!((dist - 10.).abs() < 0.2, "{:?} is not on the circle", pt);
+        }
+        // positive sweep in svg goes through negative y, which is up after the flip
+        assert!(points.iter().any(|pt| close_to(*pt, (10., 10.)) || pt.1 > 9.9));
+        let (other_way, _) = polyline(&path("M0 0 A10 10 0 0 0 20 0")[0]);
+        assert!(other_way.iter().all(|pt| pt.1 <= 0.01));
+    }
+
+    #[test]
+    fn arcs_with_radii_too_small_or_too_big() {
+        // scaled up until they reach, a half circle around the middle
+        let (points, _) = polyline(&path("M0 0 A0.0001 0.0001 0 0 1 1000 0")[0]);
+        assert_eq!(*points.last().unwrap(), (1000., 0.));
+        for pt in &points {
+            let dist = ((pt.0 - 500.).powi(2) + pt.1.powi(2)).sqrt();
+            assert!((dist - 500.).abs() < 1., "{:?} is not on the circle", pt);
+        }
+        let (points, _) = polyline(&path("M0 0 A100000 100000 0 0 1 0.001 0")[0]);
+        assert!(close_to(*points.last().unwrap(), (0.001, 0.)));
+        // zero radius is a straight line
+        assert_eq!(polyline(&path("M0 0 A0 5 0 0 1 10 0")[0]).0, vec![(0., 0.), (10., 0.)]);
+    }
+
+    #[test]
+    fn transforms() {
+        let shapes = parse(r#"<g transform="translate(10,5)"><rect width="2" height="3" transform="scale(2)"/></g>"#).unwrap();
+        assert_eq!(shapes, vec![SvgShape::Shape(Shape2D::Rect((d2(10., -11.), d2(14., -5.))))]);
+
+        let shapes = parse(r#"<circle cx="1" cy="1" r="2" transform="scale(3)"/>"#).unwrap();
+        assert_eq!(shapes, vec![SvgShape::Shape(Shape2D::Circle((d2(3., -3.), D1::from(6))))]);
+
+        // rotated rects become polygons
+        let shapes = parse(r#"<rect width="2" height="1" transform="rotate(90)"/>"#).unwrap();
+        let (points, closed) = polyline(&shapes[0]);
+        assert!(closed);
+        let expected = [(0., 0.), (0., -2.), (-1., -2.), (-1., 0.)];
+        for (pt, expected) in points.iter().zip(expected) {
+            assert!(close_to(*pt, expected), "{:?} should be {:?}", pt, expected);
+        }
+
+        let shapes = parse(r#"<line x1="1" y1="0" x2="2" y2="0" transform="rotate(90, 1, 0) matrix(1 0 0 1 0 0)"/>"#).unwrap();
+        let (points, _) = polyline(&shapes[0]);
+        assert!(close_to(points[0], (1., 0.)) && close_to(points[1], (1., -1.)), "{:?}", points);
+
+        let shapes = parse(r#"<polyline points="0,0 0,1" transform="skewX(45)"/>"#).unwrap();
+        let (points, _) = polyline(&shapes[0]);
+        assert!(close_to(points[1], (1., -1.)), "{:?}", points);
+    }
+
+    #[test]
+    fn skips_things_that_are_not_drawn() {
+        let shapes = parse(r#"
+            <defs><rect width="5" height="5"/><g><circle r="3"/></g></defs>
+            <rect width="5" height="5" display="none"/>
+            <g display="none"><circle r="3"/></g>
+            <!-- <circle r="3"/> -->
+            <text x="1">not a shape</text>
+            <circle r="1"/>
+        "#).unwrap();
+        assert_eq!(shapes, vec![SvgShape::Shape(Shape2D::Circle((d2(0., 0.), D1::one())))]);
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert_eq!(syntax_line(parse("<g>")), 3);
+        assert_eq!(syntax_line(parse("\n</g>")), 3);
+        assert_eq!(syntax_line(parse(r#"<path d="10 10"/>"#)), 2);
+        assert_eq!(syntax_line(parse(r#"<path d="M0 0 A1 1 0 2 1 5 5"/>"#)), 2);
+        assert_eq!(syntax_line(parse(r#"<path d="M0 0 L5"/>"#)), 2);
+        assert_eq!(syntax_line(parse(r#"<path d="M0 0 X5"/>"#)), 2);
+        assert_eq!(syntax_line(parse("<rect width=5/>")), 2);
+        assert_eq!(syntax_line(parse(r#"<rect width="5"#)), 2);
+        assert_eq!(syntax_line(parse(r#"<rect transform="spin(5)"/>"#)), 2);
+        assert_eq!(syntax_line(parse(r#"<rect width="a"/>"#)), 2);
+        assert!(matches!(parse_svg("<svg><!-- never closed", D1::one()), Err(SvgImportError::Syntax { .. })));
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        assert_eq!(syntax_line(parse(r#"<rect width="100000" height="10" transform="scale(1000000)"/>"#)), 2);
+        assert_eq!(syntax_line(parse(r#"<path d="M0 0 L1e30 0"/>"#)), 2);
+        assert_eq!(syntax_line(parse(r#"<path d="M30000000000 0 l30000000000 0"/>"#)), 2);
+        assert_eq!(syntax_line(parse(r#"<g transform="scale(100000)"><g transform="scale(100000)"><rect width="1" height="1"/></g></g>"#)), 2);
+        assert_eq!(syntax_line(parse(r#"<line y2="100" transform="skewX(90)"/>"#)), 2);
+        assert_eq!(syntax_line(parse(r#"<path d="M0 0 A0.000001 0.000001 0 0 1 30000000000 0"/>"#)), 2);
+        // big but fine
+        assert!(parse(r#"<line x2="1" transform="rotate(3600000000)"/>"#).is_ok());
+    }
+}
@@ -0,0 +1,411 @@
+use std::ops::Range;
+
+use serde::{Serialize, Deserialize};
+
+use crate::coordinates::*;
+use crate::draw_functions::{draw_text, Color};
+use crate::font::Font;
+use crate::render_target::RenderTarget;
+
+/// how lines of text line up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TextAlign {
+    /// the text starts at the anchor or the left of the box
+    #[default]
+    Left,
+    Center,
+    /// the text ends at the anchor or the right of the box
+    Right,
+    /// spaces are stretched so every line but the last of a paragraph fills the box.
+    /// Text that isn't laid out in a box is drawn like [TextAlign::Left]
+    Justify,
+}
+impl TextAlign {
+    /// how far left of the anchor a line `width` wide starts
+    pub fn offset(&self, width: D1) -> D1 {
+        match self {
+            TextAlign::Left | TextAlign::Justify => D1::zero(),
+            TextAlign::Center => width / D1::two(),
+            TextAlign::Right => width,
+        }
+    }
+}
+
+/// empty space between the edges of the box and the text, in screen pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Padding {
+    pub left: u16,
+    pub right: u16,
+    pub top: u16,
+    pub bottom: u16,
+}
+impl Padding {
+    pub fn uniform(padding: u16) -> Self {
+        Self { left: padding, right: padding, top: padding, bottom: padding }
+    }
+}
+
+/// settings for [TextLayout::new]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextLayoutOptions {
+    /// size of one font pixel on screen
+    pub pixel_dim: D2<u16>,
+    /// color of text outside of any color span
+    pub color: Color,
+    pub align: TextAlign,
+    /// centers the lines vertically in the box instead of starting at the top
+    pub vertical_center: bool,
+    pub padding: Padding,
+    /// reads color spans like `{red}warning{/}`, see [TextLayout::new]. Otherwise braces are just text
+    pub markup: bool,
+}
+impl TextLayoutOptions {
+    pub fn new(pixel_dim: D2<u16>, color: Color) -> Self {
+        Self {
+            pixel_dim,
+            color,
+            align: TextAlign::Left,
+            vertical_center: false,
+            padding: Padding::default(),
+            markup: true,
+        }
+    }
+    pub fn with_align(mut self, align: TextAlign) -> Self {
+        self.align = align;
+        self
+    }
+    pub fn with_vertical_center(mut self, vertical_center: bool) -> Self {
+        self.vertical_center = vertical_center;
+        self
+    }
+    pub fn with_padding(mut self, padding: Padding) -> Self {
+        self.padding = padding;
+        self
+    }
+    pub fn with_markup(mut self, markup: bool) -> Self {
+        self.markup = markup;
+        self
+    }
+}
+
+/// one character of a [TextLayout]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionedGlyph {
+    pub c: char,
+    /// bottom left of the character's line (the pen position `descent` below the baseline), in screen pixels rel to the bottom left of the box
+    pub coords: D2<i32>,
+    pub color: Color,
+}
+
+/// one line of a [TextLayout]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineBox {
+    /// bottom left in screen pixels rel to the bottom left of the box
+    pub coords: D2<i32>,
+    pub size: D2<i32>,
+    /// the characters of this line in [TextLayout::glyphs]
+    pub glyphs: Range<usize>,
+    /// false if the line doesn't fit in the box, it is not drawn
+    pub visible: bool,
+}
+
+/// text broken into lines that fit a box, made with [TextLayout::new] and drawn with [TextLayout::draw]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLayout {
+    pub glyphs: Vec<PositionedGlyph>,
+    pub lines: Vec<LineBox>,
+    /// true if some of the text didn't fit in the box
+    pub overflow: bool,
+    /// width of the widest line and height of all the lines together, in screen pixels
+    pub extent: D2<i32>,
+    /// size of the box the text was laid out in
+    pub size: D2<u16>,
+    pub pixel_dim: D2<u16>,
+}
+
+/// a character and the color it is drawn in
+type StyledChar = (char, Color);
+
+impl TextLayout {
+    /// breaks `text` into lines that fit into a box `size` pixels big. Lines are broken at spaces and '\n',
+    /// words too long for a line by themselves are split between lines.
+    ///
+    /// With markup on, `{name}` switches to the color [Color::from_name] gives for `name` (like `{red}` or `{#ff8000}`)
+    /// until the matching `{/}`. Spans can be nested and `{{` is a literal brace. Only `{` needs escaping,
+    /// `}}` is two closing braces. Anything in braces that isn't a color is left as it is
+    pub fn new(text: &str, font: &Font, size: D2<u16>, options: &TextLayoutOptions) -> Self {
+        let pixel_dim: D2<i32> = D2::from((options.pixel_dim.x as i32, options.pixel_dim.y as i32));
+        let padding = options.padding;
+        let content_width = (size.x as i32 - padding.left as i32 - padding.right as i32).max(0);
+        let width = |chars: &[StyledChar]| {
+            let line: String = chars.iter().map(|(c, _)| *c).collect();
+            font.text_width(&line) as i32 * pixel_dim.x
+        };
+
+        let styled = if options.markup {
+            parse_markup(text, options.color)
+        } else {
+            text.chars().map(|c| (c, options.color)).collect()
+        };
+        // (line, is the last line of its paragraph)
+        let mut lines: Vec<(Vec<StyledChar>, bool)> = Vec::new();
+        for paragraph in styled.split(|(c, _)| *c == '\n') {
+            let mut line: Vec<StyledChar> = Vec::new();
+            for word in paragraph.split(|(c, _)| *c == ' ').filter(|word| !word.is_empty()) {
+                let mut word = word;
+                loop {
+                    let mut candidate = line.clone();
+                    if !candidate.is_empty() {
+                        candidate.push((' ', options.color));
+                    }
+                    candidate.extend_from_slice(word);
+                    if width(&candidate) <= content_width {
+                        line = candidate;
+                        break
+                    }
+                    if !line.is_empty() {
+                        lines.push((std::mem::take(&mut line), false));
+                        continue
+                    }
+                    // too long for a line by itself, put as much as fits (at least one character) on this line
+                    let fit = (1..word.len()).rev().find(|&len| width(&word[..len]) <= content_width).unwrap_or(1);
+                    if fit == word.len() {
+                        line = word.to_vec();
+                        break
+                    }
+                    lines.push((word[..fit].to_vec(), false));
+                    word = &word[fit..];
+                }
+            }
+            lines.push((line, true));
+        }
+
+        let line_height = font.height() as i32 * pixel_dim.y;
+        let line_step = font.line_height as i32 * pixel_dim.y;
+        let total_height = if lines.is_empty() { 0 } else { line_height + (lines.len() as i32 - 1) * line_step };
+        let content_top = size.y as i32 - padding.top as i32;
+        let content_height = content_top - padding.bottom as i32;
+        let top = if options.vertical_center && total_height < content_height {
+            content_top - (content_height - total_height) / 2
+        } else {
+            content_top
+        };
+
+        let mut layout = TextLayout {
+            glyphs: Vec::new(),
+            lines: Vec::with_capacity(lines.len()),
+            overflow: false,
+            extent: D2::from((0, total_height)),
+            size,
+            pixel_dim: options.pixel_dim,
+        };
+        for (i, (line, paragraph_end)) in lines.iter().enumerate() {
+            let line_width = width(line);
+            let free = content_width - line_width;
+            let x = padding.left as i32 + match options.align {
+                TextAlign::Left | TextAlign::Justify => 0,
+                TextAlign::Center => free / 2,
+                TextAlign::Right => free,
+            }.max(0);
+            let y = top - i as i32 * line_step - line_height;
+            let visible = free >= 0 && y >= padding.bottom as i32;
+            layout.overflow |= !visible;
+            layout.extent.x = layout.extent.x.max(line_width);
+
+            // justified lines spread the free space over their spaces
+            let spaces = line.iter().filter(|(c, _)| *c == ' ').count() as i32;
+            let justify = options.align == TextAlign::Justify && !paragraph_end && spaces > 0 && free > 0;
+            let mut spaces_seen = 0;
+            let string: String = line.iter().map(|(c, _)| *c).collect();
+            let start = layout.glyphs.len();
+            for ((c, pen), (_, color)) in font.pen_positions(&string).zip(line) {
+                if c == ' ' {
+                    spaces_seen += 1;
+                }
+                let stretch = if justify { free * spaces_seen / spaces } else { 0 };
+                layout.glyphs.push(PositionedGlyph { c, coords: D2::from((x + pen * pixel_dim.x + stretch, y)), color: *color });
+            }
+            let size = D2::from((if justify { content_width } else { line_width }, line_height));
+            layout.lines.push(LineBox { coords: D2::from((x, y)), size, glyphs: start..layout.glyphs.len(), visible });
+        }
+        layout
+    }
+
+    /// draws every line that fits, `corner` is the bottom left of the box in screen pixels rel to the bottom left of the screen.
+    /// `font` has to be the font the layout was made with
+    pub fn draw<T: RenderTarget + ?Sized>(&self, img: &mut T, corner: D2<u16>, font: &Font) {
+        let mut buffer = [0; 4];
+        for line in self.lines.iter().filter(|line| line.visible) {
+            for glyph in &self.glyphs[line.glyphs.clone()] {
+                let x = corner.x as i32 + glyph.coords.x;
+                let y = corner.y as i32 + glyph.coords.y;
+                if glyph.c == ' ' || x < 0 || y < 0 || x > u16::MAX as i32 || y > u16::MAX as i32 {
+                    continue
+                }
+                let letter = glyph.c.encode_utf8(&mut buffer);
+                draw_text(img, D2::from((x as u16, y as u16)), letter, font, glyph.color, self.pixel_dim, false, false);
+            }
+        }
+    }
+}
+
+/// splits `{color}..{/}` spans out of `text`, see [TextLayout::new]
+fn parse_markup(text: &str, color: Color) -> Vec<StyledChar> {
+    let mut styled = Vec::with_capacity(text.len());
+    // the first color is never popped
+    let mut colors = vec![color];
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        let current = colors.last().copied().unwrap_or(color);
+        if c != '{' {
+            styled.push((c, current));
+            continue
+        }
+        if let Some(after) = rest.strip_prefix('{') {
+            styled.push(('{', current));
+            rest = after;
+            continue
+        }
+        match rest.split_once('}') {
+            Some(("/", after)) if colors.len() > 1 => {
+                colors.pop();
+                rest = after;
+            }
+            Some((name, after)) if Color::from_name(name).is_some() => {
+                colors.extend(Color::from_name(name));
+                rest = after;
+            }
+            // not a tag, keep the brace as text
+            _ => styled.push(('{', current)),
+        }
+    }
+    styled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lay_out(text: &str, size: (u16, u16), options: TextLayoutOptions) -> TextLayout {
+        TextLayout::new(text, &Font::default_5x5(), D2::from(size), &options)
+    }
+    fn default_options() -> TextLayoutOptions {
+        TextLayoutOptions::new(D2::from((1, 1)), Color::white())
+    }
+    fn line_texts(layout: &TextLayout) -> Vec<String> {
+        layout.lines.iter().map(|line| layout.glyphs[line.glyphs.clone()].iter().map(|glyph| glyph.c).collect()).collect()
+    }
+    fn line_box(coords: (i32, i32), size: (i32, i32), glyphs: Range<usize>, visible: bool) -> LineBox {
+        LineBox { coords: D2::from(coords), size: D2::from(size), glyphs, visible }
+    }
+
+    // every default glyph is 5 pixels wide and 6 apart, lines are 5 tall and 6 apart
+
+    #[test]
+    fn wraps_at_spaces() {
+        let layout = lay_out("ab cd\nef", (20, 30), default_options());
+        assert_eq!(line_texts(&layout), ["ab", "cd", "ef"]);
+        assert_eq!(layout.lines, [
+            line_box((0, 25), (11, 5), 0..2, true),
+            line_box((0, 19), (11, 5), 2..4, true),
+            line_box((0, 13), (11, 5), 4..6, true),
+        ]);
+        let coords: Vec<(i32, i32)> = layout.glyphs.iter().map(|glyph| (glyph.coords.x, glyph.coords.y)).collect();
+        assert_eq!(coords, [(0, 25), (6, 25), (0, 19), (6, 19), (0, 13), (6, 13)]);
+        assert_eq!(layout.extent, D2::from((11, 17)));
+        assert!(!layout.overflow);
+    }
+
+    #[test]
+    fn splits_words_too_long_for_a_line() {
+        let layout = lay_out("abcdefg h", (20, 30), default_options());
+        assert_eq!(line_texts(&layout), ["abc", "def", "g h"]);
+        assert!(!layout.overflow);
+        // a box narrower than one character still gets one per line, but they don't fit
+        let layout = lay_out("ab", (3, 30), default_options());
+        assert_eq!(line_texts(&layout), ["a", "b"]);
+        assert!(layout.overflow && layout.lines.iter().all(|line| !line.visible));
+    }
+
+    #[test]
+    fn alignment_and_padding() {
+        let padding = Padding { left: 3, right: 1, top: 4, bottom: 0 };
+        let options = default_options().with_padding(padding);
+        let left = lay_out("ab", (24, 30), options);
+        assert_eq!(left.lines[0], line_box((3, 21), (11, 5), 0..2, true));
+        // 20 wide inside the padding, 9 left over
+        let center = lay_out("ab", (24, 30), options.with_align(TextAlign::Center));
+        assert_eq!(center.lines[0].coords, D2::from((7, 21)));
+        let right = lay_out("ab", (24, 30), options.with_align(TextAlign::Right));
+        assert_eq!(right.lines[0].coords, D2::from((12, 21)));
+        assert_eq!(right.glyphs[1].coords, D2::from((18, 21)));
+    }
+
+    #[test]
+    fn justify_stretches_all_but_the_last_line() {
+        let layout = lay_out("ab c de", (30, 30), default_options().with_align(TextAlign::Justify));
+        assert_eq!(line_texts(&layout), ["ab c", "de"]);
+        // "ab c" is 23 wide, the 7 left over go into its one space
+        let x: Vec<i32> = layout.glyphs.iter().map(|glyph| glyph.coords.x).collect();
+        assert_eq!(x, [0, 6, 19, 25, 0, 6]);
+        assert_eq!(layout.lines[0].size, D2::from((30, 5)));
+        assert_eq!(layout.lines[1].size, D2::from((11, 5)));
+        // no space to stretch, so it's left aligned
+        let layout = lay_out("abcdefg", (20, 30), TextLayoutOptions { align: TextAlign::Justify, ..default_options() });
+        assert_eq!(layout.glyphs[1].coords.x, 6);
+    }
+
+    #[test]
+    fn vertical_center() {
+        let layout = lay_out("ab", (20, 30), default_options().with_vertical_center(true));
+        // 25 pixels free, 12 above
+        assert_eq!(layout.lines[0].coords, D2::from((0, 13)));
+        let options = default_options().with_vertical_center(true).with_padding(Padding { top: 4, bottom: 2, ..Padding::default() });
+        let layout = lay_out("ab\ncd", (20, 30), options);
+        // 24 pixels inside the padding and 11 of text, the top line starts 6 below the padding
+        assert_eq!(layout.lines[0].coords, D2::from((0, 15)));
+        assert_eq!(layout.lines[1].coords, D2::from((0, 9)));
+        // text taller than the box starts at the top
+        let layout = lay_out("ab\ncd", (20, 8), TextLayoutOptions { vertical_center: true, ..default_options() });
+        assert_eq!(layout.lines[0].coords, D2::from((0, 3)));
+    }
+
+    #[test]
+    fn lines_below_the_box_overflow() {
+        let layout = lay_out("ab cd ef", (20, 12), default_options());
+        let visible: Vec<bool> = layout.lines.iter().map(|line| line.visible).collect();
+        assert_eq!(visible, [true, true, false]);
+        assert!(layout.overflow);
+        assert_eq!(layout.extent, D2::from((11, 17)));
+    }
+
+    #[test]
+    fn color_spans() {
+        let layout = lay_out("a{red}b{blue}c{/}d{/}e", (100, 30), default_options());
+        let colors: Vec<(char, Color)> = layout.glyphs.iter().map(|glyph| (glyph.c, glyph.color)).collect();
+        assert_eq!(colors, [
+            ('a', Color::white()),
+            ('b', Color::red()),
+            ('c', Color::blue()),
+            ('d', Color::red()),
+            ('e', Color::white()),
+        ]);
+    }
+
+    #[test]
+    fn braces_that_arent_spans_are_text() {
+        let white = Color::white();
+        let all_white = |text: &str| text.chars().map(|c| (c, white)).collect::<Vec<_>>();
+        assert_eq!(parse_markup("{nope}x", white), all_white("{nope}x"));
+        assert_eq!(parse_markup("{{red}", white), all_white("{red}"));
+        assert_eq!(parse_markup("a}}b", white), all_white("a}}b"));
+        assert_eq!(parse_markup("{/}{red", white), all_white("{/}{red"));
+        assert_eq!(parse_markup("{#ff0000}x{/}", white), [('x', Color::red())]);
+        // unclosed spans run to the end
+        assert_eq!(parse_markup("{red}x{{", white), [('x', Color::red()), ('{', Color::red())]);
+
+        let plain = lay_out("{red}x", (100, 30), default_options().with_markup(false));
+        assert_eq!(line_texts(&plain), ["{red}x"]);
+    }
+}
@@ -0,0 +1,184 @@
+use std::{collections::HashMap, error, fmt, io, path::Path};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::draw_functions::Color;
+use crate::image_io::load_image;
+use crate::vec2d::Vec2d;
+
+/// every atlas gets its own id so a handle from one atlas is never mistaken for a texture of another
+static NEXT_ATLAS_ID: AtomicU32 = AtomicU32::new(0);
+
+/// refers to a texture inside of the [TextureAtlas] that made it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextureHandle {
+    atlas: u32,
+    index: u32,
+}
+
+#[derive(Debug)]
+pub enum TextureError {
+    /// the handle was made by a different atlas
+    UnknownHandle(TextureHandle),
+    /// no texture was added with this name
+    UnknownName(String),
+    Io(io::Error),
+    /// ascii art used a character that isn't in the palette, line and column start at 1
+    UnknownChar { c: char, line: usize, column: usize },
+}
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextureError::UnknownHandle(handle) => write!(f, "texture {} does not belong to this atlas", handle.index),
+            TextureError::UnknownName(name) => write!(f, "no texture named {:?}", name),
+            TextureError::Io(e) => write!(f, "could not load texture: {}", e),
+            TextureError::UnknownChar { c, line, column } => write!(
+                f, "{:?} at line {} column {} is not in the palette", c, line, column
+            ),
+        }
+    }
+}
+impl error::Error for TextureError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            TextureError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+impl From<io::Error> for TextureError {
+    fn from(e: io::Error) -> Self {
+        TextureError::Io(e)
+    }
+}
+
+/// named textures of any size, drawn with [draw_texture](crate::draw_texture) or [Cam::draw_texture](crate::Cam::draw_texture).
+///
+/// Adding a texture with a name that is already used replaces it, handles to the old texture then point to the new one
+#[derive(Debug)]
+pub struct TextureAtlas {
+    id: u32,
+    textures: Vec<Vec2d<Color>>,
+    names: HashMap<String, TextureHandle>,
+}
+impl Default for TextureAtlas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl TextureAtlas {
+    pub fn new() -> Self {
+        Self {
+            id: NEXT_ATLAS_ID.fetch_add(1, Ordering::Relaxed),
+            textures: Vec::new(),
+            names: HashMap::new(),
+        }
+    }
+    /// an atlas with the textures that used to be built into `draw_texture`: "square", "window" and "corners",
+    /// drawn in `color`
+    pub fn with_builtin(color: Color) -> Self {
+        let mut atlas = Self::new();
+        let palette = [('#', color)];
+        for (name, art) in [("square", SQUARE), ("window", WINDOW), ("corners", CORNERS)] {
+            atlas.insert_ascii(name, art, &palette).expect("builtin textures only use #");
+        }
+        atlas
+    }
+
+    pub fn insert(&mut self, name: &str, texture: Vec2d<Color>) -> TextureHandle {
+        if let Some(&handle) = self.names.get(name) {
+            self.textures[handle.index as usize] = texture;
+            return handle
+        }
+        let handle = TextureHandle { atlas: self.id, index: self.textures.len() as u32 };
+        self.textures.push(texture);
+        self.names.insert(name.to_owned(), handle);
+        handle
+    }
+    /// any format [load_image] can read
+    pub fn load_file<P: AsRef<Path>>(&mut self, name: &str, path: P) -> Result<TextureHandle, TextureError> {
+        let texture = load_image(path)?;
+        Ok(self.insert(name, texture))
+    }
+    /// every character of `art` is one pixel, colored by its entry in `palette`. Spaces and '.' are transparent
+    /// unless the palette says otherwise.
+    ///
+    /// Blank lines at the start and end are skipped and the indentation every line shares is removed,
+    /// so the art can be written as an indented multiline string. Short lines are padded with transparent pixels
+    pub fn insert_ascii(&mut self, name: &str, art: &str, palette: &[(char, Color)]) -> Result<TextureHandle, TextureError> {
+        let lines: Vec<(usize, &str)> = art.lines().enumerate().collect();
+        let first = lines.iter().position(|(_, line)| !line.trim().is_empty()).unwrap_or(lines.len());
+        let last = lines.iter().rposition(|(_, line)| !line.trim().is_empty()).map_or(first, |i| i + 1);
+        let lines = &lines[first..last];
+        let indent = lines.iter()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(_, line)| line.chars().take_while(|c| c.is_whitespace()).count())
+            .min()
+            .unwrap_or(0);
+        let rows: Vec<(usize, Vec<char>)> = lines.iter()
+            .map(|(i, line)| (*i, line.trim_end().chars().skip(indent).collect()))
+            .collect();
+        let width = rows.iter().map(|(_, row)| row.len()).max().unwrap_or(0);
+
+        let mut texture = Vec2d::new_with_fill((width, rows.len()), |_| Color::new_transparent());
+        for (y, (line, row)) in rows.iter().enumerate() {
+            for (x, &c) in row.iter().enumerate() {
+                texture[(x, y)] = match palette.iter().find(|(key, _)| *key == c) {
+                    Some((_, color)) => *color,
+                    None if c == ' ' || c == '.' => Color::new_transparent(),
+                    None => return Err(TextureError::UnknownChar { c, line: line + 1, column: indent + x + 1 }),
+                };
+            }
+        }
+        Ok(self.insert(name, texture))
+    }
+
+    pub fn handle(&self, name: &str) -> Result<TextureHandle, TextureError> {
+        self.names.get(name).copied().ok_or_else(|| TextureError::UnknownName(name.to_owned()))
+    }
+    pub fn get(&self, handle: TextureHandle) -> Result<&Vec2d<Color>, TextureError> {
+        if handle.atlas != self.id {
+            return Err(TextureError::UnknownHandle(handle))
+        }
+        self.textures.get(handle.index as usize).ok_or(TextureError::UnknownHandle(handle))
+    }
+    pub fn len(&self) -> usize {
+        self.textures.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.textures.is_empty()
+    }
+}
+
+const SQUARE: &str = "
+    .........
+    .........
+    ..#####..
+    ..#...#..
+    ..#...#..
+    ..#...#..
+    ..#####..
+    .........
+    .........
+";
+const WINDOW: &str = "
+    .........
+    .#######.
+    .#..#..#.
+    .#..#..#.
+    .#######.
+    .#..#..#.
+    .#..#..#.
+    .#######.
+    .........
+";
+const CORNERS: &str = "
+    #.......#
+    .........
+    .........
+    .........
+    .........
+    .........
+    .........
+    .........
+    #........
+";